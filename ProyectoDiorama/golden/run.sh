@@ -0,0 +1,33 @@
+#!/bin/sh
+# Regresión de imágenes: renderiza escenas canónicas pequeñas con semilla fija
+# y las compara con las referencias de este directorio; falla si alguna
+# difiere o no existe. Lo mismo corre en `cargo test` (tests/golden.rs).
+# Con GOLDEN_UPDATE=1 en cambio reescribe las referencias con el render
+# actual (para un cambio visual buscado; revisa las imágenes antes de
+# commitearlas).
+#
+#   ./golden/run.sh                    # desde ProyectoDiorama/
+#   GOLDEN_UPDATE=1 ./golden/run.sh
+set -e
+cd "$(dirname "$0")/.."
+cargo build --release
+bin=target/release/ProyectoDiorama
+out=$(mktemp -d)
+trap 'rm -rf "$out"' EXIT
+
+render() {
+    name=$1
+    shift
+    echo "== $name"
+    if [ -n "$GOLDEN_UPDATE" ]; then
+        "$bin" --width 160 --height 120 --seed 7 --output "golden/$name.png" "$@"
+    else
+        "$bin" --width 160 --height 120 --seed 7 --output "$out/$name.png" --golden "golden/$name.png" "$@"
+    fi
+}
+
+render base
+render decorado --scene scenes/decorado.scene
+render espiral --scene scenes/espiral.scene
+render sombras_suaves --shadow-samples 8 --light-radius 2
+render gi --gi 16 --sky-light 0.5
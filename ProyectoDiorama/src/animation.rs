@@ -0,0 +1,74 @@
+use std::f32::consts::PI;
+use crate::math::Vec3;
+
+use crate::camera::Camera;
+use crate::light::Light;
+use crate::transform::Transform;
+
+/// Cámara para el cuadro `frame` de una vuelta completa (360°) alrededor
+/// del centro de `base`
+pub fn turntable_camera(base: &Camera, frame: u32, frames: u32) -> Camera {
+    let mut camera = base.clone();
+    let angle = 2.0 * PI * frame as f32 / frames.max(1) as f32;
+    camera.orbit(angle, 0.0);
+    camera
+}
+
+/// Transformación global del diorama: giro (fijo y animado) alrededor de
+/// un pivote, escala y desplazamiento. En vez de mover todos los objetos se
+/// lleva la cámara y la luz al espacio del modelo con la inversa, que da la
+/// misma imagen. Lo precalculado con la luz (lightmaps, fotones, caché de
+/// irradiancia) solo vale mientras el modelo no gira.
+#[derive(Debug, Clone, Copy)]
+pub struct ModelMotion {
+    /// Punto del modelo que queda fijo (p. ej. el centro de su base)
+    pub pivot: Vec3,
+    pub offset: Vec3,
+    /// Giro inicial en grados
+    pub yaw: f32,
+    pub scale: f32,
+    /// Grados por segundo
+    pub spin: f32,
+}
+
+impl ModelMotion {
+    pub fn is_animated(&self) -> bool {
+        self.spin != 0.0
+    }
+
+    fn at(&self, time: f32) -> Transform {
+        Transform::new(self.pivot + self.offset, self.yaw + self.spin * time, 0.0, 0.0, self.scale)
+    }
+
+    fn model_point(&self, transform: &Transform, p: Vec3) -> Vec3 {
+        transform.point_to_local(p) + self.pivot
+    }
+
+    /// Cámara equivalente en el espacio del modelo en el instante `time`
+    pub fn camera_at(&self, camera: &Camera, time: f32) -> Camera {
+        let t = self.at(time);
+        let model = Camera::new(
+            self.model_point(&t, camera.eye),
+            self.model_point(&t, camera.center),
+            t.inverse_rotate(camera.up),
+        );
+        // La lente escala con el modelo: se conserva el punto enfocado
+        let focus = self.model_point(&t, camera.eye + camera.forward * camera.focus_distance);
+        let focus_distance = (focus - model.eye).length();
+        Camera {
+            roll: camera.roll,
+            aperture: camera.aperture * focus_distance / camera.focus_distance.max(1e-6),
+            focus_distance,
+            ..model
+        }
+    }
+
+    /// Luz equivalente en el espacio del modelo en el instante `time`
+    pub fn light_at(&self, light: &Light, time: f32) -> Light {
+        let t = self.at(time);
+        Light::new(self.model_point(&t, light.position_at(time)), light.color, light.intensity)
+            .with_radius(light.radius / self.scale)
+            .with_shadows(light.casts_shadows)
+            .with_groups(light.groups)
+    }
+}
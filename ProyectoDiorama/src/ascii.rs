@@ -0,0 +1,33 @@
+use std::fmt::Write;
+
+use crate::math::Rgba;
+
+/// Tamaño en píxeles de una vista previa de `columns` columnas de terminal
+/// con la proporción de `width`×`height`. Cada carácter muestra dos píxeles
+/// (arriba y abajo) y mide el doble de alto que de ancho, así los píxeles
+/// quedan cuadrados; el alto siempre es par.
+pub fn preview_size(columns: u32, width: u32, height: u32) -> (usize, usize) {
+    let columns = columns.max(1) as usize;
+    let rows = (columns as f32 * height as f32 / width.max(1) as f32 / 2.0).round().max(1.0) as usize;
+    (columns, rows * 2)
+}
+
+/// Imagen (por filas, `height` par) como texto para una terminal con color
+/// de 24 bits: un medio bloque `▀` por cada par de píxeles, con el de arriba
+/// como color del carácter y el de abajo como fondo
+pub fn to_ansi(pixels: &[Rgba], width: usize, height: usize) -> String {
+    let mut out = String::with_capacity(width * height * 20);
+    for y in (0..height).step_by(2) {
+        for x in 0..width {
+            let top = pixels[y * width + x];
+            let bottom = pixels.get((y + 1) * width + x).copied().unwrap_or(top);
+            let _ = write!(
+                out,
+                "\x1b[38;2;{};{};{};48;2;{};{};{}m▀",
+                top.r, top.g, top.b, bottom.r, bottom.g, bottom.b
+            );
+        }
+        out.push_str("\x1b[0m\n");
+    }
+    out
+}
@@ -0,0 +1,111 @@
+//! Textura de ruido azul (void-and-cluster, Ulichney 1993) repetida sobre la
+//! pantalla. Sirve para desplazar por píxel las secuencias de muestreo: el
+//! ruido que queda en sombras suaves y demás efectos estocásticos pierde las
+//! manchas de baja frecuencia del ruido blanco y se ve como un grano fino y
+//! parejo, que además el post-proceso borronea mucho mejor.
+
+use std::sync::LazyLock;
+
+use crate::rng::Rng;
+
+/// Lado de la textura, que se repite en mosaico
+const SIZE: usize = 64;
+/// Desvío del filtro gaussiano que mide cuán agrupados están los puntos
+const SIGMA: f32 = 1.5;
+/// Radio en píxeles a partir del cual el filtro se considera cero
+const RADIUS: i32 = 6;
+
+static TEXTURE: LazyLock<Vec<f32>> = LazyLock::new(generate);
+
+/// Valor en [0, 1) del píxel (x, y). Cada `channel` lee la misma textura
+/// desplazada, así dos dimensiones de una muestra no quedan correlacionadas
+/// y cada una sigue siendo azul en pantalla
+pub fn sample(x: usize, y: usize, channel: u32) -> f32 {
+    let ox = channel as usize * 23;
+    let oy = channel as usize * 41;
+    TEXTURE[((y + oy) % SIZE) * SIZE + (x + ox) % SIZE]
+}
+
+/// Suma (o resta) el filtro centrado en `index` a la energía, con borde
+/// periódico para que la textura empalme consigo misma
+fn splat(energy: &mut [f32], kernel: &[f32], index: usize, sign: f32) {
+    let (cx, cy) = ((index % SIZE) as i32, (index / SIZE) as i32);
+    let side = 2 * RADIUS + 1;
+    for dy in -RADIUS..=RADIUS {
+        for dx in -RADIUS..=RADIUS {
+            let x = (cx + dx).rem_euclid(SIZE as i32) as usize;
+            let y = (cy + dy).rem_euclid(SIZE as i32) as usize;
+            energy[y * SIZE + x] += sign * kernel[((dy + RADIUS) * side + dx + RADIUS) as usize];
+        }
+    }
+}
+
+/// Celda de mayor (`highest`) o menor energía entre las que están en `state`
+fn extreme(energy: &[f32], on: &[bool], state: bool, highest: bool) -> usize {
+    let candidates = (0..energy.len()).filter(|&i| on[i] == state);
+    if highest {
+        candidates.max_by(|&a, &b| energy[a].total_cmp(&energy[b])).unwrap_or(0)
+    } else {
+        candidates.min_by(|&a, &b| energy[a].total_cmp(&energy[b])).unwrap_or(0)
+    }
+}
+
+fn generate() -> Vec<f32> {
+    let n = SIZE * SIZE;
+    let kernel: Vec<f32> = (-RADIUS..=RADIUS)
+        .flat_map(|dy| (-RADIUS..=RADIUS).map(move |dx| (dx, dy)))
+        .map(|(dx, dy)| (-((dx * dx + dy * dy) as f32) / (2.0 * SIGMA * SIGMA)).exp())
+        .collect();
+
+    // Patrón inicial: un 10% de las celdas al azar
+    let mut rng = Rng::new(0xB1DE);
+    let initial = n / 10;
+    let mut on = vec![false; n];
+    let mut energy = vec![0.0; n];
+    let mut placed = 0;
+    while placed < initial {
+        let i = (rng.next_u64() % n as u64) as usize;
+        if !on[i] {
+            on[i] = true;
+            splat(&mut energy, &kernel, i, 1.0);
+            placed += 1;
+        }
+    }
+
+    // Relajación: mover el punto más apretado al hueco más grande hasta que
+    // el hueco sea el mismo lugar de donde salió
+    for _ in 0..n {
+        let cluster = extreme(&energy, &on, true, true);
+        on[cluster] = false;
+        splat(&mut energy, &kernel, cluster, -1.0);
+        let void = extreme(&energy, &on, false, false);
+        on[void] = true;
+        splat(&mut energy, &kernel, void, 1.0);
+        if void == cluster {
+            break;
+        }
+    }
+
+    let mut rank = vec![0; n];
+
+    // Fase 1: los puntos iniciales reciben los rangos bajos, quitando
+    // siempre el más agrupado
+    let mut pattern = on.clone();
+    let mut pattern_energy = energy.clone();
+    for r in (0..initial).rev() {
+        let cluster = extreme(&pattern_energy, &pattern, true, true);
+        pattern[cluster] = false;
+        splat(&mut pattern_energy, &kernel, cluster, -1.0);
+        rank[cluster] = r;
+    }
+
+    // Fases 2 y 3: el resto se llena siempre por el hueco más grande
+    for r in initial..n {
+        let void = extreme(&energy, &on, false, false);
+        on[void] = true;
+        splat(&mut energy, &kernel, void, 1.0);
+        rank[void] = r;
+    }
+
+    rank.into_iter().map(|r| (r as f32 + 0.5) / n as f32).collect()
+}
@@ -1,23 +1,33 @@
-use raylib::prelude::*;
+use crate::math::Vec3;
+
+/// Vertical field of view of every camera, in radians
+pub const FIELD_OF_VIEW: f32 = std::f32::consts::PI / 3.0;
 
 /// A 3D camera that maintains its position and orientation in world space
+#[derive(Debug, Clone, PartialEq)]
 pub struct Camera {
-    pub eye: Vector3,     // Camera position in world coordinates
-    pub center: Vector3,  // Point the camera is looking at
-    pub up: Vector3,      // Up direction (initially world up, gets orthonormalized)
-    pub forward: Vector3, // Direction camera is facing (computed from eye->center)
-    pub right: Vector3,   // Right direction (perpendicular to forward and up)
+    pub eye: Vec3,           // Camera position in world coordinates
+    pub center: Vec3,        // Point the camera is looking at
+    pub up: Vec3,            // Up direction (initially world up, gets orthonormalized)
+    pub forward: Vec3,       // Direction camera is facing (computed from eye->center)
+    pub right: Vec3,         // Right direction (perpendicular to forward and up)
+    pub roll: f32,           // Rotation of the image around `forward`, in radians
+    pub aperture: f32,       // Lens diameter for depth of field; 0 = pinhole
+    pub focus_distance: f32, // Distance along `forward` that is in focus
 }
 
 impl Camera {
     /// Creates a new camera and computes its initial orientation
-    pub fn new(eye: Vector3, center: Vector3, up: Vector3) -> Self {
+    pub fn new(eye: Vec3, center: Vec3, up: Vec3) -> Self {
         let mut camera = Camera {
             eye,
             center,
             up,
-            forward: Vector3::zero(),
-            right: Vector3::zero(),
+            forward: Vec3::zero(),
+            right: Vec3::zero(),
+            roll: 0.0,
+            aperture: 0.0,
+            focus_distance: (center - eye).length(),
         };
         camera.update_basis_vectors();
         camera
@@ -42,7 +52,7 @@ impl Camera {
         let new_pitch = (current_pitch + pitch).clamp(-1.5, 1.5);
 
         let cos_pitch = new_pitch.cos();
-        let new_relative_pos = Vector3::new(
+        let new_relative_pos = Vec3::new(
             radius * cos_pitch * new_yaw.cos(),
             radius * new_pitch.sin(),
             radius * cos_pitch * new_yaw.sin(),
@@ -67,12 +77,107 @@ impl Camera {
         self.update_basis_vectors();
     }
 
+    /// Slides eye and center together along the camera's right and up axes
+    pub fn pan(&mut self, dx: f32, dy: f32) {
+        let offset = self.right * dx + self.up * dy;
+        self.eye += offset;
+        self.center += offset;
+        self.update_basis_vectors();
+    }
+
+    /// Tilts the camera around its viewing direction (dutch angle). `up`
+    /// and `right` stay level; the roll is applied when building rays
+    pub fn roll_by(&mut self, angle: f32) {
+        self.roll = (self.roll + angle).rem_euclid(std::f32::consts::TAU);
+    }
+
+    /// Right and up axes with the roll applied
+    pub fn rolled_axes(&self) -> (Vec3, Vec3) {
+        if self.roll == 0.0 {
+            return (self.right, self.up);
+        }
+        let (sin, cos) = self.roll.sin_cos();
+        (self.right * cos + self.up * sin, self.up * cos - self.right * sin)
+    }
+
+    /// Thin-lens ray for depth of field: starts at a point of the lens picked
+    /// by `lens` (in [0, 1)²) and passes through the point where the pinhole
+    /// ray `direction` meets the focus plane
+    pub fn lens_ray(&self, direction: &Vec3, lens: (f32, f32)) -> (Vec3, Vec3) {
+        let focus = self.eye + *direction * (self.focus_distance / direction.dot(self.forward));
+        let (u, v) = crate::sampling::disk(lens.0, lens.1);
+        let (right, up) = self.rolled_axes();
+        let origin = self.eye + (right * u + up * v) * (self.aperture * 0.5);
+        (origin, (focus - origin).normalized())
+    }
+
     /// Transforms a vector from camera space to world space using basis vectors
-    pub fn basis_change(&self, v: &Vector3) -> Vector3 {
-        Vector3::new(
-            v.x * self.right.x + v.y * self.up.x - v.z * self.forward.x,
-            v.x * self.right.y + v.y * self.up.y - v.z * self.forward.y,
-            v.x * self.right.z + v.y * self.up.z - v.z * self.forward.z,
+    pub fn basis_change(&self, v: &Vec3) -> Vec3 {
+        let (right, up) = self.rolled_axes();
+        Vec3::new(
+            v.x * right.x + v.y * up.x - v.z * self.forward.x,
+            v.x * right.y + v.y * up.y - v.z * self.forward.y,
+            v.x * right.z + v.y * up.z - v.z * self.forward.z,
         )
     }
 }
+
+/// Velocity-based camera motion: input sets a target velocity for orbit,
+/// zoom and pan, and the actual velocity eases towards it, so the camera
+/// accelerates when a key is pressed and glides to a stop when released
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CameraInertia {
+    /// How fast velocity follows the input, per second; 0 = no smoothing
+    pub damping: f32,
+    yaw: f32,   // radians per second
+    pitch: f32, // radians per second
+    zoom: f32,  // log of the distance factor per second
+    pan: (f32, f32),
+    target: (f32, f32, f32, (f32, f32)),
+}
+
+impl CameraInertia {
+    pub fn new(damping: f32) -> Self {
+        CameraInertia { damping, ..Default::default() }
+    }
+
+    /// Desired velocities for this frame; anything not pushed is zero
+    /// - `zoom` < 0 → zoom in, > 0 → zoom out
+    pub fn push(&mut self, yaw: f32, pitch: f32, zoom: f32, pan: (f32, f32)) {
+        let t = &mut self.target;
+        t.0 += yaw;
+        t.1 += pitch;
+        t.2 += zoom;
+        t.3 = (t.3.0 + pan.0, t.3.1 + pan.1);
+    }
+
+    /// Eases velocities towards the pushed targets, moves the camera by
+    /// `dt` seconds and clears the targets for the next frame
+    pub fn update(&mut self, camera: &mut Camera, dt: f32) {
+        let blend = if self.damping > 0.0 { 1.0 - (-self.damping * dt).exp() } else { 1.0 };
+        let (yaw, pitch, zoom, pan) = std::mem::take(&mut self.target);
+        let ease = |v: f32, target: f32| {
+            let v = v + (target - v) * blend;
+            if v.abs() < 1e-4 { 0.0 } else { v }
+        };
+        self.yaw = ease(self.yaw, yaw);
+        self.pitch = ease(self.pitch, pitch);
+        self.zoom = ease(self.zoom, zoom);
+        self.pan = (ease(self.pan.0, pan.0), ease(self.pan.1, pan.1));
+
+        if self.yaw != 0.0 || self.pitch != 0.0 {
+            camera.orbit(self.yaw * dt, self.pitch * dt);
+        }
+        if self.zoom != 0.0 {
+            camera.zoom((self.zoom * dt).exp());
+        }
+        if self.pan != (0.0, 0.0) {
+            camera.pan(self.pan.0 * dt, self.pan.1 * dt);
+        }
+    }
+
+    /// Whether the camera is still gliding
+    pub fn is_moving(&self) -> bool {
+        self.yaw != 0.0 || self.pitch != 0.0 || self.zoom != 0.0 || self.pan != (0.0, 0.0)
+    }
+}
@@ -0,0 +1,160 @@
+use crate::camera::Camera;
+use crate::error::{Error, Result};
+use crate::math::Vec3;
+
+/// Archivo donde la vista interactiva guarda los encuadres
+pub const PRESETS_FILE: &str = "diorama_cameras.txt";
+
+/// Cantidad de encuadres (uno por tecla numérica, 0–9)
+pub const SLOTS: usize = 10;
+
+/// Encuadres de cámara guardados (ojo, centro, arriba, alabeo y lente) para
+/// volver a ellos al instante. En disco es una línea por encuadre:
+/// `slot ex ey ez cx cy cz ux uy uz [alabeo [apertura foco]]`. El campo de
+/// visión es fijo (`camera::FIELD_OF_VIEW`), así que no se guarda.
+#[derive(Debug, Clone, Default)]
+pub struct CameraPresets {
+    slots: [Option<Camera>; SLOTS],
+}
+
+impl CameraPresets {
+    /// Lee `path`; si no existe todavía, no hay encuadres
+    pub fn load(path: &str) -> Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text, path),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn parse(text: &str, path: &str) -> Result<Self> {
+        let mut presets = Self::default();
+        for (i, raw) in text.lines().enumerate() {
+            let line = raw.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let err = |msg: String| Error::Parse { path: path.to_string(), line: i + 1, msg };
+            let mut words = line.split_whitespace();
+            let slot = words
+                .next()
+                .and_then(|w| w.parse::<usize>().ok())
+                .filter(|s| *s < SLOTS)
+                .ok_or_else(|| err(format!("se espera un número de encuadre entre 0 y {}", SLOTS - 1)))?;
+            let values: Vec<f32> = words
+                .map(|s| s.parse::<f32>().map_err(|_| err(format!("número inválido: {}", s))))
+                .collect::<Result<_>>()?;
+            let shape = || {
+                err("se esperan ojo, centro y arriba (nueve valores), el alabeo y la lente (apertura y foco) opcionales"
+                    .to_string())
+            };
+            let [ex, ey, ez, cx, cy, cz, ux, uy, uz, ref rest @ ..] = values[..] else {
+                return Err(shape());
+            };
+            let camera = Camera::new(Vec3::new(ex, ey, ez), Vec3::new(cx, cy, cz), Vec3::new(ux, uy, uz));
+            presets.slots[slot] = Some(match *rest {
+                [] => camera,
+                [roll] => Camera { roll, ..camera },
+                [roll, aperture, focus_distance] if aperture >= 0.0 && focus_distance > 0.0 => {
+                    Camera { roll, aperture, focus_distance, ..camera }
+                }
+                _ => return Err(shape()),
+            });
+        }
+        Ok(presets)
+    }
+
+    pub fn save(&self, path: &str) -> Result<()> {
+        let mut text = String::from("# encuadre ojo(x y z) centro(x y z) arriba(x y z) alabeo apertura foco\n");
+        for (slot, preset) in self.slots.iter().enumerate() {
+            if let Some(camera) = preset {
+                let (e, c, u) = (camera.eye, camera.center, camera.up);
+                text += &format!(
+                    "{} {} {} {} {} {} {} {} {} {} {} {} {}\n",
+                    slot, e.x, e.y, e.z, c.x, c.y, c.z, u.x, u.y, u.z,
+                    camera.roll, camera.aperture, camera.focus_distance
+                );
+            }
+        }
+        std::fs::write(path, text)?;
+        Ok(())
+    }
+
+    /// Guarda el encuadre de `camera` (con su lente) en `slot`; fuera de
+    /// rango se ignora
+    pub fn store(&mut self, slot: usize, camera: &Camera) {
+        if let Some(s) = self.slots.get_mut(slot) {
+            *s = Some(camera.clone());
+        }
+    }
+
+    /// Cámara guardada en `slot`, si hay
+    pub fn recall(&self, slot: usize) -> Option<Camera> {
+        self.slots.get(slot)?.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_line(line: &str) -> Result<CameraPresets> {
+        CameraPresets::parse(line, "test.txt")
+    }
+
+    #[test]
+    fn save_and_load_keep_roll_and_lens() {
+        let mut camera = Camera::new(Vec3::new(1.5, 12.0, -30.25), Vec3::new(5.0, 5.0, 5.0), Vec3::new(0.0, 1.0, 0.0));
+        camera.roll = 0.3;
+        camera.aperture = 0.25;
+        camera.focus_distance = 17.5;
+        let plain = Camera::new(Vec3::new(0.0, 15.0, 30.0), Vec3::zero(), Vec3::new(0.0, 1.0, 0.0));
+        let mut presets = CameraPresets::default();
+        presets.store(2, &camera);
+        presets.store(9, &plain);
+
+        let path = std::env::temp_dir().join(format!("diorama_presets_{}.txt", std::process::id()));
+        let path = path.to_str().unwrap();
+        presets.save(path).unwrap();
+        let loaded = CameraPresets::load(path);
+        std::fs::remove_file(path).unwrap();
+        let loaded = loaded.unwrap();
+
+        for (slot, expected) in [(2, &camera), (9, &plain)] {
+            let got = loaded.recall(slot).unwrap();
+            assert_eq!((got.eye, got.center), (expected.eye, expected.center));
+            assert!((got.up - expected.up).length() < 1e-6);
+            assert_eq!(
+                (got.roll, got.aperture, got.focus_distance),
+                (expected.roll, expected.aperture, expected.focus_distance)
+            );
+        }
+        assert!(loaded.recall(0).is_none());
+    }
+
+    #[test]
+    fn optional_fields() {
+        let presets = parse_line("1 0 0 5 0 0 0 0 1 0 0.5\n").unwrap();
+        let camera = presets.recall(1).unwrap();
+        assert_eq!((camera.roll, camera.aperture, camera.focus_distance), (0.5, 0.0, 5.0));
+        let presets = parse_line("# comentario\n\n3 0 0 5 0 0 0 0 1 0\n").unwrap();
+        assert_eq!(presets.recall(3).unwrap().roll, 0.0);
+    }
+
+    #[test]
+    fn rejects_bad_lines() {
+        for text in [
+            "1 0 0 5 0 0 0",                    // faltan valores
+            "1 0 0 5 0 0 0 0 1 0 0 0.1",        // apertura sin foco
+            "1 0 0 5 0 0 0 0 1 0 0 0.1 0",      // foco nulo
+            "1 0 0 5 0 0 0 0 1 0 0 -1 4",       // apertura negativa
+            "1 0 0 5 0 0 0 0 1 0 0 0 4 9",      // sobra un valor
+            "1 0 0 5 x 0 0 0 1 0",              // basura
+            "10 0 0 5 0 0 0 0 1 0",             // encuadre fuera de rango
+            "cámara 0 0 5 0 0 0 0 1 0",         // sin número de encuadre
+        ] {
+            let error = parse_line(&format!("0 0 0 5 0 0 0 0 1 0\n{}", text)).unwrap_err();
+            assert!(matches!(error, Error::Parse { line: 2, .. }), "{}: {}", text, error);
+        }
+    }
+}
@@ -0,0 +1,324 @@
+use std::str::FromStr;
+use diorama::error::{Error, Result};
+use diorama::world::WorldOptions;
+
+/// Opciones de línea de comandos
+pub struct Args {
+    pub width: u32,
+    pub height: u32,
+    /// Archivos de escena con estructuras a estampar, etc. Con varios se
+    /// alterna entre ellos con RePág/AvPág
+    pub scenes: Vec<String>,
+    /// Si está presente, renderiza sin ventana y guarda la imagen en esta ruta
+    pub output: Option<String>,
+    /// Si está presente, renderiza una vuelta completa y la codifica con ffmpeg
+    pub video: Option<String>,
+    /// Renderiza en miniatura y lo imprime en la terminal con medios bloques
+    /// de colores ANSI, sin ventana (vista previa por SSH)
+    pub ascii: bool,
+    /// Columnas de terminal de la vista `--ascii`
+    pub ascii_columns: u32,
+    /// Cantidad de cuadros de la animación
+    pub frames: u32,
+    /// Cuadros por segundo del video
+    pub fps: u32,
+    /// PNG en escala de grises a extruir como terreno bajo el diorama
+    pub heightmap: Option<String>,
+    /// Altura máxima (en vóxeles) del terreno del heightmap
+    pub heightmap_height: i32,
+    /// Bandas de material del heightmap, p. ej. `0.3:B,0.7:W,1.0:Q`
+    pub heightmap_bands: String,
+    /// Lado del terreno procedural (Perlin) alrededor del diorama; 0 = sin terreno
+    pub terrain: i32,
+    /// Semilla del terreno procedural
+    pub terrain_seed: u64,
+    /// Fotones a trazar para cáusticas; 0 = desactivado
+    pub caustics: usize,
+    /// Rayos por muestra de la caché de irradiancia; 0 = sin luz indirecta
+    pub gi: u32,
+    /// Intensidad de la luz difusa del cielo; 0 = desactivada
+    pub sky_light: f32,
+    /// Dirección `x,y,z` hacia el disco del sol (reemplaza al de la escena)
+    pub sun: Option<(f32, f32, f32)>,
+    /// Sombreado matcap de depuración: `clay` o la ruta de una imagen de
+    /// esfera; reemplaza toda la iluminación
+    pub matcap: Option<String>,
+    /// Estilo caricatura (contornos y brillos duros) con esta cantidad de
+    /// bandas de luz si la escena no define `cel`; 0 = desactivado
+    pub toon: u32,
+    /// Resolución de los lightmaps por cara; 0 = sin horneado
+    pub bake: usize,
+    /// Rayos de sombra por punto; 1 = sombras duras
+    pub shadow_samples: u32,
+    /// Vista interactiva progresiva: mosaicos desde el centro, cuadro a cuadro
+    pub progressive: bool,
+    /// Vista interactiva que promedia cuadros mientras la cámara está quieta
+    pub accumulate: bool,
+    /// Sincronía vertical en la vista interactiva
+    pub vsync: bool,
+    /// Tope de cuadros por segundo de la ventana; 0 = sin tope
+    pub fps_cap: u32,
+    /// Cuadros por segundo que la vista interactiva intenta sostener bajando
+    /// la resolución interna mientras se mueve la cámara; 0 = siempre completa
+    pub target_fps: f32,
+    /// Radio en píxeles de la zona alrededor del cursor que la vista
+    /// interactiva traza a calidad completa; 0 = toda la imagen
+    pub foveated: f32,
+    /// Suavizado de la cámara interactiva (por segundo); 0 = sin inercia
+    pub camera_damping: f32,
+    /// Diámetro de la lente para profundidad de campo; 0 = todo enfocado
+    pub aperture: f32,
+    /// Distancia de enfoque; si falta, la del centro de la cámara
+    pub focus: Option<f32>,
+    /// Enfoca en lo que está en el centro de la imagen (o bajo el cursor)
+    pub autofocus: bool,
+    /// Rayos primarios por píxel (antialiasing)
+    pub spp: u32,
+    /// Hilos de render; si falta, `DIORAMA_THREADS` o todos los núcleos
+    /// menos uno
+    pub threads: Option<usize>,
+    /// Fija cada hilo de render a un núcleo
+    pub pin_threads: bool,
+    /// Radio de la luz principal para sombras suaves
+    pub light_radius: f32,
+    /// Radio del círculo que recorre la luz alrededor del diorama (demo de
+    /// iluminación); 0 = luz fija
+    pub light_orbit: f32,
+    /// Radianes por segundo de la luz en su círculo
+    pub light_orbit_speed: f32,
+    /// Cuánto sube y baja la luz mientras da vueltas
+    pub light_bob: f32,
+    /// Giro fijo del diorama completo alrededor del eje Y (grados)
+    pub model_yaw: f32,
+    /// Escala uniforme del diorama completo
+    pub model_scale: f32,
+    /// Desplazamiento del diorama completo, `x,y,z`
+    pub model_offset: (f32, f32, f32),
+    /// Velocidad de giro del diorama sobre su pedestal (grados por segundo)
+    pub model_spin: f32,
+    /// Semilla del muestreo aleatorio; misma semilla, misma imagen
+    pub seed: u64,
+    /// Imagen de referencia contra la que se compara `--output`
+    pub golden: Option<String>,
+    /// Diferencia máxima por canal (0–255) aceptada frente a la referencia
+    pub golden_tolerance: u8,
+    /// Escribe `.ppm` en texto (P3) en vez de binario (P6)
+    pub ppm_ascii: bool,
+    /// Escribe `.png` con 16 bits por canal
+    pub png16: bool,
+    /// Tramado ordenado en toda salida de 8 bits (imágenes, GIF, video,
+    /// ASCII y ventana)
+    pub dither: bool,
+    /// Además de los metadatos del PNG, escribe la cámara y los ajustes en
+    /// `<salida>.json`
+    pub sidecar: bool,
+    /// Rebotes máximos por rayo; si falta, el de la escena o el por defecto
+    pub max_depth: Option<u32>,
+    /// Irradiancia bajo la cual un bloque emisivo no ilumina un punto
+    pub light_cutoff: f32,
+    /// Radiancia máxima por muestra de GI (contra "fireflies"); 0 = sin límite
+    pub firefly_clamp: f32,
+    /// Rechaza muestras de GI con más de este múltiplo del brillo medio; 0 = no
+    pub reject_outliers: f32,
+    /// Distancia desde la que los trozos del mundo usan media resolución; 0 = no
+    pub lod: f32,
+}
+
+impl Default for Args {
+    fn default() -> Self {
+        Args {
+            width: 1300,
+            height: 900,
+            scenes: Vec::new(),
+            output: None,
+            video: None,
+            ascii: false,
+            ascii_columns: 80,
+            frames: 120,
+            fps: 30,
+            heightmap: None,
+            heightmap_height: 6,
+            heightmap_bands: "0.35:B,0.75:W,1.0:Q".to_string(),
+            terrain: 0,
+            terrain_seed: 1,
+            caustics: 0,
+            gi: 0,
+            sky_light: 0.0,
+            sun: None,
+            matcap: None,
+            toon: 0,
+            bake: 0,
+            shadow_samples: 1,
+            progressive: false,
+            accumulate: false,
+            vsync: false,
+            fps_cap: 60,
+            target_fps: 0.0,
+            foveated: 0.0,
+            camera_damping: 8.0,
+            spp: 1,
+            aperture: 0.0,
+            focus: None,
+            autofocus: false,
+            threads: None,
+            pin_threads: false,
+            light_radius: 1.5,
+            light_orbit: 0.0,
+            light_orbit_speed: 0.6,
+            light_bob: 4.0,
+            model_yaw: 0.0,
+            model_scale: 1.0,
+            model_offset: (0.0, 0.0, 0.0),
+            model_spin: 0.0,
+            seed: 1,
+            golden: None,
+            golden_tolerance: 2,
+            ppm_ascii: false,
+            png16: false,
+            dither: false,
+            sidecar: false,
+            max_depth: None,
+            light_cutoff: 0.005,
+            firefly_clamp: 0.0,
+            reject_outliers: 0.0,
+            lod: 0.0,
+        }
+    }
+}
+
+impl Args {
+    /// Ajustes de `diorama::world::build` que vienen de la línea de comandos
+    pub fn world_options(&self) -> WorldOptions {
+        WorldOptions {
+            width: self.width as usize,
+            height: self.height as usize,
+            heightmap: self.heightmap.clone(),
+            heightmap_height: self.heightmap_height,
+            heightmap_bands: self.heightmap_bands.clone(),
+            terrain: self.terrain,
+            terrain_seed: self.terrain_seed,
+            caustics: self.caustics,
+            gi: self.gi,
+            reject_outliers: self.reject_outliers,
+            sky_light: self.sky_light,
+            sun: self.sun,
+            matcap: self.matcap.clone(),
+            toon: self.toon,
+            bake: self.bake,
+            shadow_samples: self.shadow_samples,
+            spp: self.spp,
+            aperture: self.aperture,
+            focus: self.focus,
+            light_radius: self.light_radius,
+            light_orbit: self.light_orbit,
+            light_orbit_speed: self.light_orbit_speed,
+            light_bob: self.light_bob,
+            model_yaw: self.model_yaw,
+            model_scale: self.model_scale,
+            model_offset: self.model_offset,
+            model_spin: self.model_spin,
+            seed: self.seed,
+            max_depth: self.max_depth,
+            light_cutoff: self.light_cutoff,
+            firefly_clamp: self.firefly_clamp,
+            dither: self.dither,
+            lod: self.lod,
+        }
+    }
+
+    /// Lee las opciones de `std::env::args`
+    pub fn parse() -> Result<Self> {
+        Self::parse_from(std::env::args().skip(1))
+    }
+
+    pub fn parse_from<I: Iterator<Item = String>>(mut it: I) -> Result<Self> {
+        let mut args = Args::default();
+
+        while let Some(flag) = it.next() {
+            match flag.as_str() {
+                "--scene" => args.scenes.push(value(&mut it, &flag)?),
+                "--output" | "-o" => args.output = Some(value(&mut it, &flag)?),
+                "--video" => args.video = Some(value(&mut it, &flag)?),
+                "--ascii" => args.ascii = true,
+                "--ascii-columns" => args.ascii_columns = number(&mut it, &flag)?,
+                "--frames" => args.frames = number(&mut it, &flag)?,
+                "--fps" => args.fps = number(&mut it, &flag)?,
+                "--heightmap" => args.heightmap = Some(value(&mut it, &flag)?),
+                "--heightmap-height" => args.heightmap_height = number(&mut it, &flag)?,
+                "--heightmap-bands" => args.heightmap_bands = value(&mut it, &flag)?,
+                "--terrain" => args.terrain = number(&mut it, &flag)?,
+                "--terrain-seed" => args.terrain_seed = number(&mut it, &flag)?,
+                "--caustics" => args.caustics = number(&mut it, &flag)?,
+                "--gi" => args.gi = number(&mut it, &flag)?,
+                "--sky-light" => args.sky_light = number(&mut it, &flag)?,
+                "--sun" => args.sun = Some(vector(&mut it, &flag)?),
+                "--matcap" => args.matcap = Some(value(&mut it, &flag)?),
+                "--toon" => args.toon = number(&mut it, &flag)?,
+                "--bake" => args.bake = number(&mut it, &flag)?,
+                "--shadow-samples" => args.shadow_samples = number(&mut it, &flag)?,
+                "--progressive" => args.progressive = true,
+                "--accumulate" => args.accumulate = true,
+                "--vsync" => args.vsync = true,
+                "--fps-cap" => args.fps_cap = number(&mut it, &flag)?,
+                "--target-fps" => args.target_fps = number(&mut it, &flag)?,
+                "--foveated" => args.foveated = number(&mut it, &flag)?,
+                "--camera-damping" => args.camera_damping = number(&mut it, &flag)?,
+                "--threads" => args.threads = Some(number(&mut it, &flag)?),
+                "--pin-threads" => args.pin_threads = true,
+                "--aperture" => args.aperture = number(&mut it, &flag)?,
+                "--focus" => args.focus = Some(number(&mut it, &flag)?),
+                "--autofocus" => args.autofocus = true,
+                "--spp" => args.spp = number(&mut it, &flag)?,
+                "--light-radius" => args.light_radius = number(&mut it, &flag)?,
+                "--light-orbit" => args.light_orbit = number(&mut it, &flag)?,
+                "--light-orbit-speed" => args.light_orbit_speed = number(&mut it, &flag)?,
+                "--light-bob" => args.light_bob = number(&mut it, &flag)?,
+                "--model-yaw" => args.model_yaw = number(&mut it, &flag)?,
+                "--model-scale" => args.model_scale = number(&mut it, &flag)?,
+                "--model-offset" => args.model_offset = vector(&mut it, &flag)?,
+                "--model-spin" => args.model_spin = number(&mut it, &flag)?,
+                "--seed" => args.seed = number(&mut it, &flag)?,
+                "--golden" => args.golden = Some(value(&mut it, &flag)?),
+                "--golden-tolerance" => args.golden_tolerance = number(&mut it, &flag)?,
+                "--ppm-ascii" => args.ppm_ascii = true,
+                "--png16" => args.png16 = true,
+                "--dither" => args.dither = true,
+                "--sidecar" => args.sidecar = true,
+                "--max-depth" => args.max_depth = Some(number(&mut it, &flag)?),
+                "--light-cutoff" => args.light_cutoff = number(&mut it, &flag)?,
+                "--firefly-clamp" => args.firefly_clamp = number(&mut it, &flag)?,
+                "--reject-outliers" => args.reject_outliers = number(&mut it, &flag)?,
+                "--lod" => args.lod = number(&mut it, &flag)?,
+                "--width" => args.width = number(&mut it, &flag)?,
+                "--height" => args.height = number(&mut it, &flag)?,
+                other => return Err(Error::Args(format!("opción desconocida: {}", other))),
+            }
+        }
+
+        if args.model_scale.is_nan() || args.model_scale <= 0.0 {
+            return Err(Error::Args(format!("--model-scale debe ser positivo: {}", args.model_scale)));
+        }
+        Ok(args)
+    }
+}
+
+fn value<I: Iterator<Item = String>>(it: &mut I, flag: &str) -> Result<String> {
+    it.next()
+        .ok_or_else(|| Error::Args(format!("falta el valor de {}", flag)))
+}
+
+fn number<T: FromStr, I: Iterator<Item = String>>(it: &mut I, flag: &str) -> Result<T> {
+    let v = value(it, flag)?;
+    v.parse()
+        .map_err(|_| Error::Args(format!("valor inválido para {}: {}", flag, v)))
+}
+
+/// Vector `x,y,z`
+fn vector<I: Iterator<Item = String>>(it: &mut I, flag: &str) -> Result<(f32, f32, f32)> {
+    let v = value(it, flag)?;
+    let parts: Vec<f32> = v.split(',').filter_map(|p| p.trim().parse().ok()).collect();
+    match parts[..] {
+        [x, y, z] => Ok((x, y, z)),
+        _ => Err(Error::Args(format!("valor inválido para {} (se espera x,y,z): {}", flag, v))),
+    }
+}
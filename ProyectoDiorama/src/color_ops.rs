@@ -1,12 +1,12 @@
-use raylib::prelude::Color;
+use crate::math::Rgba;
 
 pub trait ColorOps {
-    fn mult_f32(&self, factor: f32) -> Color;
+    fn mult_f32(&self, factor: f32) -> Rgba;
 }
 
-impl ColorOps for Color {
-    fn mult_f32(&self, factor: f32) -> Color {
-        Color::new(
+impl ColorOps for Rgba {
+    fn mult_f32(&self, factor: f32) -> Rgba {
+        Rgba::new(
             (self.r as f32 * factor).min(255.0) as u8,
             (self.g as f32 * factor).min(255.0) as u8,
             (self.b as f32 * factor).min(255.0) as u8,
@@ -0,0 +1,144 @@
+use raylib::prelude::*;
+
+use diorama::camera_presets;
+
+/// Lo que puede hacer el usuario en la vista interactiva
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    OrbitLeft,
+    OrbitRight,
+    OrbitUp,
+    OrbitDown,
+    /// Con las flechas, desplaza en vez de orbitar
+    Pan,
+    ZoomIn,
+    ZoomOut,
+    RollLeft,
+    RollRight,
+    RecallPreset,
+    /// Con una tecla numérica, guarda el encuadre en vez de recuperarlo
+    StorePreset,
+    MoreBounces,
+    FewerBounces,
+    Pause,
+    Vsync,
+    FpsCap,
+    Progressive,
+    Accumulate,
+    Foveated,
+    Autofocus,
+    Crosshair,
+    ExportGif,
+    ExportObj,
+    ExportGltf,
+    ExportVox,
+    NextScene,
+    PreviousScene,
+    Reload,
+    Help,
+}
+
+/// Teclas de una acción y cómo se muestran en la ayuda
+pub struct Binding {
+    pub action: Action,
+    pub keys: &'static [KeyboardKey],
+    pub label: &'static str,
+    pub help: &'static str,
+}
+
+/// Teclas numéricas de los encuadres guardados, en orden de encuadre
+pub const SLOT_KEYS: [KeyboardKey; camera_presets::SLOTS] = [
+    KeyboardKey::KEY_ZERO,
+    KeyboardKey::KEY_ONE,
+    KeyboardKey::KEY_TWO,
+    KeyboardKey::KEY_THREE,
+    KeyboardKey::KEY_FOUR,
+    KeyboardKey::KEY_FIVE,
+    KeyboardKey::KEY_SIX,
+    KeyboardKey::KEY_SEVEN,
+    KeyboardKey::KEY_EIGHT,
+    KeyboardKey::KEY_NINE,
+];
+
+const fn bind(action: Action, keys: &'static [KeyboardKey], label: &'static str, help: &'static str) -> Binding {
+    Binding { action, keys, label, help }
+}
+
+/// Tabla de controles: la vista interactiva consulta las teclas acá y la
+/// ayuda (F1) se arma con la misma tabla. Las filas seguidas con la misma
+/// `help` se muestran juntas.
+pub const BINDINGS: &[Binding] = &[
+    bind(Action::OrbitLeft, &[KeyboardKey::KEY_LEFT], "Izq", "orbitar la cámara"),
+    bind(Action::OrbitRight, &[KeyboardKey::KEY_RIGHT], "Der", "orbitar la cámara"),
+    bind(Action::OrbitUp, &[KeyboardKey::KEY_UP], "Arriba", "orbitar la cámara"),
+    bind(Action::OrbitDown, &[KeyboardKey::KEY_DOWN], "Abajo", "orbitar la cámara"),
+    bind(
+        Action::Pan,
+        &[KeyboardKey::KEY_LEFT_SHIFT, KeyboardKey::KEY_RIGHT_SHIFT],
+        "Shift+flechas",
+        "desplazar la cámara",
+    ),
+    bind(Action::ZoomIn, &[KeyboardKey::KEY_EQUAL], "+", "acercar/alejar"),
+    bind(Action::ZoomOut, &[KeyboardKey::KEY_MINUS], "-", "acercar/alejar"),
+    bind(Action::RollLeft, &[KeyboardKey::KEY_Q], "Q", "alabeo"),
+    bind(Action::RollRight, &[KeyboardKey::KEY_E], "E", "alabeo"),
+    bind(Action::RecallPreset, &SLOT_KEYS, "0-9", "recuperar encuadre"),
+    bind(
+        Action::StorePreset,
+        &[KeyboardKey::KEY_LEFT_CONTROL, KeyboardKey::KEY_RIGHT_CONTROL],
+        "Ctrl+0-9",
+        "guardar encuadre",
+    ),
+    bind(Action::MoreBounces, &[KeyboardKey::KEY_RIGHT_BRACKET], "]", "rebotes máximos"),
+    bind(Action::FewerBounces, &[KeyboardKey::KEY_LEFT_BRACKET], "[", "rebotes máximos"),
+    bind(Action::Pause, &[KeyboardKey::KEY_SPACE], "Espacio", "pausar/reanudar el render"),
+    bind(Action::Vsync, &[KeyboardKey::KEY_F2], "F2", "sincronía vertical"),
+    bind(Action::FpsCap, &[KeyboardKey::KEY_F3], "F3", "tope de FPS sí/no"),
+    bind(Action::Progressive, &[KeyboardKey::KEY_P], "P", "render progresivo"),
+    bind(Action::Accumulate, &[KeyboardKey::KEY_A], "A", "acumular cuadros"),
+    bind(Action::Foveated, &[KeyboardKey::KEY_F], "F", "render foveado"),
+    bind(Action::Autofocus, &[KeyboardKey::KEY_K], "K", "autoenfoque"),
+    bind(Action::Crosshair, &[KeyboardKey::KEY_C], "C", "retícula y bloque apuntado"),
+    bind(Action::ExportGif, &[KeyboardKey::KEY_G], "G", "exportar vuelta en GIF"),
+    bind(Action::ExportObj, &[KeyboardKey::KEY_O], "O", "exportar OBJ"),
+    bind(Action::ExportGltf, &[KeyboardKey::KEY_L], "L", "exportar glTF"),
+    bind(Action::ExportVox, &[KeyboardKey::KEY_V], "V", "exportar .vox"),
+    bind(Action::NextScene, &[KeyboardKey::KEY_PAGE_DOWN], "AvPág", "cambiar de escena"),
+    bind(Action::PreviousScene, &[KeyboardKey::KEY_PAGE_UP], "RePág", "cambiar de escena"),
+    bind(Action::Reload, &[KeyboardKey::KEY_F5], "F5", "recargar texturas, materiales y escena"),
+    bind(Action::Help, &[KeyboardKey::KEY_F1], "F1", "esta ayuda"),
+];
+
+fn keys(action: Action) -> &'static [KeyboardKey] {
+    BINDINGS.iter().find(|b| b.action == action).map_or(&[], |b| b.keys)
+}
+
+/// Alguna tecla de `action` está apretada
+pub fn down(window: &RaylibHandle, action: Action) -> bool {
+    keys(action).iter().any(|&k| window.is_key_down(k))
+}
+
+/// Alguna tecla de `action` se apretó en este cuadro
+pub fn pressed(window: &RaylibHandle, action: Action) -> bool {
+    keys(action).iter().any(|&k| window.is_key_pressed(k))
+}
+
+/// 1, −1 o 0 según cuál de las dos acciones esté apretada
+pub fn axis(window: &RaylibHandle, positive: Action, negative: Action) -> f32 {
+    down(window, positive) as i32 as f32 - down(window, negative) as i32 as f32
+}
+
+/// Líneas de la ayuda para las acciones en las que `active` da verdadero
+pub fn help_lines(active: impl Fn(Action) -> bool) -> Vec<String> {
+    let mut groups: Vec<(Vec<&str>, &str)> = Vec::new();
+    for binding in BINDINGS.iter().filter(|b| active(b.action)) {
+        match groups.last_mut() {
+            Some((labels, help)) if *help == binding.help => labels.push(binding.label),
+            _ => groups.push((vec![binding.label], binding.help)),
+        }
+    }
+    groups
+        .into_iter()
+        .map(|(labels, help)| format!("{:<22} {}", labels.join("/"), help))
+        .collect()
+}
@@ -1,120 +1,168 @@
+use crate::geometry::{self, Aabb};
 use crate::material::Material;
-use crate::ray_intersect::{Intersect, RayIntersect, CubeFace};
-use raylib::prelude::Vector3;
+use crate::ray_intersect::{Intersect, Occlusion, RayIntersect, CubeFace};
+use crate::math::Vec3;
 
+/// Caja alineada a los ejes. `Cube::new` arma la caja cúbica de siempre y
+/// `Cube::from_bounds` una de cualquier tamaño por eje (plataformas, muros).
 #[derive(Debug, Clone)]
 pub struct Cube {
-    pub center: Vector3,
-    pub size: f32,
+    pub center: Vec3,
+    /// Mitad del tamaño en cada eje
+    pub half_extents: Vec3,
     pub material: Material,
+    /// AO por cara (índice `CubeFace::index`) en las esquinas UV (0,0), (1,0), (1,1), (0,1)
+    pub ao: [[f32; 4]; 6],
 }
 
 impl Cube {
-    pub fn new(center: Vector3, size: f32, material: Material) -> Self {
-        Cube { center, size, material }
+    pub fn new(center: Vec3, size: f32, material: Material) -> Self {
+        let half = size * 0.5;
+        Cube { center, half_extents: Vec3::new(half, half, half), material, ao: [[1.0; 4]; 6] }
     }
-}
-
-impl RayIntersect for Cube {
-    fn ray_intersect(&self, ray_origin: &Vector3, ray_direction: &Vector3) -> Intersect {
-        // Half-size
-        let half = self.size * 0.5;
-        let min = self.center - Vector3::new(half, half, half);
-        let max = self.center + Vector3::new(half, half, half);
-
-        // Reciprocal to avoid divide-by-zero
-        let inv_dir = Vector3::new(
-            1.0 / ray_direction.x,
-            1.0 / ray_direction.y,
-            1.0 / ray_direction.z,
-        );
 
-        // Intersections with x slabs
-        let mut tmin = (min.x - ray_origin.x) * inv_dir.x;
-        let mut tmax = (max.x - ray_origin.x) * inv_dir.x;
-        if tmin > tmax {
-            std::mem::swap(&mut tmin, &mut tmax);
-        }
+    /// Caja entre dos esquinas opuestas (en cualquier orden)
+    pub fn from_bounds(a: Vec3, b: Vec3, material: Material) -> Self {
+        let min = Vec3::new(a.x.min(b.x), a.y.min(b.y), a.z.min(b.z));
+        let max = Vec3::new(a.x.max(b.x), a.y.max(b.y), a.z.max(b.z));
+        Cube { center: (min + max) * 0.5, half_extents: (max - min) * 0.5, material, ao: [[1.0; 4]; 6] }
+    }
 
-        // y slabs
-        let mut tymin = (min.y - ray_origin.y) * inv_dir.y;
-        let mut tymax = (max.y - ray_origin.y) * inv_dir.y;
-        if tymin > tymax {
-            std::mem::swap(&mut tymin, &mut tymax);
-        }
+    pub fn with_ao(mut self, ao: [[f32; 4]; 6]) -> Self {
+        self.ao = ao;
+        self
+    }
 
-        if (tmin > tymax) || (tymin > tmax) {
-            return Intersect::empty();
+    /// Normal redondeada cerca de los bordes de la cara: a menos de
+    /// `material.bevel` del borde se inclina hacia afuera de ese borde,
+    /// hasta 45° justo en la arista (en las esquinas, hacia las dos)
+    fn bevel_normal(&self, normal: Vec3, face: CubeFace, u: f32, v: f32) -> Vec3 {
+        let bevel = self.material.bevel;
+        let (axis_u, axis_v) = face.uv_axes();
+        let (length_u, length_v) = self.face_size(face);
+        let mut bent = normal;
+        for ((x, y, z), t, length) in [(axis_u, u, length_u), (axis_v, v, length_v)] {
+            let axis = Vec3::new(x as f32, y as f32, z as f32);
+            let (to_min, to_max) = (t * length, (1.0 - t) * length);
+            if to_min < bevel {
+                bent -= axis * (1.0 - to_min / bevel);
+            } else if to_max < bevel {
+                bent += axis * (1.0 - to_max / bevel);
+            }
         }
+        bent.normalized()
+    }
 
-        if tymin > tmin {
-            tmin = tymin;
-        }
-        if tymax < tmax {
-            tmax = tymax;
-        }
+    /// Largo de la cara a lo largo de sus ejes `u` y `v`, en unidades del mundo
+    fn face_size(&self, face: CubeFace) -> (f32, f32) {
+        let size = self.half_extents * 2.0;
+        let ((ux, uy, uz), (vx, vy, vz)) = face.uv_axes();
+        (
+            Vec3::new(ux as f32, uy as f32, uz as f32).dot(size),
+            Vec3::new(vx as f32, vy as f32, vz as f32).dot(size),
+        )
+    }
 
-        // z slabs
-        let mut tzmin = (min.z - ray_origin.z) * inv_dir.z;
-        let mut tzmax = (max.z - ray_origin.z) * inv_dir.z;
-        if tzmin > tzmax {
-            std::mem::swap(&mut tzmin, &mut tzmax);
+    /// UV de la textura a partir de las geométricas [0,1] de la cara: la
+    /// orientación del material y luego el largo de la cara, así la textura
+    /// se repite una vez por unidad del mundo (un bloque normal queda igual;
+    /// una plataforma de 4×1 la repite cuatro veces en vez de estirarla)
+    fn texture_uv(&self, face: CubeFace, u: f32, v: f32) -> (f32, f32) {
+        let transform = self.material.face_uv[face.index()];
+        let (u, v) = transform.apply(u, v);
+        let (length_u, length_v) = self.face_size(face);
+        // Un cuarto de giro intercambia los ejes de la textura
+        if transform.quarter_turns % 2 == 1 {
+            (u * length_v, v * length_u)
+        } else {
+            (u * length_u, v * length_v)
         }
+    }
 
-        if (tmin > tzmax) || (tzmin > tmax) {
-            return Intersect::empty();
-        }
+    /// Interpolación bilineal del AO de las esquinas de la cara
+    fn ao_at(&self, face: CubeFace, u: f32, v: f32) -> f32 {
+        let [a00, a10, a11, a01] = self.ao[face.index()];
+        let bottom = a00 + (a10 - a00) * u;
+        let top = a01 + (a11 - a01) * u;
+        bottom + (top - bottom) * v
+    }
+}
 
-        if tzmin > tmin {
-            tmin = tzmin;
-        }
-        if tzmax < tmax {
-            tmax = tzmax;
-        }
+impl RayIntersect for Cube {
+    fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Intersect {
+        let min = self.center - self.half_extents;
+        let max = self.center + self.half_extents;
 
-        // Closest intersection distance
-        let t = if tmin > 0.0 { tmin } else { tmax };
-        if t < 0.0 {
+        let Some(slab) = geometry::ray_aabb(ray_origin, ray_direction, min, max) else {
             return Intersect::empty();
-        }
-
-        // Hit point
+        };
+        let (t, axis) = (slab.t, slab.axis);
         let point = *ray_origin + *ray_direction * t;
+        let on_min_side = slab.on_min_side(ray_direction);
 
         // Determine which face was hit
-        let epsilon = 1e-4;
-        let (normal, face, u, v) = if (point.x - min.x).abs() < epsilon {
+        let (normal, face, u, v) = if axis == 0 && on_min_side {
             // Left face (−X), project to Z/Y
             let u = (point.z - min.z) / (max.z - min.z);
             let v = (point.y - min.y) / (max.y - min.y);
-            (Vector3::new(-1.0, 0.0, 0.0), CubeFace::Left, u, v)
-        } else if (point.x - max.x).abs() < epsilon {
+            (Vec3::new(-1.0, 0.0, 0.0), CubeFace::Left, u, v)
+        } else if axis == 0 {
             // Right face (+X)
             let u = (point.z - min.z) / (max.z - min.z);
             let v = (point.y - min.y) / (max.y - min.y);
-            (Vector3::new(1.0, 0.0, 0.0), CubeFace::Right, u, v)
-        } else if (point.y - min.y).abs() < epsilon {
+            (Vec3::new(1.0, 0.0, 0.0), CubeFace::Right, u, v)
+        } else if axis == 1 && on_min_side {
             // Bottom face (−Y)
             let u = (point.x - min.x) / (max.x - min.x);
             let v = (point.z - min.z) / (max.z - min.z);
-            (Vector3::new(0.0, -1.0, 0.0), CubeFace::Bottom, u, v)
-        } else if (point.y - max.y).abs() < epsilon {
+            (Vec3::new(0.0, -1.0, 0.0), CubeFace::Bottom, u, v)
+        } else if axis == 1 {
             // Top face (+Y)
             let u = (point.x - min.x) / (max.x - min.x);
             let v = (point.z - min.z) / (max.z - min.z);
-            (Vector3::new(0.0, 1.0, 0.0), CubeFace::Top, u, v)
-        } else if (point.z - min.z).abs() < epsilon {
+            (Vec3::new(0.0, 1.0, 0.0), CubeFace::Top, u, v)
+        } else if on_min_side {
             // Back face (−Z)
             let u = (point.x - min.x) / (max.x - min.x);
             let v = (point.y - min.y) / (max.y - min.y);
-            (Vector3::new(0.0, 0.0, -1.0), CubeFace::Back, u, v)
+            (Vec3::new(0.0, 0.0, -1.0), CubeFace::Back, u, v)
         } else {
             // Front face (+Z)
             let u = (point.x - min.x) / (max.x - min.x);
             let v = (point.y - min.y) / (max.y - min.y);
-            (Vector3::new(0.0, 0.0, 1.0), CubeFace::Front, u, v)
+            (Vec3::new(0.0, 0.0, 1.0), CubeFace::Front, u, v)
+        };
+
+        // El AO se interpola en las UV geométricas; la textura usa las orientadas
+        let ao = self.ao_at(face, u.clamp(0.0, 1.0), v.clamp(0.0, 1.0));
+        let normal = if self.material.bevel > 0.0 {
+            self.bevel_normal(normal, face, u.clamp(0.0, 1.0), v.clamp(0.0, 1.0))
+        } else {
+            normal
         };
+        let (u, v) = self.texture_uv(face, u, v);
+        let mut hit = Intersect::new(point, normal, t, self.material.clone(), u, v, face);
+        hit.ao = ao;
+        hit
+    }
+
+    fn bounds(&self) -> Option<Aabb> {
+        Some(Aabb::new(self.center - self.half_extents, self.center + self.half_extents))
+    }
 
-        Intersect::new(point, normal, t, self.material.clone(), u, v, face)
+    /// Sin normal, UV ni copia del material si el cubo es opaco
+    fn occlusion(&self, ray_origin: &Vec3, ray_direction: &Vec3, max_t: f32) -> Occlusion {
+        let min = self.center - self.half_extents;
+        let max = self.center + self.half_extents;
+        match geometry::ray_aabb(ray_origin, ray_direction, min, max) {
+            Some(slab) if slab.t < max_t => {
+                if self.material.albedo[3] <= 0.0 {
+                    Occlusion::Opaque
+                } else {
+                    Occlusion::Tinted(Box::new(self.ray_intersect(ray_origin, ray_direction)))
+                }
+            }
+            _ => Occlusion::Clear,
+        }
     }
 }
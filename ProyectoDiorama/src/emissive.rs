@@ -0,0 +1,139 @@
+use std::f32::consts::PI;
+use crate::math::Vec3;
+
+use crate::cube::Cube;
+use crate::material::Flicker;
+use crate::rng::Rng;
+use crate::texture_manager::TextureManager;
+
+/// Punto muestreado sobre la superficie de un bloque emisivo
+#[derive(Debug, Clone, Copy)]
+pub struct LightSample {
+    pub point: Vec3,
+    pub normal: Vec3,
+    /// Inverso de la densidad por área con que se eligió `point`
+    pub area: f32,
+}
+
+/// Bloque que emite luz propia (p. ej. glowstone), usado como luz de área:
+/// en vez de esperar a que un rayo aleatorio lo encuentre, se le apuntan
+/// rayos de sombra directamente
+#[derive(Debug, Clone, Copy)]
+pub struct EmissiveBlock {
+    pub center: Vec3,
+    pub half_extents: Vec3,
+    /// Radiancia emitida (color medio de la textura × `emission`)
+    pub radiance: Vec3,
+    /// Animación del brillo del material, si la tiene
+    pub flicker: Option<Flicker>,
+}
+
+impl EmissiveBlock {
+    /// Los cubos cuyo material tiene `emission > 0`
+    pub fn from_cubes(cubes: &[Cube], tm: &TextureManager) -> Vec<EmissiveBlock> {
+        cubes
+            .iter()
+            .filter(|c| c.material.emission > 0.0)
+            .map(|c| EmissiveBlock {
+                center: c.center,
+                half_extents: c.half_extents,
+                radiance: c.material.emitted(tm),
+                flicker: c.material.flicker,
+            })
+            .collect()
+    }
+
+    /// Radiancia en `time`, con la animación del material
+    pub fn radiance_at(&self, time: f32) -> Vec3 {
+        match self.flicker {
+            Some(flicker) => self.radiance * flicker.factor(time, self.center),
+            None => self.radiance,
+        }
+    }
+
+    /// Caras del bloque que miran a `receiver`, como (eje, signo)
+    fn visible_faces(&self, receiver: Vec3) -> ([(usize, f32); 3], usize) {
+        let local = receiver - self.center;
+        let h = [self.half_extents.x, self.half_extents.y, self.half_extents.z];
+        let l = [local.x, local.y, local.z];
+        let mut faces = [(0, 0.0); 3];
+        let mut count = 0;
+        for axis in 0..3 {
+            if l[axis].abs() > h[axis] {
+                faces[count] = (axis, l[axis].signum());
+                count += 1;
+            }
+        }
+        (faces, count)
+    }
+
+    fn face_area(&self, axis: usize) -> f32 {
+        let h = [self.half_extents.x, self.half_extents.y, self.half_extents.z];
+        4.0 * h[(axis + 1) % 3] * h[(axis + 2) % 3]
+    }
+
+    /// Punto uniforme sobre una de las caras del bloque que miran a
+    /// `receiver`; `None` si el receptor está dentro del bloque
+    pub fn sample(&self, receiver: Vec3, rng: &mut Rng) -> Option<LightSample> {
+        let (faces, count) = self.visible_faces(receiver);
+        if count == 0 {
+            return None;
+        }
+
+        let h = [self.half_extents.x, self.half_extents.y, self.half_extents.z];
+        let (axis, sign) = faces[((rng.next_f32() * count as f32) as usize).min(count - 1)];
+        let (a, b) = ((axis + 1) % 3, (axis + 2) % 3);
+        let mut p = [0.0; 3];
+        let mut n = [0.0; 3];
+        p[axis] = sign * h[axis];
+        p[a] = (rng.next_f32() * 2.0 - 1.0) * h[a];
+        p[b] = (rng.next_f32() * 2.0 - 1.0) * h[b];
+        n[axis] = sign;
+
+        Some(LightSample {
+            point: self.center + Vec3::new(p[0], p[1], p[2]),
+            normal: Vec3::new(n[0], n[1], n[2]),
+            area: count as f32 * self.face_area(axis),
+        })
+    }
+
+    /// Densidad por área con que `sample` elegiría un punto de la cara de
+    /// normal `normal` visto desde `receiver` (0 si esa cara no se ve)
+    pub fn pdf_area(&self, receiver: Vec3, normal: Vec3) -> f32 {
+        let (faces, count) = self.visible_faces(receiver);
+        let axis = if normal.x.abs() > 0.5 {
+            0
+        } else if normal.y.abs() > 0.5 {
+            1
+        } else {
+            2
+        };
+        if faces[..count].iter().any(|&(a, _)| a == axis) {
+            1.0 / (count as f32 * self.face_area(axis))
+        } else {
+            0.0
+        }
+    }
+
+    /// `true` si el bloque aporta al menos `cutoff` de irradiancia en
+    /// `point` (cota por su potencia y la distancia al cuadrado); con
+    /// `cutoff <= 0` alcanza a todo
+    pub fn reaches(&self, point: Vec3, cutoff: f32) -> bool {
+        if cutoff <= 0.0 {
+            return true;
+        }
+        let h = self.half_extents;
+        // Como mucho se ve la mitad de la superficie del bloque
+        let visible_area = 4.0 * (h.x * h.y + h.y * h.z + h.x * h.z);
+        let power = self.radiance.max_component() * visible_area / PI;
+        let d = point - self.center;
+        d.dot(d) * cutoff <= power
+    }
+
+    pub fn contains(&self, point: Vec3) -> bool {
+        let d = point - self.center;
+        d.x.abs() <= self.half_extents.x + 1e-3
+            && d.y.abs() <= self.half_extents.y + 1e-3
+            && d.z.abs() <= self.half_extents.z + 1e-3
+    }
+}
@@ -0,0 +1,67 @@
+use thiserror::Error;
+
+/// Errores del renderizador
+#[derive(Debug, Error)]
+pub enum Error {
+    /// No se pudo abrir o decodificar una textura
+    #[error("no pude abrir textura {path}: {source}")]
+    Texture {
+        path: String,
+        #[source]
+        source: image::ImageError,
+    },
+
+    /// No se pudo abrir el heightmap
+    #[error("no pude abrir heightmap {path}: {source}")]
+    Heightmap {
+        path: String,
+        #[source]
+        source: image::ImageError,
+    },
+
+    /// Error al codificar una imagen (PNG, GIF, ...)
+    #[error("error de imagen: {0}")]
+    Image(#[from] image::ImageError),
+
+    /// Error de entrada/salida (archivos de escena, exportadores)
+    #[error("error de E/S: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Error de sintaxis en un archivo de escena
+    #[error("{path}:{line}: {msg}")]
+    Parse {
+        path: String,
+        line: usize,
+        msg: String,
+    },
+
+    /// Falló un script de generación procedural
+    #[error("error en script {path}: {msg}")]
+    Script { path: String, msg: String },
+
+    /// El render no coincide con su imagen de referencia
+    #[error("regresión de imagen: {0}")]
+    Golden(String),
+
+    /// Argumentos de línea de comandos inválidos
+    #[error("argumentos inválidos: {0}")]
+    Args(String),
+
+    /// El contenido no se puede exportar al formato pedido
+    #[error("no se puede exportar: {0}")]
+    Export(String),
+
+    /// Falló la exportación de video (ffmpeg)
+    #[error("error de video: {0}")]
+    Video(String),
+
+    /// No se pudo armar el pool de hilos de render
+    #[error("no pude crear los hilos de render: {0}")]
+    ThreadPool(#[from] rayon::ThreadPoolBuildError),
+
+    /// No se pudo instalar el manejador de Ctrl+C
+    #[error("no pude instalar el manejador de Ctrl+C: {0}")]
+    Signal(#[from] ctrlc::Error),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
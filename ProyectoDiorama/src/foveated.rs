@@ -0,0 +1,84 @@
+use rayon::prelude::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::camera::Camera;
+use crate::math::Vec3;
+use crate::scene::Scene;
+use crate::{apply_post, trace_pixel};
+
+/// Lado de los bloques en que se decide el nivel de detalle
+const BLOCK: usize = 4;
+
+/// Zona de la imagen que se traza con toda la calidad: un círculo alrededor
+/// del cursor (o del centro de la pantalla). Hasta el doble del radio se
+/// traza un píxel de cada 2×2 y más lejos uno de cada 4×4, siempre con una
+/// sola muestra por píxel.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Foveation {
+    /// Centro en píxeles de la imagen
+    pub center: (f32, f32),
+    /// Radio en píxeles de la zona a calidad completa
+    pub radius: f32,
+}
+
+impl Foveation {
+    /// Paso entre píxeles trazados en el bloque de `BLOCK`×`BLOCK` que
+    /// contiene (x, y): 1, 2 o 4
+    fn step(&self, x: usize, y: usize) -> usize {
+        let bx = (x / BLOCK * BLOCK) as f32 + BLOCK as f32 * 0.5;
+        let by = (y / BLOCK * BLOCK) as f32 + BLOCK as f32 * 0.5;
+        let d = ((bx - self.center.0).powi(2) + (by - self.center.1).powi(2)).sqrt();
+        if d <= self.radius {
+            1
+        } else if d <= self.radius * 2.0 {
+            2
+        } else {
+            BLOCK
+        }
+    }
+}
+
+/// Como `render_linear_cancellable`, pero con la calidad repartida según
+/// `fovea`: los píxeles que no se trazan copian el color del trazado más
+/// cercano arriba a la izquierda
+pub fn render_foveated(
+    width: usize,
+    height: usize,
+    camera: &Camera,
+    scene: &Scene,
+    fovea: &Foveation,
+    cancel: &AtomicBool,
+) -> Option<Vec<Vec3>> {
+    let periphery = Scene { pixel_samples: 1, ..*scene };
+    let rows: Option<Vec<Vec<Option<Vec3>>>> = (0..height)
+        .into_par_iter()
+        .map(|y| {
+            if cancel.load(Ordering::Relaxed) {
+                return None;
+            }
+            let row = (0..width)
+                .map(|x| match fovea.step(x, y) {
+                    1 => Some(trace_pixel(x, y, width, height, camera, scene)),
+                    step if x % step == 0 && y % step == 0 => {
+                        Some(trace_pixel(x, y, width, height, camera, &periphery))
+                    }
+                    _ => None,
+                })
+                .collect();
+            Some(row)
+        })
+        .collect();
+    let traced: Vec<Option<Vec3>> = rows?.into_iter().flatten().collect();
+
+    let mut pixels: Vec<Vec3> = (0..width * height)
+        .map(|idx| {
+            let (x, y) = (idx % width, idx / width);
+            traced[idx].unwrap_or_else(|| {
+                let step = fovea.step(x, y);
+                traced[(y - y % step) * width + (x - x % step)].unwrap_or_default()
+            })
+        })
+        .collect();
+    apply_post(&mut pixels, width, height, camera, scene);
+    Some(pixels)
+}
@@ -2,56 +2,133 @@
 
 use raylib::prelude::*;
 
+use diorama::material::vector3_to_color_at;
+use diorama::math::{Rgba, Vec3};
+
+/// El núcleo trabaja con `Rgba`; raylib recibe su propio `Color`
+fn to_raylib(color: Rgba) -> Color {
+    Color::new(color.r, color.g, color.b, color.a)
+}
+
+/// Framebuffer con doble buffer en color lineal (HDR, sin recortar): se
+/// dibuja en `color_buffer` (el de atrás) y la ventana muestra siempre
+/// `front`, el último cuadro completo, hasta que `swap` los intercambia. La
+/// conversión a 8 bits se hace recién al presentar, en `swap`
 pub struct Framebuffer {
     pub width: u32,
     pub height: u32,
-    pub color_buffer: Image,
-    background_color: Color,
-    current_color: Color,
+    pub color_buffer: Vec<Vec3>,
+    front: Vec<Vec3>,
+    /// `front` cuantizado para mostrarlo; se rehace solo tras un `swap`
+    display: Image,
+    /// Textura de `display` ya subida a la GPU; se rehace solo tras un `swap`
+    texture: Option<Texture2D>,
+    background_color: Vec3,
+    current_color: Vec3,
+    /// Suma de los cuadros acumulados desde el último `reset_accumulation`
+    accumulation: Vec<Vec3>,
+    samples: u32,
+    /// Tramado ordenado al cuantizar en `swap` (`--dither`)
+    pub dither: bool,
 }
 
 impl Framebuffer {
     pub fn new(width: u32, height: u32) -> Self {
-        let color_buffer = Image::gen_image_color(width as i32, height as i32, Color::BLACK);
+        let len = (width * height) as usize;
         Framebuffer {
             width,
             height,
-            color_buffer,
-            background_color: Color::BLACK,
-            current_color: Color::WHITE,
+            color_buffer: vec![Vec3::zero(); len],
+            front: vec![Vec3::zero(); len],
+            display: Image::gen_image_color(width as i32, height as i32, Color::BLACK),
+            texture: None,
+            background_color: Vec3::zero(),
+            current_color: Vec3::new(1.0, 1.0, 1.0),
+            accumulation: vec![Vec3::zero(); len],
+            samples: 0,
+            dither: false,
         }
     }
 
     pub fn clear(&mut self) {
-        self.color_buffer = Image::gen_image_color(self.width as i32, self.height as i32, self.background_color);
+        self.color_buffer.fill(self.background_color);
     }
 
     pub fn set_pixel(&mut self, x: u32, y: u32) {
         if x < self.width && y < self.height {
-            self.color_buffer.draw_pixel(x as i32, y as i32, self.current_color);
+            self.color_buffer[(y * self.width + x) as usize] = self.current_color;
         }
     }
 
-    pub fn set_background_color(&mut self, color: Color) {
-        self.background_color = color;
+    /// Color lineal; puede pasar de 1
+    pub fn set_current_color(&mut self, color: Vec3) {
+        self.current_color = color;
     }
 
-    pub fn set_current_color(&mut self, color: Color) {
-        self.current_color = color;
+    /// Copia un cuadro completo en color lineal (por filas, como lo devuelve
+    /// `diorama::render_linear`), sin perder lo que pasa de 1
+    pub fn blit(&mut self, pixels: &[Vec3]) {
+        for (idx, color) in pixels.iter().enumerate() {
+            let x = idx as u32 % self.width;
+            let y = idx as u32 / self.width;
+            self.set_current_color(*color);
+            self.set_pixel(x, y);
+        }
     }
 
-    pub fn _render_to_file(&self, file_path: &str) {
-        self.color_buffer.export_image(file_path);
+    /// Suma un cuadro más (del mismo punto de vista, con otras muestras) al
+    /// promedio y deja el promedio en el buffer de atrás. Refinamiento
+    /// progresivo, reproyección o denoiser trabajan sobre este promedio
+    pub fn accumulate(&mut self, sample_buffer: &[Vec3]) {
+        for (sum, sample) in self.accumulation.iter_mut().zip(sample_buffer) {
+            *sum += *sample;
+        }
+        self.samples += 1;
+        let inv = 1.0 / self.samples as f32;
+        for (target, sum) in self.color_buffer.iter_mut().zip(&self.accumulation) {
+            *target = *sum * inv;
+        }
     }
 
+    /// Descarta lo acumulado (la cámara o la escena cambiaron); el buffer de
+    /// atrás no se toca hasta el próximo `accumulate`
+    pub fn reset_accumulation(&mut self) {
+        self.accumulation.fill(Vec3::zero());
+        self.samples = 0;
+    }
+
+    /// Cuadros promediados desde el último `reset_accumulation`
+    pub fn accumulated_samples(&self) -> u32 {
+        self.samples
+    }
+
+    /// El buffer de atrás pasa a mostrarse, cuantizado (con tramado si
+    /// `dither`); el de adelante queda para el próximo cuadro
+    pub fn swap(&mut self) {
+        std::mem::swap(&mut self.color_buffer, &mut self.front);
+        let width = self.width as usize;
+        for (idx, color) in self.front.iter().enumerate() {
+            let pixel = to_raylib(vector3_to_color_at(*color, idx % width, idx / width, self.dither));
+            self.display.draw_pixel((idx % width) as i32, (idx / width) as i32, pixel);
+        }
+        self.texture = None;
+    }
+
+    /// Dibuja en la ventana el último cuadro completo y encima lo que
+    /// agregue `overlay` (retícula, textos)
     pub fn swap_buffers(
-        &self,
+        &mut self,
         window: &mut RaylibHandle,
         raylib_thread: &RaylibThread,
+        overlay: impl FnOnce(&mut RaylibDrawHandle),
     ) {
-        if let Ok(texture) = window.load_texture_from_image(raylib_thread, &self.color_buffer) {
-            let mut renderer = window.begin_drawing(raylib_thread);
-            renderer.draw_texture(&texture, 0, 0, Color::WHITE);
+        if self.texture.is_none() {
+            self.texture = window.load_texture_from_image(raylib_thread, &self.display).ok();
         }
+        let mut renderer = window.begin_drawing(raylib_thread);
+        if let Some(texture) = &self.texture {
+            renderer.draw_texture(texture, 0, 0, Color::WHITE);
+        }
+        overlay(&mut renderer);
     }
-}
\ No newline at end of file
+}
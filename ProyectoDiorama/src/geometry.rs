@@ -0,0 +1,335 @@
+//! Núcleo geométrico del trazador: intersecciones rayo–primitiva y las
+//! direcciones de reflexión y refracción. Las primitivas (`Cube`,
+//! `WaterPlane`, partículas) se construyen sobre estas funciones.
+//!
+//! Convenciones: `direction` no necesita estar normalizada, pero las
+//! distancias `t` devueltas están en unidades de `direction` (el punto es
+//! `origin + direction * t`). Solo se reportan impactos con `t >= 0`.
+
+use crate::math::Vec3;
+
+/// Impacto de un rayo con una caja alineada a los ejes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SlabHit {
+    /// Parámetro del rayo en el impacto
+    pub t: f32,
+    /// Eje del plano cruzado: 0 = X, 1 = Y, 2 = Z
+    pub axis: usize,
+    /// `true` si el rayo entra a la caja, `false` si sale (origen adentro)
+    pub entering: bool,
+}
+
+impl SlabHit {
+    /// `true` si el plano cruzado es el del mínimo de la caja en `axis`
+    pub fn on_min_side(&self, direction: &Vec3) -> bool {
+        let d = [direction.x, direction.y, direction.z][self.axis];
+        // Entrar viajando hacia +eje es cruzar el plano mínimo
+        (d > 0.0) == self.entering
+    }
+}
+
+/// Intersección con la caja `[min, max]` por el método de slabs.
+///
+/// Es robusta ante rayos paralelos a un eje (componente exactamente cero):
+/// esos solo impactan si el origen ya está entre los dos planos. Guarda qué
+/// eje limita la entrada y la salida, así la cara impactada no depende de
+/// comparar flotantes. Si el origen está dentro, devuelve la salida.
+pub fn ray_aabb(origin: &Vec3, direction: &Vec3, min: Vec3, max: Vec3) -> Option<SlabHit> {
+    let o = [origin.x, origin.y, origin.z];
+    let d = [direction.x, direction.y, direction.z];
+    let lo = [min.x, min.y, min.z];
+    let hi = [max.x, max.y, max.z];
+
+    let mut tmin = f32::NEG_INFINITY;
+    let mut tmax = f32::INFINITY;
+    let mut enter_axis = 0;
+    let mut exit_axis = 0;
+    for axis in 0..3 {
+        if d[axis] == 0.0 {
+            if o[axis] < lo[axis] || o[axis] > hi[axis] {
+                return None;
+            }
+            continue;
+        }
+        let inv = 1.0 / d[axis];
+        let mut t0 = (lo[axis] - o[axis]) * inv;
+        let mut t1 = (hi[axis] - o[axis]) * inv;
+        if t0 > t1 {
+            std::mem::swap(&mut t0, &mut t1);
+        }
+        if t0 > tmin {
+            tmin = t0;
+            enter_axis = axis;
+        }
+        if t1 < tmax {
+            tmax = t1;
+            exit_axis = axis;
+        }
+        if tmin > tmax {
+            return None;
+        }
+    }
+
+    let hit = if tmin > 0.0 {
+        SlabHit { t: tmin, axis: enter_axis, entering: true }
+    } else {
+        SlabHit { t: tmax, axis: exit_axis, entering: false }
+    };
+    (hit.t >= 0.0 && hit.t.is_finite()).then_some(hit)
+}
+
+/// Caja alineada a los ejes que envuelve un objeto
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Aabb {
+    pub fn new(min: Vec3, max: Vec3) -> Self {
+        Aabb { min, max }
+    }
+
+    /// La menor caja que contiene todos los puntos; `None` si no hay puntos
+    pub fn from_points(points: impl IntoIterator<Item = Vec3>) -> Option<Aabb> {
+        points.into_iter().fold(None, |acc, p| {
+            Some(match acc {
+                None => Aabb::new(p, p),
+                Some(b) => b.union(&Aabb::new(p, p)),
+            })
+        })
+    }
+
+    pub fn union(&self, other: &Aabb) -> Aabb {
+        Aabb {
+            min: Vec3::new(self.min.x.min(other.min.x), self.min.y.min(other.min.y), self.min.z.min(other.min.z)),
+            max: Vec3::new(self.max.x.max(other.max.x), self.max.y.max(other.max.y), self.max.z.max(other.max.z)),
+        }
+    }
+
+    pub fn center(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn corners(&self) -> [Vec3; 8] {
+        let (a, b) = (self.min, self.max);
+        [
+            Vec3::new(a.x, a.y, a.z),
+            Vec3::new(b.x, a.y, a.z),
+            Vec3::new(a.x, b.y, a.z),
+            Vec3::new(b.x, b.y, a.z),
+            Vec3::new(a.x, a.y, b.z),
+            Vec3::new(b.x, a.y, b.z),
+            Vec3::new(a.x, b.y, b.z),
+            Vec3::new(b.x, b.y, b.z),
+        ]
+    }
+
+    /// `true` si el rayo toca la caja antes de `max_t` (o sale desde adentro)
+    pub fn hit(&self, origin: &Vec3, direction: &Vec3, max_t: f32) -> bool {
+        ray_aabb(origin, direction, self.min, self.max).is_some_and(|h| !h.entering || h.t <= max_t)
+    }
+}
+
+/// Intersección con el plano que pasa por `point` con normal `normal`
+/// (no hace falta normalizarla). Rayos casi paralelos no impactan.
+pub fn ray_plane(origin: &Vec3, direction: &Vec3, point: Vec3, normal: Vec3) -> Option<f32> {
+    let denom = direction.dot(normal);
+    if denom.abs() < 1e-6 {
+        return None;
+    }
+    let t = (point - *origin).dot(normal) / denom;
+    (t > 0.0).then_some(t)
+}
+
+/// Intersección con la esfera de centro `center` y radio `radius`. Devuelve
+/// el impacto más cercano por delante del origen (la salida si el origen
+/// está dentro).
+pub fn ray_sphere(origin: &Vec3, direction: &Vec3, center: Vec3, radius: f32) -> Option<f32> {
+    let oc = *origin - center;
+    let a = direction.dot(*direction);
+    let half_b = oc.dot(*direction);
+    let c = oc.dot(oc) - radius * radius;
+    let discriminant = half_b * half_b - a * c;
+    if a == 0.0 || discriminant < 0.0 {
+        return None;
+    }
+    let sqrt_d = discriminant.sqrt();
+    let near = (-half_b - sqrt_d) / a;
+    let far = (-half_b + sqrt_d) / a;
+    if near >= 0.0 {
+        Some(near)
+    } else if far >= 0.0 {
+        Some(far)
+    } else {
+        None
+    }
+}
+
+/// Refleja `incident` respecto de `normal` (normalizada). Conserva la
+/// longitud de `incident`.
+pub fn reflect(incident: &Vec3, normal: &Vec3) -> Vec3 {
+    *incident - *normal * 2.0 * incident.dot(*normal)
+}
+
+/// Dirección refractada por la ley de Snell al pasar de aire a un medio de
+/// índice `refractive_index`, o al revés si `incident` sale por el lado de
+/// la normal. `None` en reflexión interna total.
+pub fn refract(incident: &Vec3, normal: &Vec3, refractive_index: f32) -> Option<Vec3> {
+    let mut cosi = incident.dot(*normal).clamp(-1.0, 1.0);
+
+    let mut etai = 1.0;
+    let mut etat = refractive_index;
+    let mut n = *normal;
+
+    if cosi > 0.0 {
+        std::mem::swap(&mut etai, &mut etat);
+        n = -n;
+    } else {
+        cosi = -cosi;
+    }
+
+    let eta = etai / etat;
+    let k = 1.0 - eta * eta * (1.0 - cosi * cosi);
+
+    if k < 0.0 {
+        None
+    } else {
+        Some(*incident * eta + n * (eta * cosi - k.sqrt()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit_box() -> (Vec3, Vec3) {
+        (Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 1.0, 1.0))
+    }
+
+    #[test]
+    fn aabb_zero_component_inside_slab_hits() {
+        let (min, max) = unit_box();
+        // Paralelo a Y y Z, a la altura de la caja
+        let hit = ray_aabb(&Vec3::new(-1.0, 0.5, 0.5), &Vec3::new(1.0, 0.0, 0.0), min, max).unwrap();
+        assert_eq!(hit, SlabHit { t: 1.0, axis: 0, entering: true });
+    }
+
+    #[test]
+    fn aabb_zero_component_outside_slab_misses() {
+        let (min, max) = unit_box();
+        // Paralelo a Y pero por encima de la caja
+        assert_eq!(ray_aabb(&Vec3::new(-1.0, 1.5, 0.5), &Vec3::new(1.0, 0.0, 0.0), min, max), None);
+    }
+
+    #[test]
+    fn aabb_zero_component_on_slab_boundary_hits() {
+        let (min, max) = unit_box();
+        // Rozando la cara de arriba: el borde cuenta como dentro del slab
+        let hit = ray_aabb(&Vec3::new(-1.0, 1.0, 0.5), &Vec3::new(1.0, 0.0, 0.0), min, max).unwrap();
+        assert_eq!(hit.t, 1.0);
+        assert_eq!(hit.axis, 0);
+    }
+
+    #[test]
+    fn aabb_origin_on_slab_plane() {
+        let (min, max) = unit_box();
+        // Sobre la cara mínima de X, hacia adentro: cuenta como dentro y
+        // devuelve la salida por la cara opuesta
+        let inward = ray_aabb(&Vec3::new(0.0, 0.5, 0.5), &Vec3::new(1.0, 0.0, 0.0), min, max).unwrap();
+        assert_eq!(inward, SlabHit { t: 1.0, axis: 0, entering: false });
+        assert!(!inward.on_min_side(&Vec3::new(1.0, 0.0, 0.0)));
+        // Sobre la cara máxima, hacia afuera: sale en t = 0
+        let outward = ray_aabb(&Vec3::new(1.0, 0.5, 0.5), &Vec3::new(1.0, 0.0, 0.0), min, max).unwrap();
+        assert_eq!(outward, SlabHit { t: 0.0, axis: 0, entering: false });
+    }
+
+    #[test]
+    fn aabb_origin_inside_returns_exit() {
+        let (min, max) = unit_box();
+        let direction = Vec3::new(0.0, -2.0, 0.0);
+        let hit = ray_aabb(&Vec3::new(0.5, 0.25, 0.5), &direction, min, max).unwrap();
+        assert_eq!(hit, SlabHit { t: 0.125, axis: 1, entering: false });
+        assert!(hit.on_min_side(&direction));
+    }
+
+    #[test]
+    fn aabb_behind_origin_misses() {
+        let (min, max) = unit_box();
+        assert_eq!(ray_aabb(&Vec3::new(2.0, 0.5, 0.5), &Vec3::new(1.0, 0.0, 0.0), min, max), None);
+    }
+
+    /// Rayos variados (sin normalizar) que apuntan más o menos al origen
+    fn rays() -> Vec<(Vec3, Vec3)> {
+        vec![
+            (Vec3::new(-3.0, 0.4, 0.3), Vec3::new(1.0, 0.0, 0.0)),
+            (Vec3::new(2.5, 3.0, -1.5), Vec3::new(-2.0, -2.5, 1.0)),
+            (Vec3::new(0.3, -4.0, 0.6), Vec3::new(0.1, 3.0, -0.05)),
+            (Vec3::new(0.5, 0.5, 0.5), Vec3::new(0.3, -0.7, 0.2)),
+        ]
+    }
+
+    #[test]
+    fn aabb_hit_point_on_surface() {
+        let (min, max) = (Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.5, 2.0));
+        for (origin, direction) in rays() {
+            let hit = ray_aabb(&origin, &direction, min, max).unwrap();
+            let p = origin + direction * hit.t;
+            let (p, lo, hi) = ([p.x, p.y, p.z], [min.x, min.y, min.z], [max.x, max.y, max.z]);
+            let face = if hit.on_min_side(&direction) { lo[hit.axis] } else { hi[hit.axis] };
+            assert!((p[hit.axis] - face).abs() < 1e-4, "{:?} fuera de la cara", p);
+            for axis in 0..3 {
+                assert!(p[axis] >= lo[axis] - 1e-4 && p[axis] <= hi[axis] + 1e-4, "{:?} fuera de la caja", p);
+            }
+        }
+    }
+
+    #[test]
+    fn plane_hit_point_on_surface() {
+        let (point, normal) = (Vec3::new(0.0, 0.25, 0.0), Vec3::new(0.2, 1.0, -0.1));
+        let mut hits = 0;
+        for (origin, direction) in rays() {
+            if let Some(t) = ray_plane(&origin, &direction, point, normal) {
+                let p = origin + direction * t;
+                assert!((p - point).dot(normal).abs() < 1e-4, "{:?} fuera del plano", p);
+                hits += 1;
+            }
+        }
+        assert!(hits >= 2);
+        assert_eq!(ray_plane(&Vec3::new(0.0, 1.0, 0.0), &Vec3::new(1.0, 0.0, 0.0), Vec3::zero(), Vec3::new(0.0, 1.0, 0.0)), None);
+    }
+
+    #[test]
+    fn sphere_hit_point_on_surface() {
+        let (center, radius) = (Vec3::new(0.2, 0.1, -0.3), 1.3);
+        for (origin, direction) in rays() {
+            let t = ray_sphere(&origin, &direction, center, radius).unwrap();
+            let p = origin + direction * t;
+            assert!(((p - center).length() - radius).abs() < 1e-4, "{:?} fuera de la esfera", p);
+        }
+    }
+
+    #[test]
+    fn reflect_preserves_length() {
+        let normal = Vec3::new(0.3, 0.9, -0.2).normalized();
+        for (_, direction) in rays() {
+            let reflected = reflect(&direction, &normal);
+            assert!((reflected.length() - direction.length()).abs() < 1e-5);
+            // El componente normal se invierte
+            assert!((reflected.dot(normal) + direction.dot(normal)).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn refract_total_internal_reflection() {
+        let normal = Vec3::new(0.0, 1.0, 0.0);
+        // Saliendo del vidrio (1.5) casi rasante: ángulo crítico ~41.8°
+        let grazing = Vec3::new(0.9, 0.2, 0.0).normalized();
+        assert_eq!(refract(&grazing, &normal, 1.5), None);
+        // Saliendo de frente sí pasa
+        let straight = Vec3::new(0.1, 1.0, 0.0).normalized();
+        assert!(refract(&straight, &normal, 1.5).is_some());
+        // Entrando al vidrio nunca hay reflexión interna total
+        assert!(refract(&-grazing, &normal, 1.5).is_some());
+    }
+}
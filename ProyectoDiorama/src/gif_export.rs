@@ -0,0 +1,43 @@
+use std::fs::File;
+use std::io::BufWriter;
+
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{Delay, Frame, RgbaImage};
+
+use crate::animation::turntable_camera;
+use crate::camera::Camera;
+use crate::error::{Error, Result};
+use crate::scene::Scene;
+
+/// Resolución y duración de la vista previa animada
+pub const GIF_WIDTH: u32 = 320;
+pub const GIF_HEIGHT: u32 = 220;
+pub const GIF_FRAMES: u32 = 36;
+const GIF_FRAME_DELAY_MS: u32 = 80;
+
+/// Renderiza una vuelta de 360° a baja resolución y la guarda como GIF animado
+pub fn export_turntable_gif(
+    path: &str,
+    camera: &Camera,
+    scene: &Scene,
+) -> Result<()> {
+    let file = BufWriter::new(File::create(path)?);
+    let mut encoder = GifEncoder::new_with_speed(file, 10);
+    encoder.set_repeat(Repeat::Infinite)?;
+
+    for frame in 0..GIF_FRAMES {
+        let frame_camera = turntable_camera(camera, frame, GIF_FRAMES);
+        let rgba = crate::render_rgba(
+            GIF_WIDTH as usize,
+            GIF_HEIGHT as usize,
+            &frame_camera,
+            &Scene { time: frame as f32 * GIF_FRAME_DELAY_MS as f32 / 1000.0, ..*scene },
+        );
+        let buffer = RgbaImage::from_raw(GIF_WIDTH, GIF_HEIGHT, rgba)
+            .ok_or_else(|| Error::Video("buffer de cuadro inválido".to_string()))?;
+        let delay = Delay::from_numer_denom_ms(GIF_FRAME_DELAY_MS, 1);
+        encoder.encode_frame(Frame::from_parts(buffer, 0, 0, delay))?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,232 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+use image::codecs::png::PngEncoder;
+use image::{ExtendedColorType, ImageEncoder};
+
+use crate::error::Result;
+use crate::material::{Material, MaterialLibrary};
+use crate::texture_manager::TextureManager;
+use crate::voxel_world::VoxelWorld;
+
+const GLB_MAGIC: u32 = 0x4654_6C67; // "glTF"
+const CHUNK_JSON: u32 = 0x4E4F_534A;
+const CHUNK_BIN: u32 = 0x004E_4942;
+
+const FLOAT: u32 = 5126;
+const UNSIGNED_INT: u32 = 5125;
+const ARRAY_BUFFER: u32 = 34962;
+const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+const NEAREST: u32 = 9728;
+
+/// UV de las esquinas de `CubeFace::corners` (glTF usa v hacia abajo)
+const CORNER_UVS: [(f32, f32); 4] = [(0.0, 1.0), (1.0, 1.0), (1.0, 0.0), (0.0, 0.0)];
+
+/// Acumula el buffer binario y las listas JSON del archivo glTF
+#[derive(Default)]
+struct GltfBuilder {
+    bin: Vec<u8>,
+    buffer_views: Vec<String>,
+    accessors: Vec<String>,
+    images: Vec<String>,
+    textures: Vec<String>,
+    materials: Vec<String>,
+    primitives: Vec<String>,
+}
+
+impl GltfBuilder {
+    /// Agrega bytes alineados a 4 al buffer y devuelve el índice del bufferView
+    fn push_view(&mut self, bytes: &[u8], target: Option<u32>) -> usize {
+        while !self.bin.len().is_multiple_of(4) {
+            self.bin.push(0);
+        }
+        let offset = self.bin.len();
+        self.bin.extend_from_slice(bytes);
+
+        let target = target.map(|t| format!(r#","target":{}"#, t)).unwrap_or_default();
+        self.buffer_views.push(format!(
+            r#"{{"buffer":0,"byteOffset":{},"byteLength":{}{}}}"#,
+            offset,
+            bytes.len(),
+            target
+        ));
+        self.buffer_views.len() - 1
+    }
+
+    fn push_floats(&mut self, values: &[f32], kind: &str, components: usize, bounds: bool) -> usize {
+        let bytes: Vec<u8> = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+        let view = self.push_view(&bytes, Some(ARRAY_BUFFER));
+
+        let bounds = if bounds {
+            let mut min = vec![f32::INFINITY; components];
+            let mut max = vec![f32::NEG_INFINITY; components];
+            for chunk in values.chunks(components) {
+                for (i, v) in chunk.iter().enumerate() {
+                    min[i] = min[i].min(*v);
+                    max[i] = max[i].max(*v);
+                }
+            }
+            format!(r#","min":{:?},"max":{:?}"#, min, max)
+        } else {
+            String::new()
+        };
+
+        self.accessors.push(format!(
+            r#"{{"bufferView":{},"componentType":{},"count":{},"type":"{}"{}}}"#,
+            view,
+            FLOAT,
+            values.len() / components,
+            kind,
+            bounds
+        ));
+        self.accessors.len() - 1
+    }
+
+    fn push_indices(&mut self, indices: &[u32]) -> usize {
+        let bytes: Vec<u8> = indices.iter().flat_map(|v| v.to_le_bytes()).collect();
+        let view = self.push_view(&bytes, Some(ELEMENT_ARRAY_BUFFER));
+        self.accessors.push(format!(
+            r#"{{"bufferView":{},"componentType":{},"count":{},"type":"SCALAR"}}"#,
+            view,
+            UNSIGNED_INT,
+            indices.len()
+        ));
+        self.accessors.len() - 1
+    }
+
+    /// Codifica la textura como PNG dentro del buffer y devuelve su índice
+    fn push_texture(&mut self, key: char, tm: &TextureManager) -> Result<Option<usize>> {
+        let Some(tex) = tm.images.get(&key) else {
+            return Ok(None);
+        };
+        let mut png = Vec::new();
+        PngEncoder::new(&mut png).write_image(&tex.data, tex.width, tex.height, ExtendedColorType::Rgba8)?;
+
+        let view = self.push_view(&png, None);
+        self.images.push(format!(r#"{{"bufferView":{},"mimeType":"image/png"}}"#, view));
+        self.textures.push(format!(r#"{{"sampler":0,"source":{}}}"#, self.images.len() - 1));
+        Ok(Some(self.textures.len() - 1))
+    }
+
+    fn push_material(&mut self, symbol: char, mat: &Material, texture: Option<usize>) -> usize {
+        // Exponente de Phong a rugosidad (aprox. Blinn-Phong ↔ GGX)
+        let roughness = (2.0 / (mat.specular + 2.0)).sqrt().sqrt().clamp(0.0, 1.0);
+        let base = match texture {
+            Some(t) => format!(r#""baseColorTexture":{{"index":{}}}"#, t),
+            None => format!(
+                r#""baseColorFactor":[{},{},{},1.0]"#,
+                mat.diffuse.x, mat.diffuse.y, mat.diffuse.z
+            ),
+        };
+        self.materials.push(format!(
+            r#"{{"name":"mat_{}","pbrMetallicRoughness":{{{},"metallicFactor":0.0,"roughnessFactor":{}}}}}"#,
+            symbol, base, roughness
+        ));
+        self.materials.len() - 1
+    }
+
+    fn json(&self) -> String {
+        let mut json = format!(
+            r#"{{"asset":{{"version":"2.0","generator":"ProyectoDiorama"}},"scene":0,"scenes":[{{"nodes":[0]}}],"nodes":[{{"mesh":0,"name":"diorama"}}],"meshes":[{{"primitives":[{}]}}],"buffers":[{{"byteLength":{}}}],"bufferViews":[{}],"accessors":[{}],"materials":[{}]"#,
+            self.primitives.join(","),
+            self.bin.len(),
+            self.buffer_views.join(","),
+            self.accessors.join(","),
+            self.materials.join(","),
+        );
+        if !self.textures.is_empty() {
+            json.push_str(&format!(
+                r#","samplers":[{{"magFilter":{n},"minFilter":{n}}}],"images":[{}],"textures":[{}]"#,
+                self.images.join(","),
+                self.textures.join(","),
+                n = NEAREST
+            ));
+        }
+        json.push('}');
+        json
+    }
+}
+
+/// Exporta las caras visibles del mundo como glTF 2.0 binario (.glb), con las
+/// texturas de los bloques embebidas como PNG
+pub fn export_gltf(
+    path: &str,
+    world: &VoxelWorld,
+    materials: &MaterialLibrary,
+    tm: &TextureManager,
+) -> Result<()> {
+    let faces = world.exposed_faces();
+    let mut symbols: Vec<char> = faces.iter().map(|f| f.symbol).collect();
+    symbols.sort();
+    symbols.dedup();
+
+    let mut gltf = GltfBuilder::default();
+    let mut texture_indices: HashMap<char, Option<usize>> = HashMap::new();
+
+    for symbol in symbols {
+        let Some(mat) = materials.get(symbol) else { continue };
+
+        let mut positions = Vec::new();
+        let mut normals = Vec::new();
+        let mut uvs = Vec::new();
+        let mut indices = Vec::new();
+        for f in faces.iter().filter(|f| f.symbol == symbol) {
+            let base = (positions.len() / 3) as u32;
+            let (x, y, z) = f.position;
+            let n = f.face.normal();
+            for (c, (u, v)) in f.face.corners().iter().zip(CORNER_UVS) {
+                positions.extend([x as f32 + c.x, y as f32 + c.y, z as f32 + c.z]);
+                normals.extend([n.x, n.y, n.z]);
+                uvs.extend([u, v]);
+            }
+            indices.extend([base, base + 1, base + 2, base, base + 2, base + 3]);
+        }
+
+        let texture = match mat.texture_key {
+            Some(key) => match texture_indices.get(&key) {
+                Some(t) => *t,
+                None => {
+                    let t = gltf.push_texture(key, tm)?;
+                    texture_indices.insert(key, t);
+                    t
+                }
+            },
+            None => None,
+        };
+        let material = gltf.push_material(symbol, mat, texture);
+
+        let position = gltf.push_floats(&positions, "VEC3", 3, true);
+        let normal = gltf.push_floats(&normals, "VEC3", 3, false);
+        let texcoord = gltf.push_floats(&uvs, "VEC2", 2, false);
+        let index = gltf.push_indices(&indices);
+        gltf.primitives.push(format!(
+            r#"{{"attributes":{{"POSITION":{},"NORMAL":{},"TEXCOORD_0":{}}},"indices":{},"material":{}}}"#,
+            position, normal, texcoord, index, material
+        ));
+    }
+
+    let mut json = gltf.json().into_bytes();
+    while json.len() % 4 != 0 {
+        json.push(b' ');
+    }
+    let mut bin = gltf.bin;
+    while bin.len() % 4 != 0 {
+        bin.push(0);
+    }
+    let total = 12 + 8 + json.len() + 8 + bin.len();
+
+    let mut out = BufWriter::new(File::create(path)?);
+    out.write_all(&GLB_MAGIC.to_le_bytes())?;
+    out.write_all(&2u32.to_le_bytes())?;
+    out.write_all(&(total as u32).to_le_bytes())?;
+    out.write_all(&(json.len() as u32).to_le_bytes())?;
+    out.write_all(&CHUNK_JSON.to_le_bytes())?;
+    out.write_all(&json)?;
+    out.write_all(&(bin.len() as u32).to_le_bytes())?;
+    out.write_all(&CHUNK_BIN.to_le_bytes())?;
+    out.write_all(&bin)?;
+    out.flush()?;
+
+    Ok(())
+}
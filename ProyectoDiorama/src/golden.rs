@@ -0,0 +1,59 @@
+use std::path::Path;
+
+use crate::error::{Error, Result};
+use crate::textures::Texture;
+
+/// Resultado de comparar un render contra su imagen de referencia
+#[derive(Debug, Clone, Copy)]
+pub struct GoldenReport {
+    /// Mayor diferencia encontrada en un canal (0–255)
+    pub max_diff: u8,
+    /// Píxeles con algún canal fuera de la tolerancia
+    pub mismatched: usize,
+    pub total: usize,
+}
+
+impl GoldenReport {
+    /// Se acepta hasta un 0.1% de píxeles distintos (bordes de sombras con
+    /// ruido de punto flotante entre plataformas)
+    pub fn passed(&self) -> bool {
+        self.mismatched * 1000 <= self.total
+    }
+}
+
+/// Compara `rendered` con `reference` canal por canal; un píxel difiere si
+/// algún canal se aleja más de `tolerance`. Que falte la referencia es un
+/// error: se crean a propósito (`GOLDEN_UPDATE=1 golden/run.sh`), nunca
+/// copiando el render que se quiere probar.
+pub fn check(rendered: &str, reference: &str, tolerance: u8) -> Result<GoldenReport> {
+    if !Path::new(reference).exists() {
+        return Err(Error::Golden(format!(
+            "falta la referencia {}; créala con GOLDEN_UPDATE=1 golden/run.sh",
+            reference
+        )));
+    }
+
+    let actual = Texture::load(rendered)?;
+    let expected = Texture::load(reference)?;
+    if (actual.width, actual.height) != (expected.width, expected.height) {
+        return Err(Error::Golden(format!(
+            "{} mide {}x{} pero la referencia {} mide {}x{}",
+            rendered, actual.width, actual.height, reference, expected.width, expected.height
+        )));
+    }
+
+    let mut report = GoldenReport {
+        max_diff: 0,
+        mismatched: 0,
+        total: (actual.width * actual.height) as usize,
+    };
+    for (a, e) in actual.data.chunks_exact(4).zip(expected.data.chunks_exact(4)) {
+        // Alpha no cuenta: el render siempre es opaco
+        let diff = (0..3).map(|i| a[i].abs_diff(e[i])).max().unwrap_or(0);
+        report.max_diff = report.max_diff.max(diff);
+        if diff > tolerance {
+            report.mismatched += 1;
+        }
+    }
+    Ok(report)
+}
@@ -0,0 +1,79 @@
+use crate::error::{Error, Result};
+use crate::voxel_world::VoxelWorld;
+
+/// Banda de altura: las columnas con altura normalizada <= `max_height`
+/// (y mayor que la banda anterior) se construyen con `symbol`
+#[derive(Debug, Clone, Copy)]
+pub struct HeightBand {
+    pub max_height: f32,
+    pub symbol: char,
+}
+
+/// Parámetros de extrusión de un heightmap
+#[derive(Debug, Clone)]
+pub struct HeightmapOptions {
+    /// Altura en vóxeles de un píxel blanco
+    pub max_height: i32,
+    /// Celda de la esquina (x, z) del mapa; `y` es la base de las columnas
+    pub origin: (i32, i32, i32),
+    /// Bandas ordenadas de menor a mayor `max_height`
+    pub bands: Vec<HeightBand>,
+}
+
+/// Lee bandas con el formato `0.3:B,0.7:W,1.0:Q`
+pub fn parse_bands(spec: &str) -> Result<Vec<HeightBand>> {
+    let mut bands = Vec::new();
+    for part in spec.split(',') {
+        let (h, s) = part
+            .split_once(':')
+            .ok_or_else(|| Error::Args(format!("banda inválida: {}", part)))?;
+        let max_height: f32 = h
+            .trim()
+            .parse()
+            .map_err(|_| Error::Args(format!("altura inválida en banda: {}", part)))?;
+        let symbol = s
+            .trim()
+            .chars()
+            .next()
+            .ok_or_else(|| Error::Args(format!("falta el símbolo en banda: {}", part)))?;
+        bands.push(HeightBand { max_height, symbol });
+    }
+    bands.sort_by(|a, b| a.max_height.total_cmp(&b.max_height));
+    Ok(bands)
+}
+
+/// Extruye un PNG en escala de grises como columnas de vóxeles: cada píxel
+/// (x, z) se vuelve una columna de altura proporcional a su luminancia.
+/// Las celdas ya ocupadas en `world` no se sobreescriben.
+pub fn stamp_heightmap(world: &mut VoxelWorld, path: &str, options: &HeightmapOptions) -> Result<usize> {
+    let img = image::open(path)
+        .map_err(|source| Error::Heightmap { path: path.to_string(), source })?
+        .to_luma8();
+
+    let (ox, oy, oz) = options.origin;
+    let mut placed = 0;
+    for z in 0..img.height() {
+        for x in 0..img.width() {
+            let level = img.get_pixel(x, z).0[0] as f32 / 255.0;
+            let height = ((level * options.max_height as f32).round() as i32).max(1);
+
+            let Some(band) = options
+                .bands
+                .iter()
+                .find(|b| level <= b.max_height)
+                .or(options.bands.last())
+            else {
+                continue;
+            };
+
+            for y in 0..height {
+                let pos = (ox + x as i32, oy + y, oz + z as i32);
+                if !world.is_solid(pos) {
+                    world.set(pos, band.symbol.to_ascii_uppercase());
+                    placed += 1;
+                }
+            }
+        }
+    }
+    Ok(placed)
+}
@@ -0,0 +1,170 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::error::{Error, Result};
+use crate::material::vector3_to_color_at;
+use crate::math::{Rgba, Vec3};
+
+/// Opciones de los formatos que las admiten
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SaveOptions {
+    /// PPM en texto (P3) en lugar de binario (P6)
+    pub ppm_ascii: bool,
+    /// PNG de 16 bits por canal: sin bandas en degradados suaves como el cielo
+    pub png16: bool,
+    /// Tramado ordenado al cuantizar a 8 bits (menos bandas en el cielo, pero
+    /// cada píxel queda corrido hasta un escalón: no sirve para comparar)
+    pub dither: bool,
+}
+
+/// Guarda un cuadro lineal (por filas, como lo devuelve `render_offline`) en
+/// `path`. El formato sale de la extensión:
+///
+/// - `.exr` (OpenEXR) y `.hdr` (Radiance): flotantes de 32 bits sin
+///   recortar, para ajustar la exposición en herramientas externas
+/// - `.pfm`: flotantes sin recortar, ver `write_pfm`
+/// - `.ppm`: 8 bits, binario o texto según `options.ppm_ascii`
+/// - `.png` con `options.png16`: 16 bits por canal, recortado a [0, 1]
+/// - cualquier otra (PNG, JPG, BMP, ...): 8 bits por canal, recortado a [0, 1]
+pub fn save(path: &str, width: usize, height: usize, pixels: &[Vec3], options: &SaveOptions) -> Result<()> {
+    let extension = Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_ascii_lowercase();
+
+    match extension.as_str() {
+        "ppm" => {
+            let mut out = BufWriter::new(File::create(path)?);
+            write_ppm(&mut out, width, height, pixels, options)?;
+            out.flush()?;
+        }
+        "pfm" => {
+            let mut out = BufWriter::new(File::create(path)?);
+            write_pfm(&mut out, width, height, pixels)?;
+            out.flush()?;
+        }
+        "png" if options.png16 => {
+            let bytes: Vec<u8> = pixels
+                .iter()
+                .flat_map(|c| [c.x, c.y, c.z])
+                .map(|f| (f.clamp(0.0, 1.0) * 65535.0).round() as u16)
+                .flat_map(|v| v.to_ne_bytes())
+                .collect();
+            image::save_buffer(path, &bytes, width as u32, height as u32, image::ExtendedColorType::Rgb16)?;
+        }
+        "exr" | "hdr" => {
+            let floats: Vec<f32> = pixels.iter().flat_map(|c| [c.x, c.y, c.z]).collect();
+            let bytes: Vec<u8> = floats.iter().flat_map(|f| f.to_ne_bytes()).collect();
+            image::save_buffer(path, &bytes, width as u32, height as u32, image::ExtendedColorType::Rgb32F)?;
+        }
+        _ => {
+            let data: Vec<u8> = quantize(pixels, width, options.dither)
+                .into_iter()
+                .flat_map(|c| [c.r, c.g, c.b, c.a])
+                .collect();
+            image::save_buffer(path, &data, width as u32, height as u32, image::ExtendedColorType::Rgba8)?;
+        }
+    }
+    Ok(())
+}
+
+/// Agrega a un PNG ya guardado un bloque `tEXt` por cada par (clave, valor),
+/// justo después de la cabecera. Así la imagen lleva consigo la cámara y
+/// los ajustes con que se hizo (los visores los muestran como propiedades)
+pub fn embed_png_text(path: &str, entries: &[(String, String)]) -> Result<()> {
+    let png = std::fs::read(path)?;
+    // Firma de 8 bytes y después IHDR: largo, tipo, 13 bytes de datos y CRC
+    const IHDR_END: usize = 8 + 4 + 4 + 13 + 4;
+    if png.len() < IHDR_END || &png[12..16] != b"IHDR" {
+        return Err(Error::Export(format!("{} no es un PNG", path)));
+    }
+    let mut out = Vec::with_capacity(png.len() + entries.len() * 64);
+    out.extend_from_slice(&png[..IHDR_END]);
+    for (key, value) in entries {
+        let mut chunk = b"tEXt".to_vec();
+        chunk.extend(key.chars().filter(|c| *c != '\0').map(latin1));
+        chunk.push(0);
+        chunk.extend(value.chars().filter(|c| *c != '\0').map(latin1));
+        out.extend_from_slice(&((chunk.len() - 4) as u32).to_be_bytes());
+        out.extend_from_slice(&chunk);
+        out.extend_from_slice(&crc32(&chunk).to_be_bytes());
+    }
+    out.extend_from_slice(&png[IHDR_END..]);
+    std::fs::write(path, out)?;
+    Ok(())
+}
+
+/// `tEXt` es Latin-1; lo que no entra se reemplaza por `?`
+fn latin1(c: char) -> u8 {
+    u8::try_from(u32::from(c)).unwrap_or(b'?')
+}
+
+/// CRC-32 de PNG (polinomio 0xEDB88320)
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &b in bytes {
+        crc ^= b as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+/// Escribe los mismos pares en `<path>.json`, para formatos sin metadatos o
+/// para leerlos desde un script
+pub fn write_sidecar(path: &str, entries: &[(String, String)]) -> Result<()> {
+    let escape = |s: &str| s.replace('\\', "\\\\").replace('"', "\\\"");
+    let fields: Vec<String> = entries
+        .iter()
+        .map(|(k, v)| format!("  \"{}\": \"{}\"", escape(k), escape(v)))
+        .collect();
+    std::fs::write(format!("{}.json", path), format!("{{\n{}\n}}\n", fields.join(",\n")))?;
+    Ok(())
+}
+
+/// Cuantiza a 8 bits, con tramado ordenado si `dither`
+fn quantize(pixels: &[Vec3], width: usize, dither: bool) -> Vec<Rgba> {
+    pixels
+        .iter()
+        .enumerate()
+        .map(|(idx, c)| vector3_to_color_at(*c, idx % width.max(1), idx / width.max(1), dither))
+        .collect()
+}
+
+/// PPM de 8 bits: `P6` binario o `P3` en texto según `options.ppm_ascii`
+/// (un píxel por línea, fácil de comparar con `diff` contra otro trazador)
+pub fn write_ppm<W: Write>(
+    out: &mut W,
+    width: usize,
+    height: usize,
+    pixels: &[Vec3],
+    options: &SaveOptions,
+) -> Result<()> {
+    writeln!(out, "{}\n{} {}\n255", if options.ppm_ascii { "P3" } else { "P6" }, width, height)?;
+    for c in quantize(pixels, width, options.dither) {
+        if options.ppm_ascii {
+            writeln!(out, "{} {} {}", c.r, c.g, c.b)?;
+        } else {
+            out.write_all(&[c.r, c.g, c.b])?;
+        }
+    }
+    Ok(())
+}
+
+/// PFM (Portable Float Map) en color: flotantes little-endian sin recortar,
+/// con las filas de abajo hacia arriba como pide el formato
+pub fn write_pfm<W: Write>(out: &mut W, width: usize, height: usize, pixels: &[Vec3]) -> Result<()> {
+    // Escala negativa = little-endian
+    write!(out, "PF\n{} {}\n-1.0\n", width, height)?;
+    for row in pixels.chunks(width.max(1)).rev() {
+        for c in row {
+            for f in [c.x, c.y, c.z] {
+                out.write_all(&f.to_le_bytes())?;
+            }
+        }
+    }
+    Ok(())
+}
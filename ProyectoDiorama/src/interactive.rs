@@ -0,0 +1,633 @@
+use std::f32::consts::PI;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use raylib::prelude::*;
+
+use diorama::camera::{Camera, CameraInertia};
+use diorama::camera_presets::{self, CameraPresets};
+use diorama::foveated::{render_foveated, Foveation};
+use diorama::progressive::Progressive;
+use diorama::ray_intersect::{CubeFace, Intersect};
+use diorama::scene::Scene;
+use diorama::voxel_world::VoxelWorld;
+use diorama::world::Diorama;
+use diorama::math::Vec3;
+use diorama::{autofocus, gif_export, pick, gltf_export, obj_export, render_linear_cancellable, vox_export};
+
+use crate::cli::Args;
+use crate::controls::{self, Action};
+use crate::framebuffers::Framebuffer;
+use crate::SceneSwitch;
+
+/// Ventana de raylib donde mostrar una escena
+pub type Window<'a> = (&'a mut RaylibHandle, &'a RaylibThread);
+
+pub fn open_window(args: &Args) -> (RaylibHandle, RaylibThread) {
+    let mut builder = raylib::init();
+    builder.size(args.width as i32, args.height as i32).title("Pokeball Diorama - Capas");
+    if args.vsync {
+        builder.vsync();
+    }
+    let (window, thread) = builder.build();
+    raylib::set_trace_log(TraceLogLevel::LOG_WARNING);
+    (window, thread)
+}
+
+/// Pedido de un cuadro al hilo de render
+struct Job {
+    id: u64,
+    camera: Camera,
+    time: f32,
+    max_depth: u32,
+    /// Fracción de la resolución de la ventana a la que se traza
+    scale: f32,
+    /// Zona a calidad completa, en píxeles de la ventana
+    fovea: Option<Foveation>,
+    /// Primera sub-muestra por píxel (ver `Scene::sample_offset`)
+    sample_offset: u32,
+}
+
+/// Cuadro terminado (o cancelado) por el hilo de render
+struct Frame {
+    id: u64,
+    image: Option<Vec<Vec3>>,
+    scale: f32,
+    elapsed: Duration,
+}
+
+/// Hilo que renderiza cuadros completos fuera del bucle de la ventana, así
+/// la entrada y el dibujo siguen a 60 FPS aunque un cuadro tarde segundos.
+/// Un pedido nuevo cancela el que está en curso.
+struct RenderWorker {
+    jobs: Sender<Job>,
+    results: Receiver<Frame>,
+    cancel: Arc<AtomicBool>,
+    next_id: u64,
+    /// Pedido cuyo resultado todavía no llegó
+    pending: Option<u64>,
+}
+
+impl RenderWorker {
+    fn spawn<'scope, 'a: 'scope>(
+        scope: &'scope std::thread::Scope<'scope, '_>,
+        width: usize,
+        height: usize,
+        view: &Diorama<'a>,
+    ) -> Self {
+        let (jobs, job_rx) = mpsc::channel::<Job>();
+        let (result_tx, results) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let worker_cancel = Arc::clone(&cancel);
+        let (motion, light, base_scene) = (view.motion, view.light, view.scene);
+
+        scope.spawn(move || {
+            while let Ok(job) = job_rx.recv() {
+                // Solo importa el pedido más reciente
+                let job = job_rx.try_iter().last().unwrap_or(job);
+                worker_cancel.store(false, Ordering::Relaxed);
+
+                let frame_light = motion.light_at(light, job.time);
+                let frame_camera = motion.camera_at(&job.camera, job.time);
+                let frame_scene = Scene {
+                    time: job.time,
+                    light: &frame_light,
+                    max_depth: job.max_depth,
+                    sample_offset: job.sample_offset,
+                    ..base_scene
+                };
+                let start = Instant::now();
+                let (w, h) = scaled_size(width, height, job.scale);
+                let image = match job.fovea {
+                    Some(fovea) => {
+                        let fovea = Foveation {
+                            center: (fovea.center.0 * job.scale, fovea.center.1 * job.scale),
+                            radius: fovea.radius * job.scale,
+                        };
+                        render_foveated(w, h, &frame_camera, &frame_scene, &fovea, &worker_cancel)
+                    }
+                    None => render_linear_cancellable(w, h, &frame_camera, &frame_scene, &worker_cancel),
+                }
+                .map(|image| upscale(&image, w, h, width, height));
+                let frame = Frame { id: job.id, image, scale: job.scale, elapsed: start.elapsed() };
+                if result_tx.send(frame).is_err() {
+                    break;
+                }
+            }
+        });
+
+        RenderWorker { jobs, results, cancel, next_id: 0, pending: None }
+    }
+
+    /// Pide un cuadro nuevo (el `id` de `job` lo pone el hilo); con
+    /// `restart` cancela el que esté en curso
+    fn request(&mut self, job: Job, restart: bool) {
+        if self.pending.is_some() {
+            if !restart {
+                return;
+            }
+            self.cancel.store(true, Ordering::Relaxed);
+        }
+        self.next_id += 1;
+        let job = Job { id: self.next_id, ..job };
+        if self.jobs.send(job).is_ok() {
+            self.pending = Some(self.next_id);
+        }
+    }
+
+    /// Abandona el cuadro en curso, si hay (su resultado llega vacío)
+    fn cancel_pending(&self) {
+        if self.pending.is_some() {
+            self.cancel.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Último cuadro terminado desde la llamada anterior, si hay
+    fn poll(&mut self) -> Option<Frame> {
+        let mut latest = None;
+        for frame in self.results.try_iter() {
+            if self.pending == Some(frame.id) {
+                self.pending = None;
+            }
+            if frame.image.is_some() {
+                latest = Some(frame);
+            }
+        }
+        latest
+    }
+}
+
+impl Drop for RenderWorker {
+    fn drop(&mut self) {
+        // Que el hilo no termine un cuadro que ya nadie va a mostrar
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Resolución dinámica: mientras la cámara se mueve ajusta la escala de
+/// render para que cada cuadro tarde lo que pide `--target-fps`; con la
+/// cámara quieta vuelve a la resolución completa
+struct DynamicResolution {
+    target: Duration,
+    scale: f32,
+}
+
+impl DynamicResolution {
+    const MIN_SCALE: f32 = 0.2;
+    /// Tiempo sin mover la cámara tras el que se traza a resolución completa
+    const IDLE: Duration = Duration::from_millis(300);
+
+    fn new(target_fps: f32) -> Option<Self> {
+        (target_fps > 0.0).then(|| DynamicResolution {
+            target: Duration::from_secs_f32(1.0 / target_fps),
+            scale: 1.0,
+        })
+    }
+
+    /// Corrige la escala con lo que tardó un cuadro trazado a `scale`. El
+    /// costo crece con el área, así que la escala va con la raíz del cociente
+    fn update(&mut self, scale: f32, elapsed: Duration) {
+        let ratio = self.target.as_secs_f32() / elapsed.as_secs_f32().max(1e-4);
+        self.scale = (scale * ratio.sqrt().clamp(0.7, 1.2)).clamp(Self::MIN_SCALE, 1.0);
+    }
+
+    fn scale(&self, idle: bool) -> f32 {
+        if idle { 1.0 } else { self.scale }
+    }
+}
+
+/// Tamaño de render a una fracción de la ventana (al menos 1×1)
+fn scaled_size(width: usize, height: usize, scale: f32) -> (usize, usize) {
+    (
+        ((width as f32 * scale).round() as usize).clamp(1, width),
+        ((height as f32 * scale).round() as usize).clamp(1, height),
+    )
+}
+
+/// Agranda una imagen al tamaño de la ventana por vecino más cercano
+fn upscale(image: &[Vec3], width: usize, height: usize, to_width: usize, to_height: usize) -> Vec<Vec3> {
+    if (width, height) == (to_width, to_height) {
+        return image.to_vec();
+    }
+    (0..to_width * to_height)
+        .map(|i| {
+            let x = (i % to_width) * width / to_width;
+            let y = (i / to_width) * height / to_height;
+            image[y * width + x]
+        })
+        .collect()
+}
+
+/// Nombre de la cara de un bloque para la lectura de la retícula
+fn face_name(face: CubeFace) -> &'static str {
+    match face {
+        CubeFace::Front => "frente",
+        CubeFace::Back => "atrás",
+        CubeFace::Left => "izquierda",
+        CubeFace::Right => "derecha",
+        CubeFace::Top => "arriba",
+        CubeFace::Bottom => "abajo",
+    }
+}
+
+/// Líneas de texto sobre lo que apunta la retícula: celda de la grilla,
+/// símbolo de material, cara y distancia (en unidades del mundo, de ahí
+/// `scale`, la escala del modelo)
+fn target_info(hit: Option<&Intersect>, world: &VoxelWorld, scale: f32) -> Vec<String> {
+    let Some(hit) = hit else {
+        return vec!["cielo".to_string()];
+    };
+    // El punto está sobre la cara: medio bloque hacia adentro está la celda
+    let inside = hit.point - hit.normal * 0.5;
+    let cell = (inside.x.round() as i32, inside.y.round() as i32, inside.z.round() as i32);
+    let symbol = world.get(cell).map_or("-".to_string(), |c| c.to_string());
+    vec![
+        format!("celda ({}, {}, {})  capa {}", cell.0, cell.1, cell.2, cell.1),
+        format!("material {}  cara {}", symbol, face_name(hit.face)),
+        format!("distancia {:.2}", hit.distance * scale),
+    ]
+}
+
+/// Cruz en el centro de la ventana y, arriba a la izquierda, `lines`
+fn draw_crosshair(d: &mut RaylibDrawHandle, width: i32, height: i32, lines: &[String]) {
+    let (cx, cy) = (width / 2, height / 2);
+    d.draw_line(cx - 8, cy, cx + 8, cy, Color::WHITE);
+    d.draw_line(cx, cy - 8, cx, cy + 8, Color::WHITE);
+    d.draw_rectangle(4, 4, 260, 8 + 20 * lines.len() as i32, Color::new(0, 0, 0, 160));
+    for (i, line) in lines.iter().enumerate() {
+        d.draw_text(line, 10, 10 + 20 * i as i32, 16, Color::WHITE);
+    }
+}
+
+/// Recuadro con la ayuda de controles, arriba a la derecha
+fn draw_help(d: &mut RaylibDrawHandle, width: i32, lines: &[String]) {
+    let (w, x) = (420, (width - 424).max(4));
+    d.draw_rectangle(x, 4, w, 8 + 18 * lines.len() as i32, Color::new(0, 0, 0, 180));
+    for (i, line) in lines.iter().enumerate() {
+        d.draw_text(line, x + 6, 10 + 18 * i as i32, 14, Color::WHITE);
+    }
+}
+
+/// Tope de FPS con la imagen quieta: solo hace falta atender la entrada
+const IDLE_FPS: u32 = 15;
+
+/// Velocidad de órbita con las flechas, en radianes por segundo
+const ORBIT_SPEED: f32 = PI * 0.6;
+/// Velocidad de alabeo con Q/E, en radianes por segundo
+const ROLL_SPEED: f32 = PI * 0.25;
+/// Velocidad de zoom con +/−, en logaritmo del factor de distancia por segundo
+const ZOOM_SPEED: f32 = 3.0;
+
+/// Punto de la ventana donde mira el usuario: el cursor si está dentro, si
+/// no el centro de la pantalla
+fn focus_point(window: &RaylibHandle, width: f32, height: f32) -> (f32, f32) {
+    let mouse = window.get_mouse_position();
+    if (0.0..width).contains(&mouse.x) && (0.0..height).contains(&mouse.y) {
+        (mouse.x, mouse.y)
+    } else {
+        (width * 0.5, height * 0.5)
+    }
+}
+
+/// Bucle de la ventana. Las teclas están en `controls::BINDINGS` (F1 las
+/// muestra): cámara con inercia (ver `CameraInertia`), encuadres guardados,
+/// rebotes máximos, modos de render y exportadores. Los cuadros completos se
+/// trazan en un hilo aparte (ver `RenderWorker`), a resolución reducida
+/// mientras la cámara se mueve si se pidió `--target-fps` (ver
+/// `DynamicResolution`)
+pub fn show(window: &mut RaylibHandle, thread: &RaylibThread, args: &Args, view: Diorama) -> diorama::error::Result<SceneSwitch> {
+    std::thread::scope(|scope| {
+        let worker = RenderWorker::spawn(scope, args.width as usize, args.height as usize, &view);
+        show_loop(window, thread, args, view, worker)
+    })
+}
+
+fn show_loop(
+    window: &mut RaylibHandle,
+    thread: &RaylibThread,
+    args: &Args,
+    mut view: Diorama,
+    mut worker: RenderWorker,
+) -> diorama::error::Result<SceneSwitch> {
+    let mut framebuffer = Framebuffer::new(args.width, args.height);
+    framebuffer.dither = args.dither;
+    let mut inertia = CameraInertia::new(args.camera_damping);
+    // Tope de FPS: el configurado mientras algo cambia; si la imagen quedó
+    // quieta (ver `still`) basta con atender la entrada
+    let mut vsync = args.vsync;
+    let mut capped = args.fps_cap > 0;
+    let mut current_fps = None;
+
+    // En modo progresivo o acumulando el tiempo queda congelado mientras se
+    // refina
+    let mut progressive = args.progressive.then(|| Progressive::new(args.width as usize, args.height as usize, 32));
+    let mut accumulating = args.accumulate;
+    // Primer pedido cuyo cuadro entra en la acumulación actual
+    let mut accumulate_from = 0;
+    let mut frozen_time = 0.0;
+    // En pausa no se traza nada: se sigue mostrando el último cuadro y
+    // atendiendo la entrada. El reloj de la escena no avanza en pausa
+    let mut paused_at: Option<f64> = None;
+    let mut paused_total = 0.0;
+    // El último cuadro llegó igual al anterior sin que nada cambiara: la
+    // escena es estática y no hace falta seguir trazándola
+    let mut still = false;
+    let mut last_image: Option<Vec<Vec3>> = None;
+    let mut last_camera = view.camera.clone();
+    let mut last_depth = view.scene.max_depth;
+    let mut first_frame = true;
+    let mut dynamic = DynamicResolution::new(args.target_fps);
+    let mut last_change = Instant::now();
+    let fovea_radius = if args.foveated > 0.0 { args.foveated } else { args.height as f32 / 6.0 };
+    let mut foveated = args.foveated > 0.0;
+    let mut autofocusing = args.autofocus;
+    let mut crosshair = false;
+    let mut help = false;
+    let mut presets = CameraPresets::load(camera_presets::PRESETS_FILE).unwrap_or_else(|e| {
+        eprintln!("No pude leer los encuadres guardados: {}", e);
+        CameraPresets::default()
+    });
+    while !window.window_should_close() {
+        if controls::pressed(window, Action::Pause) {
+            match paused_at.take() {
+                Some(since) => {
+                    paused_total += window.get_time() - since;
+                    println!("Render reanudado");
+                }
+                None => {
+                    paused_at = Some(window.get_time());
+                    worker.cancel_pending();
+                    println!("Render en pausa");
+                }
+            }
+        }
+        let clock = (paused_at.unwrap_or_else(|| window.get_time()) - paused_total) as f32;
+        let frozen = progressive.is_some() || accumulating;
+        let time = if frozen { frozen_time } else { clock };
+        let frame_light = view.motion.light_at(view.light, time);
+        let frame_camera = view.motion.camera_at(&view.camera, time);
+        let frame_scene = Scene { time, light: &frame_light, ..view.scene };
+
+        // Con Shift las flechas desplazan la cámara en vez de orbitar
+        let pan_speed = (view.camera.eye - view.camera.center).length();
+        let horizontal = controls::axis(window, Action::OrbitLeft, Action::OrbitRight);
+        let vertical = controls::axis(window, Action::OrbitDown, Action::OrbitUp);
+        if controls::down(window, Action::Pan) {
+            inertia.push(0.0, 0.0, 0.0, (-horizontal * pan_speed, -vertical * pan_speed));
+        } else {
+            inertia.push(horizontal * ORBIT_SPEED, vertical * ORBIT_SPEED, 0.0, (0.0, 0.0));
+        }
+        let zoom = controls::axis(window, Action::ZoomOut, Action::ZoomIn);
+        inertia.push(0.0, 0.0, zoom * ZOOM_SPEED, (0.0, 0.0));
+        inertia.update(&mut view.camera, window.get_frame_time());
+        let roll = controls::axis(window, Action::RollLeft, Action::RollRight);
+        if roll != 0.0 {
+            view.camera.roll_by(roll * ROLL_SPEED * window.get_frame_time());
+        }
+
+        if controls::pressed(window, Action::MoreBounces) {
+            view.scene.max_depth += 1;
+            println!("Rebotes máximos: {}", view.scene.max_depth);
+        }
+        if controls::pressed(window, Action::FewerBounces) && view.scene.max_depth > 0 {
+            view.scene.max_depth -= 1;
+            println!("Rebotes máximos: {}", view.scene.max_depth);
+        }
+
+        let ctrl = controls::down(window, Action::StorePreset);
+        for (slot, key) in controls::SLOT_KEYS.into_iter().enumerate() {
+            if !window.is_key_pressed(key) {
+                continue;
+            }
+            if ctrl {
+                presets.store(slot, &view.camera);
+                match presets.save(camera_presets::PRESETS_FILE) {
+                    Ok(()) => println!("Encuadre {} guardado", slot),
+                    Err(e) => eprintln!("No pude guardar el encuadre: {}", e),
+                }
+            } else if let Some(camera) = presets.recall(slot) {
+                view.camera = camera;
+            }
+        }
+
+        if controls::pressed(window, Action::Reload) {
+            // Si alguna textura no se puede leer se sigue con la escena actual
+            match view.textures.check_files() {
+                Ok(_) => return Ok(SceneSwitch::Reload(view.camera.clone())),
+                Err(e) => eprintln!("No recargo: {}", e),
+            }
+        }
+
+        if args.scenes.len() > 1 {
+            if controls::pressed(window, Action::NextScene) {
+                return Ok(SceneSwitch::Next);
+            }
+            if controls::pressed(window, Action::PreviousScene) {
+                return Ok(SceneSwitch::Previous);
+            }
+        }
+
+        if controls::pressed(window, Action::ExportGif) {
+            let path = "diorama_turntable.gif";
+            println!("Renderizando vuelta animada ({} cuadros)...", gif_export::GIF_FRAMES);
+            match gif_export::export_turntable_gif(path, &frame_camera, &frame_scene) {
+                Ok(()) => println!("GIF guardado en {}", path),
+                Err(e) => eprintln!("No pude exportar el GIF: {}", e),
+            }
+        }
+
+        if controls::pressed(window, Action::ExportObj) {
+            match obj_export::export_obj("diorama.obj", view.world, view.materials, view.textures) {
+                Ok(()) => println!("Malla guardada en diorama.obj / diorama.mtl"),
+                Err(e) => eprintln!("No pude exportar el OBJ: {}", e),
+            }
+        }
+
+        if controls::pressed(window, Action::ExportGltf) {
+            match gltf_export::export_gltf("diorama.glb", view.world, view.materials, view.textures) {
+                Ok(()) => println!("glTF guardado en diorama.glb"),
+                Err(e) => eprintln!("No pude exportar el glTF: {}", e),
+            }
+        }
+
+        if controls::pressed(window, Action::ExportVox) {
+            match vox_export::export_vox("diorama.vox", view.world, view.materials, view.textures) {
+                Ok(()) => println!("Vóxeles guardados en diorama.vox"),
+                Err(e) => eprintln!("No pude exportar el .vox: {}", e),
+            }
+        }
+
+        if controls::pressed(window, Action::Vsync) {
+            vsync = !vsync;
+            let state = WindowState::default().set_vsync_hint(true);
+            if vsync {
+                window.set_window_state(state);
+            } else {
+                window.clear_window_state(state);
+            }
+            println!("Sincronía vertical: {}", if vsync { "sí" } else { "no" });
+        }
+        if controls::pressed(window, Action::FpsCap) {
+            capped = !capped;
+            println!("Tope de FPS: {}", if capped { "sí" } else { "no" });
+        }
+        let fps = match (capped, still || paused_at.is_some()) {
+            (_, true) => IDLE_FPS,
+            (true, false) => if args.fps_cap > 0 { args.fps_cap } else { 60 },
+            (false, false) => 0,
+        };
+        if current_fps != Some(fps) {
+            window.set_target_fps(fps);
+            current_fps = Some(fps);
+        }
+
+        if controls::pressed(window, Action::Progressive) {
+            progressive = match progressive {
+                Some(_) => None,
+                None => Some(Progressive::new(args.width as usize, args.height as usize, 32)),
+            };
+            still = false;
+            frozen_time = clock;
+        }
+
+        let mut toggled_accumulation = false;
+        if controls::pressed(window, Action::Accumulate) {
+            accumulating = !accumulating;
+            toggled_accumulation = true;
+            frozen_time = clock;
+            println!("Acumulación: {}", if accumulating { "sí" } else { "no" });
+        }
+
+        if controls::pressed(window, Action::Autofocus) {
+            autofocusing = !autofocusing;
+            println!("Autoenfoque: {}", if autofocusing { "sí" } else { "no" });
+        }
+        if autofocusing && view.camera.aperture > 0.0 {
+            // Enfoca lo que está bajo el cursor (o en el centro); solo se
+            // corrige si cambia más de un 1 %, para no reiniciar el cuadro
+            let (x, y) = focus_point(window, args.width as f32, args.height as f32);
+            let (w, h) = (args.width as usize, args.height as usize);
+            if let Some(distance) = autofocus(x, y, w, h, &frame_camera, &frame_scene) {
+                let focus = distance * view.camera.focus_distance / frame_camera.focus_distance;
+                if (focus - view.camera.focus_distance).abs() > view.camera.focus_distance * 0.01 {
+                    view.camera.focus_distance = focus;
+                }
+            }
+        }
+
+        if controls::pressed(window, Action::Help) {
+            help = !help;
+        }
+        let help_lines = help.then(|| {
+            let many_scenes = args.scenes.len() > 1;
+            controls::help_lines(|a| many_scenes || !matches!(a, Action::NextScene | Action::PreviousScene))
+        });
+
+        if controls::pressed(window, Action::Crosshair) {
+            crosshair = !crosshair;
+        }
+        let target = crosshair.then(|| {
+            let (w, h) = (args.width as usize, args.height as usize);
+            let hit = pick(w as f32 * 0.5, h as f32 * 0.5, w, h, &frame_camera, &frame_scene);
+            target_info(hit.as_ref(), view.world, view.motion.scale)
+        });
+
+        if controls::pressed(window, Action::Foveated) {
+            foveated = !foveated;
+            still = false;
+            println!("Render foveado: {}", if foveated { "sí" } else { "no" });
+        }
+
+        if paused_at.is_none() {
+            let changed = first_frame
+                || toggled_accumulation
+                || view.camera != last_camera
+                || view.scene.max_depth != last_depth;
+            match progressive.as_mut() {
+                Some(p) => {
+                    if changed {
+                        p.restart();
+                    }
+                    if !p.is_done() {
+                        p.step(&frame_camera, &frame_scene, Duration::from_millis(30));
+                        framebuffer.clear();
+                        framebuffer.blit(&p.linear(&frame_camera, &frame_scene));
+                        framebuffer.swap();
+                    }
+                    still = p.is_done();
+                }
+                None => {
+                    // Si la vista cambió se cancela el cuadro en curso; si no,
+                    // se pide el siguiente (animaciones) cuando termine. Con
+                    // resolución dinámica no se cancela: el cuadro en curso ya
+                    // es barato y sirve para medir
+                    if changed {
+                        still = false;
+                        last_change = Instant::now();
+                        framebuffer.reset_accumulation();
+                        accumulate_from = worker.next_id + 1;
+                    }
+                    let idle = last_change.elapsed() > DynamicResolution::IDLE;
+                    let mut job = Job {
+                        id: 0,
+                        camera: view.camera.clone(),
+                        time,
+                        max_depth: view.scene.max_depth,
+                        scale: 1.0,
+                        fovea: None,
+                        sample_offset: 0,
+                    };
+                    if accumulating {
+                        // Cuadros completos, cada uno con las sub-muestras que
+                        // siguen a las ya acumuladas
+                        job.sample_offset = framebuffer.accumulated_samples() * view.scene.pixel_samples.max(1);
+                    } else {
+                        job.scale = dynamic.as_ref().map_or(1.0, |d| d.scale(idle));
+                        job.fovea = foveated.then(|| Foveation {
+                            center: focus_point(window, args.width as f32, args.height as f32),
+                            radius: fovea_radius,
+                        });
+                    }
+                    if !still {
+                        worker.request(job, changed && dynamic.is_none());
+                    }
+                    if let Some(frame) = worker.poll() {
+                        if let Some(d) = dynamic.as_mut() {
+                            d.update(frame.scale, frame.elapsed);
+                        }
+                        if let Some(image) = frame.image {
+                            if accumulating && frame.id >= accumulate_from {
+                                framebuffer.accumulate(&image);
+                            } else {
+                                framebuffer.clear();
+                                framebuffer.blit(&image);
+                            }
+                            framebuffer.swap();
+                            // El foveado sigue al cursor, que no cuenta como cambio
+                            still = !changed && !foveated && last_image.as_ref() == Some(&image);
+                            last_image = Some(image);
+                        }
+                    }
+                }
+            }
+            first_frame = false;
+            last_camera = view.camera.clone();
+            last_depth = view.scene.max_depth;
+        }
+        framebuffer.swap_buffers(window, thread, |d| {
+            if let Some(lines) = &target {
+                draw_crosshair(d, args.width as i32, args.height as i32, lines);
+            }
+            if let Some(lines) = &help_lines {
+                draw_help(d, args.width as i32, lines);
+            }
+            if paused_at.is_some() {
+                d.draw_text("En pausa", 10, args.height as i32 - 26, 16, Color::WHITE);
+            }
+        });
+    }
+
+    Ok(SceneSwitch::Quit)
+}
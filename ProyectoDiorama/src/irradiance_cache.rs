@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::camera::Camera;
+use crate::math::Vec3;
+
+use crate::ray_intersect::Intersect;
+use crate::rng::Rng;
+use crate::sampling;
+use crate::scene::Scene;
+use crate::{clamp_radiance, direct_diffuse, emission_mis, nearest_hit, offset_origin, primary_direction};
+
+/// Muestra de irradiancia indirecta guardada en una superficie
+#[derive(Debug, Clone, Copy)]
+struct Record {
+    position: Vec3,
+    normal: Vec3,
+    /// Radiancia difusa indirecta promedio (irradiancia / π)
+    indirect: Vec3,
+    /// Distancia media armónica a la geometría vista desde el punto
+    radius: f32,
+}
+
+/// Parámetros de la caché de irradiancia
+#[derive(Debug, Clone, Copy)]
+pub struct IrradianceSettings {
+    /// Rayos del hemisferio por muestra nueva
+    pub samples: u32,
+    /// Tolerancia de interpolación (menor = más muestras, más precisión)
+    pub accuracy: f32,
+    pub min_radius: f32,
+    pub max_radius: f32,
+    /// Descarta muestras con más de este múltiplo del brillo medio;
+    /// 0 = sin rechazo
+    pub outlier_factor: f32,
+}
+
+impl Default for IrradianceSettings {
+    fn default() -> Self {
+        IrradianceSettings {
+            samples: 64,
+            accuracy: 0.3,
+            min_radius: 0.15,
+            max_radius: 3.0,
+            outlier_factor: 0.0,
+        }
+    }
+}
+
+/// Caché de irradiancia (Ward et al.): guarda muestras dispersas de luz
+/// indirecta difusa e interpola entre ellas, calculando una muestra nueva
+/// solo cuando ninguna existente es válida para el punto. Las muestras se
+/// conservan entre cuadros mientras la escena no cambie.
+pub struct IrradianceCache {
+    settings: IrradianceSettings,
+    grid: RwLock<HashMap<(i32, i32, i32), Vec<Record>>>,
+    /// Cerrada tras `prefill`: las muestras nuevas se calculan pero no se
+    /// guardan, así el orden de los hilos no cambia la imagen
+    sealed: AtomicBool,
+}
+
+impl IrradianceCache {
+    pub fn new(settings: IrradianceSettings) -> Self {
+        IrradianceCache {
+            settings,
+            grid: RwLock::new(HashMap::new()),
+            sealed: AtomicBool::new(false),
+        }
+    }
+
+    /// Llena la caché en un solo hilo, en orden de scanlines, con los
+    /// impactos primarios de la imagen y la cierra. Con la caché llena de
+    /// antemano el render en paralelo solo lee, y la misma semilla da la
+    /// misma imagen. No hace nada si ya estaba cerrada.
+    pub fn prefill(&self, width: usize, height: usize, camera: &Camera, scene: &Scene) {
+        if self.sealed.load(Ordering::Relaxed) {
+            return;
+        }
+        for y in 0..height {
+            for x in 0..width {
+                let direction = primary_direction(x as f32, y as f32, width, height, camera);
+                let hit = nearest_hit(&camera.eye, &direction, scene);
+                if hit.is_intersecting {
+                    self.indirect(&hit, scene);
+                }
+            }
+        }
+        self.sealed.store(true, Ordering::Relaxed);
+    }
+
+    fn cell(&self, p: Vec3) -> (i32, i32, i32) {
+        let size = self.settings.max_radius;
+        (
+            (p.x / size).floor() as i32,
+            (p.y / size).floor() as i32,
+            (p.z / size).floor() as i32,
+        )
+    }
+
+    /// Interpolación ponderada de las muestras válidas cerca de `p`
+    fn interpolate(&self, p: Vec3, n: Vec3) -> Option<Vec3> {
+        let grid = self.grid.read().ok()?;
+        let (cx, cy, cz) = self.cell(p);
+        let mut sum = Vec3::zero();
+        let mut weight_sum = 0.0;
+
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                for dz in -1..=1 {
+                    let Some(records) = grid.get(&(cx + dx, cy + dy, cz + dz)) else {
+                        continue;
+                    };
+                    for r in records {
+                        let error = (p - r.position).length() / r.radius
+                            + (1.0 - n.dot(r.normal)).max(0.0).sqrt();
+                        if error < self.settings.accuracy {
+                            let w = 1.0 / error.max(1e-4);
+                            sum += r.indirect * w;
+                            weight_sum += w;
+                        }
+                    }
+                }
+            }
+        }
+
+        (weight_sum > 0.0).then(|| sum / weight_sum)
+    }
+
+    /// Promedio de las muestras del hemisferio. Con `outlier_factor` se
+    /// ignoran las que superan ese múltiplo del brillo medio (sin reponderar:
+    /// se pierde algo de energía a cambio de una caché sin manchas)
+    fn average(&self, radiance: &[Vec3]) -> Vec3 {
+        if radiance.is_empty() {
+            return Vec3::zero();
+        }
+        let limit = if self.settings.outlier_factor > 0.0 {
+            let mean = radiance.iter().map(Vec3::max_component).sum::<f32>() / radiance.len() as f32;
+            mean * self.settings.outlier_factor
+        } else {
+            f32::INFINITY
+        };
+        let sum = radiance
+            .iter()
+            .filter(|r| r.max_component() <= limit)
+            .fold(Vec3::zero(), |acc, &r| acc + r);
+        sum / radiance.len() as f32
+    }
+
+    /// Radiancia difusa indirecta que llega a `hit` (un rebote)
+    pub fn indirect(&self, hit: &Intersect, scene: &Scene) -> Vec3 {
+        if let Some(value) = self.interpolate(hit.point, hit.normal) {
+            return value;
+        }
+
+        // Semilla a partir de la posición (y no del píxel que la pidió) para
+        // que el resultado sea estable
+        let scene = &Scene { pixel: None, ..*scene };
+        let mut rng = Rng::at_point(scene.seed, hit.point);
+
+        let mut radiance = Vec::with_capacity(self.settings.samples as usize);
+        let mut inv_distance_sum = 0.0;
+        // Hammersley rotado: el hemisferio queda cubierto parejo aun con
+        // pocas muestras
+        let shift = (rng.next_f32(), rng.next_f32());
+        for i in 0..self.settings.samples {
+            let (u, v) = sampling::rotate(sampling::hammersley(i, self.settings.samples), shift);
+            let dir = sampling::cosine_hemisphere(u, v, hit.normal);
+            let origin = offset_origin(hit, &dir, scene.bias);
+            let other = nearest_hit(&origin, &dir, scene);
+            if other.is_intersecting {
+                let bounce = direct_diffuse(&other, scene) + emission_mis(hit, dir, &other, scene);
+                radiance.push(clamp_radiance(bounce, scene.firefly_clamp));
+                inv_distance_sum += 1.0 / other.distance.max(1e-3);
+            } else {
+                radiance.push(Vec3::zero());
+            }
+        }
+
+        let samples = self.settings.samples.max(1) as f32;
+        let indirect = self.average(&radiance);
+        let radius = if inv_distance_sum > 0.0 {
+            (samples / inv_distance_sum).clamp(self.settings.min_radius, self.settings.max_radius)
+        } else {
+            self.settings.max_radius
+        };
+
+        let record = Record {
+            position: hit.point,
+            normal: hit.normal,
+            indirect,
+            radius,
+        };
+        if !self.sealed.load(Ordering::Relaxed)
+            && let Ok(mut grid) = self.grid.write()
+        {
+            grid.entry(self.cell(hit.point)).or_default().push(record);
+        }
+        indirect
+    }
+}
@@ -0,0 +1,980 @@
+//! Trazador de rayos de dioramas de vóxeles.
+//!
+//! La biblioteca contiene todo el render: primitivas (`cube`, `water_plane`,
+//! `sprites`, `transform`), materiales y texturas, el mundo de vóxeles y sus
+//! generadores, y la iluminación (sombras, cáusticas, luz indirecta,
+//! lightmaps). El diorama completo (texturas, materiales, capas y el armado
+//! de la escena a partir de un archivo de escena) está en `world::build`.
+//! El binario `ProyectoDiorama` es solo la interfaz interactiva con raylib
+//! sobre esta API.
+//!
+//! Uso mínimo:
+//!
+//! ```no_run
+//! use diorama::camera::Camera;
+//! use diorama::cube::Cube;
+//! use diorama::light::Light;
+//! use diorama::material::Material;
+//! use diorama::math::{Rgba, Vec3};
+//! use diorama::ray_intersect::RayIntersect;
+//! use diorama::scene::Scene;
+//! use diorama::texture_manager::TextureManager;
+//!
+//! let cube = Cube::new(Vec3::zero(), 1.0, Material::solid(Vec3::new(0.8, 0.2, 0.2), 10.0, [0.9, 0.1, 0.0, 0.0], 0.0));
+//! let objects: Vec<&dyn RayIntersect> = vec![&cube];
+//! let light = Light::new(Vec3::new(-5.0, 5.0, 5.0), Rgba::WHITE, 2.0);
+//! let textures = TextureManager::default();
+//! let scene = Scene::new(&objects, &light, &textures);
+//! let camera = Camera::new(Vec3::new(0.0, 1.0, 4.0), Vec3::zero(), Vec3::new(0.0, 1.0, 0.0));
+//! let pixels: Vec<Rgba> = diorama::render(320, 240, &camera, &scene);
+//! ```
+
+use std::f32::consts::PI;
+use std::ops::ControlFlow;
+use rayon::prelude::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+pub mod animation;
+pub mod ascii;
+pub mod blue_noise;
+pub mod camera;
+pub mod camera_presets;
+pub mod color_ops;
+pub mod cube;
+pub mod emissive;
+pub mod error;
+pub mod foveated;
+pub mod geometry;
+pub mod gif_export;
+pub mod golden;
+pub mod gltf_export;
+pub mod heightmap;
+pub mod image_output;
+pub mod irradiance_cache;
+pub mod light;
+pub mod lightmap;
+pub mod lod;
+pub mod matcap;
+pub mod material;
+pub mod math;
+pub mod obj_export;
+pub mod occluder_cache;
+pub mod particles;
+pub mod photon_map;
+pub mod post;
+pub mod progress;
+pub mod progressive;
+pub mod ray_intersect;
+pub mod rng;
+pub mod sampling;
+pub mod scene;
+pub mod scene_bounds;
+pub mod scene_file;
+pub mod script;
+pub mod shapes;
+pub mod sky;
+pub mod sky_light;
+pub mod sprites;
+pub mod stamps;
+pub mod stats;
+pub mod terrain;
+pub mod texture_manager;
+pub mod textures;
+pub mod threads;
+pub mod toon;
+pub mod transform;
+pub mod video;
+pub mod vox_export;
+pub mod voxel_text;
+pub mod voxel_world;
+pub mod voxelizer;
+pub mod water;
+pub mod water_plane;
+pub mod weather;
+pub mod world;
+
+use ray_intersect::{CubeFace, Intersect, Occlusion, RayIntersect};
+use camera::Camera;
+use material::{vector3_to_color_at, Clearcoat, Subsurface};
+use scene::Scene;
+use math::{Rgba, Vec3};
+use geometry::{reflect, refract};
+
+/// Cielo de fondo: verde abajo, blanco en el horizonte y azul arriba
+pub fn procedural_sky(dir: Vec3) -> Vec3 {
+    let d = dir.normalized();
+    let t = (d.y + 1.0) * 0.5;
+
+    let green = Vec3::new(0.1, 0.6, 0.2);
+    let white = Vec3::new(1.0, 1.0, 1.0);
+    let blue = Vec3::new(0.3, 0.5, 1.0);
+
+    if t < 0.54 {
+        let k = t / 0.55;
+        green * (1.0 - k) + white * k
+    } else if t < 0.55 {
+        white
+    } else if t < 0.8 {
+        let k = (t - 0.55) / 0.25;
+        white * (1.0 - k) + blue * k
+    } else {
+        blue
+    }
+}
+
+/// Desplaza el origen de un rayo secundario fuera de la superficie para
+/// evitar el acné. El sesgo crece con la distancia del impacto (la precisión
+/// del punto empeora) y en ángulos rasantes, donde un sesgo fijo no alcanza.
+pub(crate) fn offset_origin(intersect: &Intersect, direction: &Vec3, bias: f32) -> Vec3 {
+    let cos = direction.dot(intersect.normal).abs().max(0.1);
+    let offset = intersect.normal * (bias * (1.0 + intersect.distance) / cos);
+    if direction.dot(intersect.normal) < 0.0 {
+        intersect.point - offset
+    } else {
+        intersect.point + offset
+    }
+}
+
+/// Luz que llega a `intersect` desde la luz principal, por canal (nada si la
+/// luz no ilumina al grupo del material): se anula con un bloque opaco y cada
+/// bloque transparente que atraviesa la tiñe con su color y la atenúa por su
+/// transparencia (`albedo[3]`). Con `shadow_samples > 1` y una luz con radio
+/// promedia varios rayos hacia puntos del disco de la luz, lo que da
+/// penumbras suaves.
+pub(crate) fn cast_shadow(intersect: &Intersect, scene: &Scene) -> Vec3 {
+    if !scene.light.lights(intersect.material.light_groups) {
+        return Vec3::zero();
+    }
+    if !scene.light.casts_shadows {
+        return Vec3::one();
+    }
+    let samples = scene.shadow_samples.max(1);
+    if samples == 1 || scene.light.radius <= 0.0 {
+        return shadow_ray(intersect, scene.light.position, scene);
+    }
+
+    let axis = (scene.light.position - intersect.point).normalized();
+    let (tangent, bitangent) = rng::orthonormal_basis(axis);
+    let mut rng = rng::Rng::at_point(scene.seed, intersect.point);
+
+    // Conjunto de Hammersley sobre el disco, rotado por píxel con ruido
+    // azul para que los vecinos no repitan el mismo patrón
+    let shift = (pixel_noise(scene, &mut rng, 0), pixel_noise(scene, &mut rng, 1));
+    let mut sum = Vec3::zero();
+    for i in 0..samples {
+        let (v, u) = sampling::rotate(sampling::hammersley(i, samples), shift);
+        let (dx, dy) = sampling::disk(u, v);
+        let r = scene.light.radius;
+        let target = scene.light.position + tangent * (r * dx) + bitangent * (r * dy);
+        sum += shadow_ray(intersect, target, scene);
+    }
+    sum / samples as f32
+}
+
+/// Valor en [0, 1) para la dimensión `channel` de un patrón de muestras: el
+/// ruido azul del píxel (más la razón áurea por sub-muestra) si se está
+/// trazando un píxel, o uno aleatorio del generador del punto si no
+fn pixel_noise(scene: &Scene, rng: &mut rng::Rng, channel: u32) -> f32 {
+    match scene.pixel {
+        Some((x, y, i)) => (blue_noise::sample(x, y, channel) + i as f32 * 0.618_034).fract(),
+        None => rng.next_f32(),
+    }
+}
+
+/// Transmisión a lo largo de un único rayo de sombra hacia `target`. Corta
+/// en el primer oclusor opaco (probando antes el último que tapó este
+/// píxel, si hay `scene.occluders`)
+fn shadow_ray(intersect: &Intersect, target: Vec3, scene: &Scene) -> Vec3 {
+    let light_dir = (target - intersect.point).normalized();
+    let light_distance = (target - intersect.point).length();
+
+    let shadow_ray_origin = offset_origin(intersect, &light_dir, scene.bias);
+
+    let cache = scene.occluders.zip(scene.pixel).map(|(cache, (x, y, _))| (cache, x, y));
+    if let Some((cache, x, y)) = cache
+        && let Some(object) = cache.get(x, y, scene.frame).and_then(|i| scene.objects.get(i))
+        && let Occlusion::Opaque = object.occlusion(&shadow_ray_origin, &light_dir, light_distance)
+    {
+        return Vec3::zero();
+    }
+
+    let mut transmission = Vec3::one();
+    let blocked = visit_objects(scene, &shadow_ray_origin, &light_dir, light_distance, |index, object| {
+        match object.occlusion(&shadow_ray_origin, &light_dir, light_distance) {
+            Occlusion::Clear => {}
+            Occlusion::Opaque => {
+                if let Some((cache, x, y)) = cache {
+                    cache.set(x, y, scene.frame, index);
+                }
+                return ControlFlow::Break(());
+            }
+            Occlusion::Tinted(hit) => {
+                let tint = hit.material.color_at(scene.tm, hit.u, hit.v, hit.inner_point());
+                let tint = Vec3::new(
+                    tint.r as f32 / 255.0,
+                    tint.g as f32 / 255.0,
+                    tint.b as f32 / 255.0,
+                );
+                transmission *= tint * hit.material.albedo[3];
+            }
+        }
+        ControlFlow::Continue(())
+    });
+
+    if blocked.is_break() { Vec3::zero() } else { transmission }
+}
+
+/// Estimación de evento siguiente para los bloques emisivos: en cada punto
+/// sombreado se elige un bloque al azar y se traza un rayo de sombra a un
+/// punto de su superficie, en vez de esperar a que un rayo del hemisferio lo
+/// encuentre. Devuelve la irradiancia / π, lista para multiplicar por el
+/// color difuso. Con `mis` el mismo punto también muestrea el hemisferio
+/// (caché de irradiancia) y cada muestra se pondera con la heurística de
+/// potencia frente a esa otra estrategia (ver `emission_mis`)
+pub(crate) fn emissive_direct(hit: &Intersect, scene: &Scene, samples: u32, mis: bool) -> Vec3 {
+    // Solo compiten los bloques cuyo alcance llega al punto
+    let in_range = |b: &&emissive::EmissiveBlock| b.reaches(hit.point, scene.light_cutoff);
+    let count = scene.emissive.iter().filter(in_range).count();
+    if count == 0 {
+        return Vec3::zero();
+    }
+    let samples = samples.max(1);
+    let mut rng = rng::Rng::at_point(scene.seed ^ 0xE5EE, hit.point);
+
+    let mut sum = Vec3::zero();
+    for _ in 0..samples {
+        let index = ((rng.next_f32() * count as f32) as usize).min(count - 1);
+        let Some(block) = scene.emissive.iter().filter(in_range).nth(index) else {
+            continue;
+        };
+        let Some(sample) = block.sample(hit.point, &mut rng) else {
+            continue;
+        };
+        let to_light = sample.point - hit.point;
+        let distance_sq = to_light.dot(to_light).max(1e-2);
+        let dir = to_light.normalized();
+        let cos_receiver = hit.normal.dot(dir);
+        let cos_emitter = -sample.normal.dot(dir);
+        if cos_receiver <= 0.0 || cos_emitter <= 0.0 {
+            continue;
+        }
+        // El objetivo apenas afuera de la cara, para que el propio bloque
+        // no cuente como oclusor
+        let transmission = shadow_ray(hit, sample.point + sample.normal * 1e-3, scene);
+        let mut weight = cos_receiver * cos_emitter * sample.area * count as f32 / (PI * distance_sq);
+        if mis {
+            let pdf_light = distance_sq / (cos_emitter * sample.area * count as f32);
+            let pdf_brdf = cos_receiver / PI;
+            weight *= power_heuristic(pdf_light, pdf_brdf);
+        }
+        sum += block.radiance_at(scene.time) * transmission * weight;
+    }
+    clamp_radiance(sum / samples as f32, scene.firefly_clamp)
+}
+
+/// Peso de la heurística de potencia (β = 2) de una estrategia de muestreo
+/// con densidad `pdf` frente a otra con densidad `other`
+fn power_heuristic(pdf: f32, other: f32) -> f32 {
+    let (a, b) = (pdf * pdf, other * other);
+    if a + b > 0.0 { a / (a + b) } else { 0.0 }
+}
+
+/// Luz propia de `other` vista desde `hit` por un rayo muestreado con la
+/// distribución coseno, ponderada por MIS frente a `emissive_direct`: así
+/// los bloques emisivos grandes y cercanos (mal muestreados por área) los
+/// resuelve el hemisferio y los chicos y lejanos los resuelve la luz
+pub(crate) fn emission_mis(hit: &Intersect, dir: Vec3, other: &Intersect, scene: &Scene) -> Vec3 {
+    if other.material.emission <= 0.0 {
+        return Vec3::zero();
+    }
+    let tex = other.material.color_at(scene.tm, other.u, other.v, other.inner_point());
+    let emission = other.material.emission_at(scene.time, other.inner_point());
+    let emitted = Vec3::new(tex.r as f32, tex.g as f32, tex.b as f32) / 255.0 * emission;
+
+    let cos_receiver = hit.normal.dot(dir).max(0.0);
+    let cos_emitter = -other.normal.dot(dir);
+    let Some(block) = scene.emissive.iter().find(|b| b.contains(other.point)) else {
+        // Emisor que la estimación directa no conoce: sin MIS
+        return emitted;
+    };
+    if !block.reaches(hit.point, scene.light_cutoff) {
+        // Fuera de alcance: la estimación directa no lo habría elegido
+        return emitted;
+    }
+    let count = scene.emissive.iter().filter(|b| b.reaches(hit.point, scene.light_cutoff)).count() as f32;
+    if cos_emitter <= 0.0 {
+        return Vec3::zero();
+    }
+    let pdf_light = block.pdf_area(hit.point, other.normal) / count * other.distance * other.distance / cos_emitter;
+    let pdf_brdf = cos_receiver / PI;
+    emitted * power_heuristic(pdf_brdf, pdf_light)
+}
+
+/// Escala `radiance` para que ningún canal supere `max`, conservando el
+/// tono; evita que una muestra aislada muy brillante deje un "firefly"
+pub(crate) fn clamp_radiance(radiance: Vec3, max: f32) -> Vec3 {
+    let peak = radiance.max_component();
+    if peak > max { radiance * (max / peak) } else { radiance }
+}
+
+/// Recorre los objetos de la escena que el rayo puede tocar antes de
+/// `max_t`: todos, o solo los de las capas que cruza si hay `scene.bounds`.
+/// Un `Break` de `visit` corta el recorrido
+fn visit_objects<'a>(
+    scene: &Scene<'a>,
+    ray_origin: &Vec3,
+    ray_direction: &Vec3,
+    max_t: f32,
+    mut visit: impl FnMut(usize, &'a dyn RayIntersect) -> ControlFlow<()>,
+) -> ControlFlow<()> {
+    match scene.bounds {
+        Some(bounds) => bounds.visit(ray_origin, ray_direction, max_t, |i| visit(i, scene.objects[i])),
+        None => scene.objects.iter().enumerate().try_for_each(|(i, &object)| visit(i, object)),
+    }
+}
+
+/// Intersección más cercana del rayo contra los objetos de la escena
+pub(crate) fn nearest_hit(ray_origin: &Vec3, ray_direction: &Vec3, scene: &Scene) -> Intersect {
+    let mut intersect = Intersect::empty();
+    let mut zbuffer = f32::INFINITY;
+
+    let _ = visit_objects(scene, ray_origin, ray_direction, f32::INFINITY, |_, object| {
+        let i = object.ray_intersect(ray_origin, ray_direction);
+        if i.is_intersecting && i.distance < zbuffer {
+            zbuffer = i.distance;
+            intersect = i;
+        }
+        ControlFlow::Continue(())
+    });
+
+    intersect
+}
+
+/// `true` si desde `hit` se ve el cielo hacia arriba (nada encima)
+fn open_sky(hit: &Intersect, scene: &Scene) -> bool {
+    let up = Vec3::new(0.0, 1.0, 0.0);
+    let origin = offset_origin(hit, &up, scene.bias);
+    !nearest_hit(&origin, &up, scene).is_intersecting
+}
+
+/// Aporte de las luces adicionales (`scene.extra_lights`) en `hit`, como
+/// (intensidad difusa, especular) por canal. Las que no proyectan sombras
+/// no trazan ningún rayo.
+fn extra_lighting(hit: &Intersect, view_dir: Vec3, scene: &Scene) -> (Vec3, Vec3) {
+    let mut diffuse = Vec3::zero();
+    let mut specular = Vec3::zero();
+    for light in scene.extra_lights.iter().filter(|l| l.lights(hit.material.light_groups)) {
+        let light_dir = (light.position - hit.point).normalized();
+        let cos = hit.normal.dot(light_dir);
+        if cos <= 0.0 {
+            continue;
+        }
+        let transmission = if light.casts_shadows {
+            shadow_ray(hit, light.position, scene)
+        } else {
+            Vec3::one()
+        };
+        let color = material::color_to_vector3(light.color) * light.intensity;
+        diffuse += color * transmission * cos;
+        specular += color * transmission * specular_lobe(hit, light_dir, view_dir);
+    }
+    (diffuse, specular)
+}
+
+/// Luz de la luz principal que atraviesa un material con `subsurface` y
+/// sale por `hit`. Un rayo corto por dentro hacia la luz mide el grosor;
+/// desde donde sale se prueba la sombra, y la luz se atenúa con el grosor.
+/// Se ve sobre todo mirando hacia la luz, como una hoja a contraluz.
+fn translucency(hit: &Intersect, sss: &Subsurface, light_dir: Vec3, view_dir: Vec3, scene: &Scene) -> Vec3 {
+    if hit.normal.dot(light_dir) >= 0.0 {
+        return Vec3::zero();
+    }
+    let origin = offset_origin(hit, &light_dir, scene.bias);
+    let exit = nearest_hit(&origin, &light_dir, scene);
+    if !exit.is_intersecting || exit.distance > sss.depth * 4.0 {
+        return Vec3::zero();
+    }
+    let behind = cast_shadow(&exit, scene) * scene.light.intensity;
+    let forward = view_dir.dot(-light_dir).max(0.0);
+    let spread = 0.25 + 0.75 * forward * forward;
+    sss.color * behind * ((-exit.distance / sss.depth).exp() * spread)
+}
+
+/// Brillo especular de una luz en `light_dir` visto desde `view_dir`:
+/// Phong con `specular`, o con `anisotropy` un lóbulo de Blinn cuyo
+/// exponente varía entre los ejes de la cara (Ashikhmin–Shirley)
+fn specular_lobe(hit: &Intersect, light_dir: Vec3, view_dir: Vec3) -> f32 {
+    let Some(aniso) = hit.material.anisotropy else {
+        let reflect_dir = reflect(&-light_dir, &hit.normal).normalized();
+        return view_dir.dot(reflect_dir).max(0.0).powf(hit.material.specular);
+    };
+    let half = (light_dir + view_dir).normalized();
+    let nh = hit.normal.dot(half);
+    if nh <= 0.0 || hit.normal.dot(light_dir) <= 0.0 {
+        return 0.0;
+    }
+    let (tangent, bitangent) = hit.face.tangent_frame(hit.normal);
+    let (th, bh) = (tangent.dot(half), bitangent.dot(half));
+    let exponent = (aniso.along_u * th * th + aniso.along_v * bh * bh) / (1.0 - nh * nh).max(1e-6);
+    nh.powf(exponent)
+}
+
+/// Luz directa difusa (con sombra) que sale de `hit`, ya multiplicada por
+/// el color de la textura y `albedo[0]`; la usa la caché de irradiancia
+pub(crate) fn direct_diffuse(hit: &Intersect, scene: &Scene) -> Vec3 {
+    let light_dir = (scene.light.position - hit.point).normalized();
+    let light_intensity = cast_shadow(hit, scene) * scene.light.intensity;
+    let (extra, _) = extra_lighting(hit, hit.normal, scene);
+    let diffuse_intensity =
+        light_intensity * hit.normal.dot(light_dir).max(0.0) + emissive_direct(hit, scene, 1, false) + extra;
+
+    let tex_color = hit.material.color_at(scene.tm, hit.u, hit.v, hit.inner_point());
+    let tex_v3 = Vec3::new(
+        tex_color.r as f32 / 255.0,
+        tex_color.g as f32 / 255.0,
+        tex_color.b as f32 / 255.0,
+    );
+    tex_v3 * diffuse_intensity * hit.material.albedo[0]
+}
+
+/// Agua con Fresnel: mezcla reflexión y refracción según el ángulo de vista
+/// (aproximación de Schlick) y tiñe lo refractado según la profundidad que
+/// atraviesa el rayo bajo la superficie
+fn shade_water(
+    ray_direction: &Vec3,
+    intersect: &Intersect,
+    optics: &water::WaterOptics,
+    specular: Vec3,
+    scene: &Scene,
+    depth: u32,
+    throughput: f32,
+) -> Vec3 {
+    let normal = intersect.normal;
+    let ior = intersect.material.refractive_index;
+    let cos_theta = ray_direction.dot(normal).abs().min(1.0);
+    let r0 = ((1.0 - ior) / (1.0 + ior)).powi(2);
+    let mut fresnel = r0 + (1.0 - r0) * (1.0 - cos_theta).powi(5);
+
+    let reflect_dir = reflect(ray_direction, &normal).normalized();
+    let reflect_origin = offset_origin(intersect, &reflect_dir, scene.bias);
+    let reflect_color = trace_ray(&reflect_origin, &reflect_dir, scene, depth + 1, throughput * fresnel);
+
+    let refract_color = match refract(ray_direction, &normal, ior) {
+        Some(refract_dir) => {
+            let refract_dir = refract_dir.normalized();
+            let refract_origin = offset_origin(intersect, &refract_dir, scene.bias);
+            let bottom = nearest_hit(&refract_origin, &refract_dir, scene);
+            let water_depth = if bottom.is_intersecting {
+                bottom.distance
+            } else {
+                optics.max_depth
+            };
+            let transmittance = optics.transmittance(water_depth);
+            let seen = trace_ray(
+                &refract_origin,
+                &refract_dir,
+                scene,
+                depth + 1,
+                throughput * (1.0 - fresnel) * transmittance.max_component(),
+            );
+            seen * transmittance + optics.deep_color * (Vec3::new(1.0, 1.0, 1.0) - transmittance)
+        }
+        None => {
+            // Reflexión interna total
+            fresnel = 1.0;
+            Vec3::zero()
+        }
+    };
+
+    reflect_color * fresnel + refract_color * (1.0 - fresnel) + specular
+}
+
+/// Rebotes que siempre se trazan; a partir de ahí decide la ruleta rusa
+/// (hasta `scene.max_depth`)
+const ROULETTE_DEPTH: u32 = 3;
+
+/// Color (lineal, 0..1 por canal) que ve un rayo: sombreado Phong con
+/// sombras, reflexión y refracción recursivas, más los extras activos en
+/// `scene` (partículas, agua, lightmaps, GI). Con `scene.matcap` solo se
+/// busca el impacto y se colorea por su normal
+pub fn cast_ray(
+    ray_origin: &Vec3,
+    ray_direction: &Vec3,
+    scene: &Scene,
+    depth: u32,
+) -> Vec3 {
+    if let Some(matcap) = scene.matcap {
+        let hit = nearest_hit(ray_origin, ray_direction, scene);
+        return matcap.shade(*ray_direction, hit.is_intersecting.then_some(hit.normal));
+    }
+    trace_ray(ray_origin, ray_direction, scene, depth, 1.0)
+}
+
+/// `cast_ray` con el peso (`throughput`) que tendrá este rayo en el píxel.
+/// Pasados `ROULETTE_DEPTH` rebotes el rayo sigue con probabilidad igual a
+/// su peso y, si sobrevive, su color se divide por esa probabilidad: los
+/// espejos enfrentados no se cortan de golpe, pero los rebotes que casi no
+/// aportan dejan de costar
+fn trace_ray(
+    ray_origin: &Vec3,
+    ray_direction: &Vec3,
+    scene: &Scene,
+    depth: u32,
+    throughput: f32,
+) -> Vec3 {
+    if depth > scene.max_depth {
+        return scene.sky.color(*ray_direction, scene.time);
+    }
+    let mut survival = 1.0;
+    if depth > ROULETTE_DEPTH {
+        survival = throughput.clamp(0.05, 1.0);
+        // Sin ruido azul: todas las ramas de un mismo píxel y rebote (reflejo,
+        // refracción, canales de dispersión) sacarían el mismo valor y se
+        // cortarían juntas. Cada rama siembra con su propio rayo
+        let mut rng = rng::Rng::at_point(scene.seed ^ depth as u64, *ray_origin + *ray_direction);
+        if rng.next_f32() >= survival {
+            return Vec3::zero();
+        }
+    }
+    shade(ray_origin, ray_direction, scene, depth, throughput) / survival
+}
+
+/// Sombreado del impacto más cercano del rayo (sin ruleta)
+fn shade(
+    ray_origin: &Vec3,
+    ray_direction: &Vec3,
+    scene: &Scene,
+    depth: u32,
+    throughput: f32,
+) -> Vec3 {
+    let mut intersect = nearest_hit(ray_origin, ray_direction, scene);
+
+    // Partículas (fuego, humo): brillan con su propio color y dejan ver lo
+    // que hay detrás según su opacidad
+    let surface_distance = if intersect.is_intersecting { intersect.distance } else { f32::INFINITY };
+    let particle = scene
+        .emitters
+        .iter()
+        .filter_map(|e| e.hit(ray_origin, ray_direction, scene.time, surface_distance))
+        .chain(scene.weather.and_then(|w| w.hit(ray_origin, ray_direction, scene.time, surface_distance)))
+        .min_by(|a, b| a.distance.total_cmp(&b.distance));
+    if let Some(p) = particle {
+        let behind = if p.opacity < 1.0 {
+            let behind_origin = *ray_origin + *ray_direction * (p.distance + 1e-3);
+            trace_ray(&behind_origin, ray_direction, scene, depth + 1, throughput * (1.0 - p.opacity))
+        } else {
+            Vec3::zero()
+        };
+        return p.color * p.opacity + behind * (1.0 - p.opacity);
+    }
+
+    if !intersect.is_intersecting {
+        return scene.sky.color(*ray_direction, scene.time);
+    }
+
+    // Agua animada: la normal ondulada alimenta reflexión y refracción
+    if let Some(waves) = &intersect.material.waves {
+        intersect.normal = waves.perturb(intersect.normal, intersect.point, scene.time);
+    }
+
+    // Nieve acumulada en las caras de arriba que quedan a cielo abierto
+    if let Some(snow) = scene.snow
+        && intersect.face == CubeFace::Top
+        && intersect.normal.y > 0.5
+        && intersect.material.water_optics.is_none()
+        && intersect.material.waves.is_none()
+        && open_sky(&intersect, scene)
+    {
+        intersect.material = snow.clone();
+    }
+
+    // Niebla hacia el horizonte sobre el color final de la superficie
+    let distance = intersect.distance;
+    let fogged = |color: Vec3| match scene.fog {
+        Some(fog) => fog.apply(color, *ray_origin, *ray_direction, distance, scene.sky, scene.time),
+        None => color,
+    };
+
+    let light_dir = (scene.light.position - intersect.point).normalized();
+    let view_dir = (*ray_origin - intersect.point).normalized();
+
+    // Con lightmaps horneados no hace falta trazar sombras ni luz indirecta
+    let baked = scene.lightmaps.and_then(|maps| maps.lookup(&intersect));
+    let light_transmission = match baked {
+        Some(texel) => texel.transmission,
+        None => cast_shadow(&intersect, scene),
+    };
+    let light_intensity = light_transmission * scene.light.intensity;
+
+    // ---- USAR TEXTURA (si existe) en lugar del color diffuse fijo ----
+    let tex_color = intersect
+        .material
+        .color_at(scene.tm, intersect.u, intersect.v, intersect.inner_point()); // devuelve raylib::Rgba
+    let mut tex_v3 = Vec3::new(
+        tex_color.r as f32 / 255.0,
+        tex_color.g as f32 / 255.0,
+        tex_color.b as f32 / 255.0,
+    );
+    // Con lluvia, las caras de arriba se ven mojadas (más oscuras)
+    if let Some(weather) = scene.weather {
+        tex_v3 *= weather.wetness(intersect.normal);
+    }
+    let mut diffuse = match baked {
+        Some(texel) => tex_v3 * texel.irradiance,
+        None => {
+            let cos = intersect.normal.dot(light_dir);
+            let diffuse_intensity = match (scene.cel, intersect.material.subsurface) {
+                (Some(cel), _) => light_intensity * cel.band(cos),
+                (None, Some(sss)) => light_intensity * sss.wrap_cos(cos),
+                (None, None) => light_intensity * cos.max(0.0),
+            };
+            let mut diffuse = tex_v3 * diffuse_intensity;
+            if let Some(sss) = &intersect.material.subsurface {
+                diffuse += tex_v3 * translucency(&intersect, sss, light_dir, view_dir, scene);
+            }
+            if let Some(photons) = scene.photons {
+                diffuse += tex_v3 * photons.irradiance(intersect.point, intersect.normal);
+            }
+            if let Some(sky) = scene.sky_light {
+                diffuse += tex_v3 * sky.diffuse(intersect.normal);
+            }
+            // Con la caché activa el hemisferio también ve los emisores: MIS
+            let mis = depth == 0 && scene.irradiance.is_some();
+            diffuse += tex_v3 * emissive_direct(&intersect, scene, scene.shadow_samples, mis);
+            // Luz indirecta solo en impactos primarios, para acotar el costo
+            if depth == 0
+                && let Some(cache) = scene.irradiance
+            {
+                diffuse += tex_v3 * cache.indirect(&intersect, scene);
+            }
+            diffuse
+        }
+    };
+    let (extra_diffuse, extra_specular) = extra_lighting(&intersect, view_dir, scene);
+    diffuse += tex_v3 * extra_diffuse;
+    diffuse += tex_v3 * scene.ambient;
+    diffuse *= intersect.ao;
+    // ------------------------------------------------------------------
+
+    let lobe = specular_lobe(&intersect, light_dir, view_dir);
+    let specular_intensity = light_intensity * scene.toon.map_or(lobe, |toon| toon.highlight(lobe));
+    let light_color_v3 = Vec3::new(
+        scene.light.color.r as f32 / 255.0,
+        scene.light.color.g as f32 / 255.0,
+        scene.light.color.b as f32 / 255.0,
+    );
+    // Con película delgada, lo especular (brillo y reflejo) se tiñe según
+    // el ángulo de vista
+    let film = intersect
+        .material
+        .thin_film
+        .map_or(Vec3::one(), |f| f.tint(view_dir.dot(intersect.normal).abs(), intersect.inner_point()));
+    let specular = (light_color_v3 * specular_intensity + extra_specular) * film;
+
+    if let Some(optics) = &intersect.material.water_optics {
+        return fogged(shade_water(ray_direction, &intersect, optics, specular * intersect.material.albedo[1], scene, depth, throughput));
+    }
+
+    let albedo = intersect.material.albedo;
+    let phong_color = diffuse * albedo[0] + specular * albedo[1];
+
+    let reflectivity = intersect.material.albedo[2];
+    let reflect_color = if reflectivity > 0.0 {
+        let reflect_dir = reflect(ray_direction, &intersect.normal).normalized();
+        let reflect_origin = offset_origin(&intersect, &reflect_dir, scene.bias);
+        trace_ray(&reflect_origin, &reflect_dir, scene, depth + 1, throughput * reflectivity) * film
+    } else {
+        Vec3::zero()
+    };
+
+    let transparency = intersect.material.albedo[3];
+    let refract_color = if transparency > 0.0 {
+        if let Some(iors) = intersect.material.dispersion {
+            refract_dispersed(ray_direction, &intersect, iors, scene, depth, throughput * transparency)
+        } else if let Some(refract_dir) =
+            refract(ray_direction, &intersect.normal, intersect.material.refractive_index)
+        {
+            let refract_origin = offset_origin(&intersect, &refract_dir, scene.bias);
+            trace_ray(&refract_origin, &refract_dir, scene, depth + 1, throughput * transparency)
+        } else {
+            let reflect_dir = reflect(ray_direction, &intersect.normal).normalized();
+            let reflect_origin = offset_origin(&intersect, &reflect_dir, scene.bias);
+            trace_ray(&reflect_origin, &reflect_dir, scene, depth + 1, throughput * transparency)
+        }
+    } else {
+        Vec3::zero()
+    };
+
+    let emitted = tex_v3 * intersect.material.emission_at(scene.time, intersect.inner_point());
+
+    let base = phong_color * (1.0 - reflectivity - transparency)
+        + reflect_color * reflectivity
+        + refract_color * transparency;
+
+    // Laca: tapa a la base según Fresnel y suma su propio brillo y reflejo
+    let Some(coat) = &intersect.material.clearcoat else {
+        return fogged(base + emitted);
+    };
+    let (coat_weight, coat_reflection) = clearcoat_reflection(ray_direction, &intersect, coat, scene, depth, throughput);
+    let half = (light_dir + view_dir).normalized();
+    let highlight = if intersect.normal.dot(light_dir) > 0.0 {
+        intersect.normal.dot(half).max(0.0).powf(coat.gloss) * coat.strength
+    } else {
+        0.0
+    };
+    let coat_color = coat_reflection * coat_weight + light_color_v3 * light_intensity * highlight;
+    fogged(base * (1.0 - coat_weight) + coat_color + emitted)
+}
+
+/// Reflejo de la laca de `intersect`: devuelve cuánto refleja (Fresnel ×
+/// `strength`) y el color que ve el rayo reflejado
+fn clearcoat_reflection(
+    ray_direction: &Vec3,
+    intersect: &Intersect,
+    coat: &Clearcoat,
+    scene: &Scene,
+    depth: u32,
+    throughput: f32,
+) -> (f32, Vec3) {
+    let cos = ray_direction.dot(intersect.normal).abs().min(1.0);
+    let fresnel = coat.fresnel(cos);
+    if fresnel < 1e-3 {
+        return (fresnel, Vec3::zero());
+    }
+    let reflect_dir = reflect(ray_direction, &intersect.normal).normalized();
+    let reflect_origin = offset_origin(intersect, &reflect_dir, scene.bias);
+    (fresnel, trace_ray(&reflect_origin, &reflect_dir, scene, depth + 1, throughput * fresnel))
+}
+
+/// Refracción con dispersión: un rayo por canal, cada uno con su índice, y
+/// de cada resultado se toma solo su canal. Si un canal se refleja por
+/// completo, ese canal sigue el rayo reflejado.
+fn refract_dispersed(
+    ray_direction: &Vec3,
+    intersect: &Intersect,
+    iors: [f32; 3],
+    scene: &Scene,
+    depth: u32,
+    throughput: f32,
+) -> Vec3 {
+    let mut channels = [0.0f32; 3];
+    for (channel, ior) in iors.into_iter().enumerate() {
+        let dir = refract(ray_direction, &intersect.normal, ior)
+            .unwrap_or_else(|| reflect(ray_direction, &intersect.normal).normalized());
+        let origin = offset_origin(intersect, &dir, scene.bias);
+        let color = trace_ray(&origin, &dir, scene, depth + 1, throughput);
+        channels[channel] = [color.x, color.y, color.z][channel];
+    }
+    Vec3::new(channels[0], channels[1], channels[2])
+}
+
+/// Renderiza un cuadro completo en color lineal, ya con el post-proceso de
+/// la escena; devuelve los píxeles por filas, de arriba hacia abajo
+/// (`width * height` colores)
+pub fn render_linear(
+    width: usize,
+    height: usize,
+    camera: &Camera,
+    scene: &Scene,
+) -> Vec<Vec3> {
+    let mut pixels: Vec<Vec3> = (0..width * height)
+        .into_par_iter()
+        .map(|idx| trace_pixel(idx % width, idx / width, width, height, camera, scene))
+        .collect();
+    apply_post(&mut pixels, width, height, camera, scene);
+    pixels
+}
+
+/// Como `render_linear`, cuantizado a 8 bits para mostrarlo
+pub fn render(
+    width: usize,
+    height: usize,
+    camera: &Camera,
+    scene: &Scene,
+) -> Vec<Rgba> {
+    render_linear(width, height, camera, scene)
+        .into_iter()
+        .enumerate()
+        .map(|(idx, c)| vector3_to_color_at(c, idx % width, idx / width, scene.dither))
+        .collect()
+}
+
+/// Como `render_linear`, pero abandona el cuadro (y devuelve `None`) apenas
+/// se levanta `cancel`; lo usa el hilo de render de la vista interactiva
+pub fn render_linear_cancellable(
+    width: usize,
+    height: usize,
+    camera: &Camera,
+    scene: &Scene,
+    cancel: &AtomicBool,
+) -> Option<Vec<Vec3>> {
+    let rows: Option<Vec<Vec<Vec3>>> = (0..height)
+        .into_par_iter()
+        .map(|y| {
+            if cancel.load(Ordering::Relaxed) {
+                return None;
+            }
+            Some((0..width).map(|x| trace_pixel(x, y, width, height, camera, scene)).collect())
+        })
+        .collect();
+    let mut pixels: Vec<Vec3> = rows?.into_iter().flatten().collect();
+    apply_post(&mut pixels, width, height, camera, scene);
+    Some(pixels)
+}
+
+/// Como `render_linear_cancellable`, cuantizado a 8 bits
+pub fn render_cancellable(
+    width: usize,
+    height: usize,
+    camera: &Camera,
+    scene: &Scene,
+    cancel: &AtomicBool,
+) -> Option<Vec<Rgba>> {
+    let pixels = render_linear_cancellable(width, height, camera, scene, cancel)?;
+    Some(
+        pixels
+            .into_iter()
+            .enumerate()
+            .map(|(idx, c)| vector3_to_color_at(c, idx % width, idx / width, scene.dither))
+            .collect(),
+    )
+}
+
+/// Contornos del modo caricatura (si está activo) y luego el post-proceso
+/// de la escena
+pub(crate) fn apply_post(pixels: &mut [Vec3], width: usize, height: usize, camera: &Camera, scene: &Scene) {
+    if let Some(toon) = scene.toon {
+        toon.outline(pixels, width, height, camera, scene);
+    }
+    if let Some(post) = scene.post {
+        post.apply(pixels, width, height, scene.time);
+    }
+}
+
+/// Dirección del rayo primario por el punto (x, y) de la imagen, en
+/// píxeles (con decimales para las sub-muestras)
+pub(crate) fn primary_direction(x: f32, y: f32, width: usize, height: usize, camera: &Camera) -> Vec3 {
+    let width_f = width as f32;
+    let height_f = height as f32;
+    let aspect_ratio = width_f / height_f;
+    let perspective_scale = (camera::FIELD_OF_VIEW * 0.5).tan();
+
+    let screen_x = (2.0 * x) / width_f - 1.0;
+    let screen_y = -(2.0 * y) / height_f + 1.0;
+
+    let screen_x = screen_x * aspect_ratio * perspective_scale;
+    let screen_y = screen_y * perspective_scale;
+
+    let ray_direction = Vec3::new(screen_x, screen_y, -1.0).normalized();
+    camera.basis_change(&ray_direction)
+}
+
+/// Canales del ruido azul para la muestra de la lente (los primeros son de
+/// sombras y ruleta rusa, ver `pixel_noise`)
+const LENS_CHANNEL: u32 = 64;
+
+/// Traza el rayo primario del píxel (x, y) y devuelve su color lineal, sin
+/// recortar a [0, 1]
+pub(crate) fn trace_pixel(
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+    camera: &Camera,
+    scene: &Scene,
+) -> Vec3 {
+    // Sub-píxeles de Halton: la muestra 0 es la esquina del píxel, así con
+    // una sola muestra la imagen no cambia
+    let samples = scene.pixel_samples.max(1);
+    let mut sum = Vec3::zero();
+    for i in scene.sample_offset..scene.sample_offset + samples {
+        let (dx, dy) = sampling::halton(i);
+        let scene = &Scene { pixel: Some((x, y, i)), frame: (width, height), ..*scene };
+        let direction = primary_direction(x as f32 + dx, y as f32 + dy, width, height, camera);
+
+        sum += if camera.aperture > 0.0 {
+            let lens = (
+                (blue_noise::sample(x, y, LENS_CHANNEL) + i as f32 * 0.618_034).fract(),
+                (blue_noise::sample(x, y, LENS_CHANNEL + 1) + i as f32 * 0.754_877_7).fract(),
+            );
+            let (origin, direction) = camera.lens_ray(&direction, lens);
+            cast_ray(&origin, &direction, scene, 0)
+        } else {
+            cast_ray(&camera.eye, &direction, scene, 0)
+        };
+    }
+    sum / samples as f32
+}
+
+/// Lo primero que se ve en el punto (x, y) de la imagen (rayo por el
+/// centro de la lente); `None` si ahí solo hay cielo
+pub fn pick(x: f32, y: f32, width: usize, height: usize, camera: &Camera, scene: &Scene) -> Option<Intersect> {
+    let direction = primary_direction(x, y, width, height, camera);
+    let hit = nearest_hit(&camera.eye, &direction, scene);
+    hit.is_intersecting.then_some(hit)
+}
+
+/// Distancia de enfoque (a lo largo de la vista) del objeto que se ve en el
+/// punto (x, y) de la imagen; `None` si ahí solo hay cielo
+pub fn autofocus(x: f32, y: f32, width: usize, height: usize, camera: &Camera, scene: &Scene) -> Option<f32> {
+    let hit = pick(x, y, width, height, camera, scene)?;
+    let direction = (hit.point - camera.eye).normalized();
+    Some(hit.distance * direction.dot(camera.forward))
+}
+
+/// Render sin ventana, por scanlines, con barra de progreso. Devuelve el
+/// buffer lineal (HDR, sin recortar) para que el exportador elija cómo
+/// cuantizarlo. Si `cancel` se levanta a medio render, las filas pendientes
+/// quedan en negro y se devuelve `false` (la imagen queda parcial pero
+/// utilizable). La caché de irradiancia, si hay, se llena antes en un solo
+/// hilo para que el resultado no dependa del reparto entre hilos.
+pub fn render_offline(
+    width: usize,
+    height: usize,
+    camera: &Camera,
+    scene: &Scene,
+    cancel: &AtomicBool,
+) -> (Vec<Vec3>, bool) {
+    if let Some(cache) = scene.irradiance {
+        cache.prefill(width, height, camera, scene);
+    }
+    let progress = progress::Progress::new(height);
+
+    let rows: Vec<Option<Vec<Vec3>>> = (0..height)
+        .into_par_iter()
+        .map(|y| {
+            if cancel.load(Ordering::Relaxed) {
+                return None;
+            }
+            let row = (0..width)
+                .map(|x| trace_pixel(x, y, width, height, camera, scene))
+                .collect();
+            progress.tick();
+            Some(row)
+        })
+        .collect();
+    progress.finish();
+
+    let complete = rows.iter().all(Option::is_some);
+    let mut pixels: Vec<Vec3> = rows
+        .into_iter()
+        .flat_map(|row| row.unwrap_or_else(|| vec![Vec3::zero(); width]))
+        .collect();
+    apply_post(&mut pixels, width, height, camera, scene);
+    (pixels, complete)
+}
+
+/// Renderiza un cuadro completo a un buffer RGBA8 plano, sin pasar por el
+/// framebuffer de raylib (para exportadores de animación)
+pub fn render_rgba(
+    width: usize,
+    height: usize,
+    camera: &Camera,
+    scene: &Scene,
+) -> Vec<u8> {
+    render(width, height, camera, scene)
+        .into_iter()
+        .flat_map(|c| [c.r, c.g, c.b, c.a])
+        .collect()
+}
+
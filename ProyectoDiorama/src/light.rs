@@ -1,17 +1,91 @@
-use raylib::prelude::*;
+use crate::math::{Rgba, Vec3};
+
+/// Recorrido paramétrico de una luz para demos de iluminación: un círculo
+/// horizontal alrededor de `center` con la altura oscilando
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LightPath {
+    /// Centro del círculo (su `y` es la altura media)
+    pub center: Vec3,
+    pub radius: f32,
+    /// Radianes por segundo alrededor del centro
+    pub speed: f32,
+    /// Cuánto sube y baja la luz respecto de `center.y`
+    pub bob: f32,
+    /// Oscilaciones de altura por segundo
+    pub bob_speed: f32,
+}
+
+impl LightPath {
+    pub fn position_at(&self, time: f32) -> Vec3 {
+        let angle = time * self.speed;
+        let height = self.bob * (time * self.bob_speed * std::f32::consts::TAU).sin();
+        self.center + Vec3::new(angle.cos() * self.radius, height, angle.sin() * self.radius)
+    }
+}
+
+/// Máscara de todos los grupos de luces
+pub const ALL_GROUPS: u32 = u32::MAX;
 
 pub struct Light {
-    pub position: Vector3,
-    pub color: Color,
+    pub position: Vec3,
+    pub color: Rgba,
     pub intensity: f32,
+    /// Radio del disco emisor para sombras suaves; 0 = luz puntual
+    pub radius: f32,
+    /// Si está presente, la luz recorre este camino y `position` se ignora
+    pub path: Option<LightPath>,
+    /// `false` para luces de relleno: iluminan sin trazar rayos de sombra
+    pub casts_shadows: bool,
+    /// Grupos de objetos que ilumina (un bit por grupo, ver
+    /// `Material::light_groups`); por defecto, todos
+    pub groups: u32,
 }
 
 impl Light {
-    pub fn new(position: Vector3, color: Color, intensity: f32) -> Self {
+    pub fn new(position: Vec3, color: Rgba, intensity: f32) -> Self {
         Light {
             position,
             color,
             intensity,
+            radius: 0.0,
+            path: None,
+            casts_shadows: true,
+            groups: ALL_GROUPS,
         }
     }
+
+    pub fn with_radius(mut self, radius: f32) -> Self {
+        self.radius = radius;
+        self
+    }
+
+    pub fn with_shadows(mut self, casts_shadows: bool) -> Self {
+        self.casts_shadows = casts_shadows;
+        self
+    }
+
+    pub fn with_groups(mut self, groups: u32) -> Self {
+        self.groups = groups;
+        self
+    }
+
+    /// `true` si la luz alcanza a objetos de los grupos `groups`
+    pub fn lights(&self, groups: u32) -> bool {
+        self.groups & groups != 0
+    }
+
+    pub fn with_path(mut self, path: LightPath) -> Self {
+        self.path = Some(path);
+        self
+    }
+
+    /// Posición en el instante `time`
+    pub fn position_at(&self, time: f32) -> Vec3 {
+        self.path.map_or(self.position, |p| p.position_at(time))
+    }
+
+    /// `true` si la luz se mueve con el tiempo
+    pub fn is_animated(&self) -> bool {
+        self.path.is_some_and(|p| p.speed != 0.0 || p.bob != 0.0)
+    }
 }
\ No newline at end of file
@@ -0,0 +1,131 @@
+use std::collections::HashMap;
+use rayon::prelude::*;
+use crate::math::Vec3;
+
+use crate::cast_shadow;
+use crate::ray_intersect::{CubeFace, Intersect};
+use crate::scene::Scene;
+use crate::voxel_world::ExposedFace;
+
+/// Iluminación horneada en un punto de una cara
+#[derive(Debug, Clone, Copy)]
+pub struct Texel {
+    /// Luz difusa que llega (directa + indirecta), a multiplicar por el color
+    pub irradiance: Vec3,
+    /// Luz principal que llega tras atravesar bloques transparentes (cero = en sombra)
+    pub transmission: Vec3,
+}
+
+impl Texel {
+    fn lerp(a: Texel, b: Texel, t: f32) -> Texel {
+        Texel {
+            irradiance: a.irradiance + (b.irradiance - a.irradiance) * t,
+            transmission: a.transmission + (b.transmission - a.transmission) * t,
+        }
+    }
+}
+
+/// Clave de una cara: celda del vóxel y lado
+type FaceKey = ((i32, i32, i32), CubeFace);
+
+/// Lightmaps por cara expuesta: una grilla `resolution × resolution` de
+/// texels en el espacio UV de la cara. Con ellos la vista interactiva se
+/// ahorra los rayos de sombra y de luz indirecta en impactos con vóxeles.
+pub struct Lightmaps {
+    resolution: usize,
+    faces: HashMap<FaceKey, Vec<Texel>>,
+}
+
+impl Lightmaps {
+    /// Calcula la iluminación de cada cara con todo lo que la escena tenga
+    /// activo (luz directa, cáusticas, cielo e irradiancia indirecta)
+    pub fn bake(faces: &[ExposedFace], scene: &Scene, resolution: usize) -> Self {
+        let resolution = resolution.max(1);
+        let baked = faces
+            .par_iter()
+            .map(|face| {
+                let mut texels = Vec::with_capacity(resolution * resolution);
+                for j in 0..resolution {
+                    for i in 0..resolution {
+                        let u = (i as f32 + 0.5) / resolution as f32;
+                        let v = (j as f32 + 0.5) / resolution as f32;
+                        texels.push(bake_texel(face, u, v, scene));
+                    }
+                }
+                ((face.position, face.face), texels)
+            })
+            .collect();
+        Lightmaps { resolution, faces: baked }
+    }
+
+    pub fn face_count(&self) -> usize {
+        self.faces.len()
+    }
+
+    /// Texel interpolado en el punto de impacto, si cayó sobre una cara horneada
+    pub fn lookup(&self, hit: &Intersect) -> Option<Texel> {
+        let cell = hit.point - hit.normal * 0.5;
+        let pos = (cell.x.round() as i32, cell.y.round() as i32, cell.z.round() as i32);
+        let texels = self.faces.get(&(pos, hit.face))?;
+
+        // Descarta superficies que no están sobre la cara (p. ej. planos de agua)
+        let center = Vec3::new(pos.0 as f32, pos.1 as f32, pos.2 as f32) + hit.face.normal() * 0.5;
+        if (hit.point - center).dot(hit.face.normal()).abs() > 1e-3 {
+            return None;
+        }
+
+        // UV geométricas de la cara (las de `hit` pueden estar reorientadas)
+        let ((ux, uy, uz), (vx, vy, vz)) = hit.face.uv_axes();
+        let local = hit.point - center;
+        let u = local.dot(Vec3::new(ux as f32, uy as f32, uz as f32)) + 0.5;
+        let v = local.dot(Vec3::new(vx as f32, vy as f32, vz as f32)) + 0.5;
+
+        let res = self.resolution;
+        let fx = (u * res as f32 - 0.5).clamp(0.0, (res - 1) as f32);
+        let fy = (v * res as f32 - 0.5).clamp(0.0, (res - 1) as f32);
+        let (x0, y0) = (fx as usize, fy as usize);
+        let (x1, y1) = ((x0 + 1).min(res - 1), (y0 + 1).min(res - 1));
+        let (tx, ty) = (fx - x0 as f32, fy - y0 as f32);
+
+        let bottom = Texel::lerp(texels[y0 * res + x0], texels[y0 * res + x1], tx);
+        let top = Texel::lerp(texels[y1 * res + x0], texels[y1 * res + x1], tx);
+        Some(Texel::lerp(bottom, top, ty))
+    }
+}
+
+fn bake_texel(face: &ExposedFace, u: f32, v: f32, scene: &Scene) -> Texel {
+    let (px, py, pz) = face.position;
+    let ((ux, uy, uz), (vx, vy, vz)) = face.face.uv_axes();
+    let u_axis = Vec3::new(ux as f32, uy as f32, uz as f32);
+    let v_axis = Vec3::new(vx as f32, vy as f32, vz as f32);
+    let normal = face.face.normal();
+    let point = Vec3::new(px as f32, py as f32, pz as f32)
+        + normal * 0.5
+        + u_axis * (u - 0.5)
+        + v_axis * (v - 0.5);
+
+    let hit = Intersect {
+        point,
+        normal,
+        is_intersecting: true,
+        u,
+        v,
+        face: face.face,
+        ..Intersect::empty()
+    };
+
+    let light_dir = (scene.light.position - point).normalized();
+    let transmission = cast_shadow(&hit, scene);
+    let mut irradiance = transmission * normal.dot(light_dir).max(0.0) * scene.light.intensity;
+    if let Some(photons) = scene.photons {
+        irradiance += photons.irradiance(point, normal);
+    }
+    if let Some(sky) = scene.sky_light {
+        irradiance += sky.diffuse(normal);
+    }
+    if let Some(cache) = scene.irradiance {
+        irradiance += cache.indirect(&hit, scene);
+    }
+
+    Texel { irradiance, transmission }
+}
@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+
+use crate::cube::Cube;
+use crate::geometry::Aabb;
+use crate::math::Vec3;
+use crate::ray_intersect::{Intersect, Occlusion, RayIntersect};
+
+/// Lado en vóxeles de cada trozo del mundo
+pub const CHUNK_SIZE: i32 = 8;
+
+/// Trozo del mundo con dos niveles de detalle: los cubos originales y una
+/// versión a media resolución (un cubo de 2×2×2 donde al menos la mitad de
+/// las celdas estaban llenas). Los rayos que parten lejos del trozo recorren
+/// la versión gruesa, con un octavo de los cubos.
+pub struct LodChunk {
+    fine: Vec<Cube>,
+    coarse: Vec<Cube>,
+    bounds: Aabb,
+    center: Vec3,
+    /// Distancia desde la que se usa la versión gruesa
+    distance: f32,
+}
+
+impl LodChunk {
+    fn new(fine: Vec<Cube>, distance: f32) -> Option<Self> {
+        let bounds = fine
+            .iter()
+            .filter_map(|c| c.bounds())
+            .reduce(|a, b| a.union(&b))?;
+        let coarse = coarsen(&fine);
+        Some(LodChunk { center: bounds.center(), fine, coarse, bounds, distance })
+    }
+
+    fn level(&self, ray_origin: &Vec3) -> &[Cube] {
+        if (*ray_origin - self.center).length() > self.distance {
+            &self.coarse
+        } else {
+            &self.fine
+        }
+    }
+}
+
+/// Agrupa los cubos en trozos de `CHUNK_SIZE` vóxeles; cada trozo usa su
+/// versión gruesa para rayos que parten a más de `distance`
+pub fn build_chunks(cubes: &[Cube], distance: f32) -> Vec<LodChunk> {
+    let mut groups: HashMap<(i32, i32, i32), Vec<Cube>> = HashMap::new();
+    for cube in cubes {
+        let c = cube.center;
+        let key = (
+            (c.x.round() as i32).div_euclid(CHUNK_SIZE),
+            (c.y.round() as i32).div_euclid(CHUNK_SIZE),
+            (c.z.round() as i32).div_euclid(CHUNK_SIZE),
+        );
+        groups.entry(key).or_default().push(cube.clone());
+    }
+    let mut keys: Vec<_> = groups.keys().copied().collect();
+    keys.sort();
+    keys.into_iter()
+        .filter_map(|k| LodChunk::new(groups.remove(&k)?, distance))
+        .collect()
+}
+
+/// Media resolución: un cubo de lado 2 por celda de 2×2×2 con al menos 4
+/// vóxeles llenos, con el material del primero y el AO promedio de los
+/// llenos. Las cajas que no son un vóxel (bloques parciales, plataformas)
+/// pasan tal cual: no ocupan una celda entera ni caben en una sola.
+fn coarsen(fine: &[Cube]) -> Vec<Cube> {
+    let unit = Vec3::new(0.5, 0.5, 0.5);
+    let mut cells: HashMap<(i32, i32, i32), Vec<usize>> = HashMap::new();
+    let mut passthrough = Vec::new();
+    for (i, cube) in fine.iter().enumerate() {
+        if cube.half_extents != unit {
+            passthrough.push(cube.clone());
+            continue;
+        }
+        let c = cube.center;
+        let key = (
+            (c.x.round() as i32).div_euclid(2),
+            (c.y.round() as i32).div_euclid(2),
+            (c.z.round() as i32).div_euclid(2),
+        );
+        cells.entry(key).or_default().push(i);
+    }
+    let mut coarse: Vec<((i32, i32, i32), Cube)> = cells
+        .into_iter()
+        .filter(|(_, filled)| filled.len() >= 4)
+        .map(|((x, y, z), filled)| {
+            let center = Vec3::new(2.0 * x as f32 + 0.5, 2.0 * y as f32 + 0.5, 2.0 * z as f32 + 0.5);
+            let mut ao = [[0.0; 4]; 6];
+            for &i in &filled {
+                for (face, corners) in ao.iter_mut().zip(fine[i].ao) {
+                    for (sum, corner) in face.iter_mut().zip(corners) {
+                        *sum += corner / filled.len() as f32;
+                    }
+                }
+            }
+            ((x, y, z), Cube::new(center, 2.0, fine[filled[0]].material.clone()).with_ao(ao))
+        })
+        .collect();
+    coarse.sort_by_key(|(key, _)| *key);
+    coarse.into_iter().map(|(_, cube)| cube).chain(passthrough).collect()
+}
+
+impl RayIntersect for LodChunk {
+    fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Intersect {
+        if !self.bounds.hit(ray_origin, ray_direction, f32::INFINITY) {
+            return Intersect::empty();
+        }
+        let mut best = Intersect::empty();
+        for cube in self.level(ray_origin) {
+            let hit = cube.ray_intersect(ray_origin, ray_direction);
+            if hit.is_intersecting && (!best.is_intersecting || hit.distance < best.distance) {
+                best = hit;
+            }
+        }
+        best
+    }
+
+    fn occlusion(&self, ray_origin: &Vec3, ray_direction: &Vec3, max_t: f32) -> Occlusion {
+        if !self.bounds.hit(ray_origin, ray_direction, max_t) {
+            return Occlusion::Clear;
+        }
+        let mut nearest: Option<Box<Intersect>> = None;
+        for cube in self.level(ray_origin) {
+            match cube.occlusion(ray_origin, ray_direction, max_t) {
+                Occlusion::Clear => {}
+                Occlusion::Opaque => return Occlusion::Opaque,
+                Occlusion::Tinted(hit) => {
+                    if nearest.as_ref().is_none_or(|n| hit.distance < n.distance) {
+                        nearest = Some(hit);
+                    }
+                }
+            }
+        }
+        nearest.map_or(Occlusion::Clear, Occlusion::Tinted)
+    }
+
+    fn bounds(&self) -> Option<Aabb> {
+        Some(self.bounds)
+    }
+
+    fn primitive_count(&self) -> usize {
+        self.fine.len()
+    }
+
+    fn memory_bytes(&self) -> usize {
+        std::mem::size_of::<Self>() + (self.fine.len() + self.coarse.len()) * std::mem::size_of::<Cube>()
+    }
+}
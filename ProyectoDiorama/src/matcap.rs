@@ -0,0 +1,75 @@
+use crate::error::Result;
+use crate::math::Vec3;
+use crate::rng::orthonormal_basis;
+use crate::textures::Texture;
+
+/// Fondo de los rayos que no chocan: gris parejo, así las siluetas se leen
+/// sin el cielo detrás
+const BACKGROUND: Vec3 = Vec3::new(0.12, 0.12, 0.13);
+
+/// Sombreado de depuración "matcap": cada superficie toma el color de una
+/// esfera fotografiada (o la de arcilla incluida) en el punto que tiene la
+/// misma normal vista desde la cámara. No usa luces, sombras ni materiales,
+/// así que sirve para revisar normales y siluetas rápido.
+#[derive(Clone)]
+pub struct Matcap {
+    /// Imagen de la esfera; `None` usa la arcilla procedural
+    image: Option<Texture>,
+}
+
+impl Matcap {
+    /// Arcilla gris con luz arriba a la izquierda y un borde claro
+    pub fn clay() -> Self {
+        Matcap { image: None }
+    }
+
+    /// Esfera desde una imagen cuadrada (el disco ocupa toda la imagen)
+    pub fn load(path: &str) -> Result<Self> {
+        Ok(Matcap { image: Some(Texture::load(path)?) })
+    }
+
+    /// `clay` o la imagen de `spec`, según lo que pida `--matcap`
+    pub fn from_spec(spec: &str) -> Result<Self> {
+        if spec == "clay" { Ok(Self::clay()) } else { Self::load(spec) }
+    }
+
+    /// Color para un rayo en `direction` que choca con una superficie de
+    /// normal `normal`; sin choque, el fondo
+    pub fn shade(&self, direction: Vec3, normal: Option<Vec3>) -> Vec3 {
+        let Some(normal) = normal else {
+            return BACKGROUND;
+        };
+        // Marco de vista de este rayo: adelante hacia la cámara, arriba lo
+        // más cerca posible del eje Y del mundo
+        let forward = -direction.normalized();
+        let world_up = Vec3::new(0.0, 1.0, 0.0);
+        let right = world_up.cross(forward);
+        let (right, up) = if right.length() < 1e-4 {
+            orthonormal_basis(forward)
+        } else {
+            let right = right.normalized();
+            (right, forward.cross(right))
+        };
+        let (x, y) = (normal.dot(right), normal.dot(up));
+
+        match &self.image {
+            Some(image) => {
+                let c = image.sample((0.5 + 0.5 * x, 0.5 + 0.5 * y));
+                Vec3::new(c.r as f32, c.g as f32, c.b as f32) / 255.0
+            }
+            None => clay(x, y),
+        }
+    }
+}
+
+/// Esfera de arcilla procedural en el punto (x, y) del disco unitario
+fn clay(x: f32, y: f32) -> Vec3 {
+    let z = (1.0 - x * x - y * y).max(0.0).sqrt();
+    let n = Vec3::new(x, y, z);
+    let key = Vec3::new(-0.5, 0.6, 0.62).normalized();
+    let diffuse = n.dot(key).max(0.0);
+    let highlight = n.dot((key + Vec3::new(0.0, 0.0, 1.0)).normalized()).max(0.0).powf(40.0);
+    let rim = (1.0 - z).powi(3);
+    let base = Vec3::new(0.72, 0.68, 0.64);
+    base * (0.2 + 0.8 * diffuse) + Vec3::one() * (0.3 * highlight + 0.25 * rim)
+}
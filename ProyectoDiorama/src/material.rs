@@ -1,19 +1,264 @@
-use raylib::prelude::Color;
+use std::collections::HashMap;
+use std::sync::Arc;
+use crate::math::Rgba;
+use crate::light::ALL_GROUPS;
+use crate::ray_intersect::CubeFace;
+use crate::rng::Rng;
 use crate::texture_manager::TextureManager;
-use raylib::prelude::Vector3;
+use crate::water::{WaterOptics, Waves};
+use crate::math::Vec3;
+
+/// Orientación de la textura en una cara: espejado y giros de 90°
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UvTransform {
+    /// Giros antihorarios de la textura (0..=3)
+    pub quarter_turns: u8,
+    pub flip_u: bool,
+    pub flip_v: bool,
+}
+
+impl UvTransform {
+    pub const IDENTITY: UvTransform = UvTransform { quarter_turns: 0, flip_u: false, flip_v: false };
+    pub const FLIP_U: UvTransform = UvTransform { quarter_turns: 0, flip_u: true, flip_v: false };
+    pub const FLIP_V: UvTransform = UvTransform { quarter_turns: 0, flip_u: false, flip_v: true };
+
+    pub fn apply(&self, u: f32, v: f32) -> (f32, f32) {
+        let u = if self.flip_u { 1.0 - u } else { u };
+        let v = if self.flip_v { 1.0 - v } else { v };
+        (0..self.quarter_turns % 4).fold((u, v), |(u, v), _| (v, 1.0 - u))
+    }
+}
+
+/// Orientación por defecto (índice `CubeFace::index`), estilo Minecraft: en
+/// las caras laterales `u` crece hacia la derecha vista desde afuera y `v`
+/// hacia arriba; la de arriba queda con `v` hacia −Z. Coincide con las UV de
+/// `CubeFace::corners` que usan los exportadores.
+pub const DEFAULT_FACE_UV: [UvTransform; 6] = [
+    UvTransform::IDENTITY, // Front
+    UvTransform::FLIP_U,   // Back
+    UvTransform::IDENTITY, // Left
+    UvTransform::FLIP_U,   // Right
+    UvTransform::FLIP_V,   // Top
+    UvTransform::IDENTITY, // Bottom
+];
+
+/// Tinte que depende de la posición en el mundo, para que una textura
+/// repetida no se vea idéntica en todo el mapa: un degradado horizontal
+/// (p. ej. pasto más seco hacia un borde) y una variación de brillo por
+/// bloque. Los colores multiplican al de la textura (1 = sin cambio).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tint {
+    /// Variación máxima de brillo por bloque (0.1 = ±10 %)
+    pub jitter: f32,
+    pub gradient: Option<Gradient>,
+}
+
+/// Degradado de color en XZ entre dos puntos; fuera del tramo se mantiene
+/// el color del extremo más cercano
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Gradient {
+    pub from: (f32, f32),
+    pub from_color: Vec3,
+    pub to: (f32, f32),
+    pub to_color: Vec3,
+}
+
+impl Tint {
+    pub fn jitter(amount: f32) -> Self {
+        Tint { jitter: amount.clamp(0.0, 1.0), gradient: None }
+    }
+
+    pub fn with_gradient(mut self, from: (f32, f32), from_color: Vec3, to: (f32, f32), to_color: Vec3) -> Self {
+        self.gradient = Some(Gradient { from, from_color, to, to_color });
+        self
+    }
+
+    /// Factor por canal en `point` (un punto apenas dentro del bloque)
+    pub fn factor(&self, point: Vec3) -> Vec3 {
+        let mut factor = Vec3::one();
+        if let Some(g) = self.gradient {
+            let ((x0, z0), (x1, z1)) = (g.from, g.to);
+            let (dx, dz) = (x1 - x0, z1 - z0);
+            let length2 = (dx * dx + dz * dz).max(1e-6);
+            let t = (((point.x - x0) * dx + (point.z - z0) * dz) / length2).clamp(0.0, 1.0);
+            factor = g.from_color * (1.0 - t) + g.to_color * t;
+        }
+        if self.jitter > 0.0 {
+            // Los cubos están centrados en coordenadas enteras
+            let cell = Vec3::new(point.x.round(), point.y.round(), point.z.round());
+            let r = Rng::at_point(0x7147, cell).next_f32();
+            factor *= 1.0 + self.jitter * (r * 2.0 - 1.0);
+        }
+        factor
+    }
+}
+
+/// Animación de la luz propia: el brillo oscila alrededor de `emission`.
+/// Cada bloque tiene su propia fase, así dos antorchas no laten a la par.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Flicker {
+    /// Variación máxima del brillo (0.2 = ±20 %)
+    pub amplitude: f32,
+    /// Oscilaciones (o cambios de ruido) por segundo
+    pub frequency: f32,
+    /// Ruido suave en vez de una senoide: parpadeo de llama
+    pub noise: bool,
+}
+
+impl Flicker {
+    /// Factor de brillo en `time` para el bloque que contiene `point`
+    pub fn factor(&self, time: f32, point: Vec3) -> f32 {
+        let cell = Vec3::new(point.x.round(), point.y.round(), point.z.round());
+        let phase = Rng::at_point(0xF11C, cell).next_f32();
+        let x = time * self.frequency + phase * 16.0;
+        let wave = if self.noise {
+            // Ruido de valor 1D: valores al azar por entero, interpolados
+            let value = |i: f32| Rng::at_point(0xF11C, cell + Vec3::new(i, 0.0, 0.0)).next_f32() * 2.0 - 1.0;
+            let (i, f) = (x.floor(), x - x.floor());
+            let k = f * f * (3.0 - 2.0 * f);
+            value(i) * (1.0 - k) + value(i + 1.0) * k
+        } else {
+            (x * std::f32::consts::TAU).sin()
+        };
+        (1.0 + self.amplitude * wave).max(0.0)
+    }
+}
+
+/// Aproximación de luz bajo la superficie (cera, jade, slime): la luz
+/// difusa se envuelve un poco hacia el lado oscuro y la que llega por
+/// detrás atraviesa el bloque, atenuada según su grosor.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Subsurface {
+    /// Color de la luz que atraviesa el material
+    pub color: Vec3,
+    /// Cuánto se envuelve la luz difusa (0 = Lambert)
+    pub wrap: f32,
+    /// Grosor en el que la luz transmitida cae a 1/e
+    pub depth: f32,
+}
+
+impl Subsurface {
+    /// Término difuso con envoltura para el coseno `cos` entre normal y luz
+    pub fn wrap_cos(&self, cos: f32) -> f32 {
+        ((cos + self.wrap) / (1.0 + self.wrap)).max(0.0)
+    }
+}
+
+/// Película delgada (pompa de jabón, mancha de aceite): la luz reflejada
+/// arriba y abajo de la película interfiere y cada longitud de onda se
+/// refuerza o se apaga según el grosor y el ángulo de vista.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ThinFilm {
+    /// Grosor de la película en nanómetros
+    pub thickness: f32,
+    /// Cuánto varía el grosor sobre la superficie (en nanómetros), para que
+    /// los colores formen remolinos en vez de un tono parejo
+    pub variation: f32,
+    /// Índice de refracción de la película
+    pub ior: f32,
+}
+
+impl ThinFilm {
+    /// Longitudes de onda (nm) representativas de cada canal
+    const WAVELENGTHS: [f32; 3] = [650.0, 510.0, 475.0];
+
+    /// Color por el que se multiplica lo especular en `point`, con `cos` el
+    /// coseno entre la vista y la normal
+    pub fn tint(&self, cos: f32, point: Vec3) -> Vec3 {
+        let swirl = (point.x * 1.7 + (point.z * 2.3).sin()).sin() * (point.y * 1.3 + point.z * 0.9).cos();
+        let thickness = (self.thickness + self.variation * swirl).max(0.0);
+        // Ángulo dentro de la película (Snell) y diferencia de camino óptico
+        let sin2 = (1.0 - cos * cos) / (self.ior * self.ior);
+        let cos_t = (1.0 - sin2).max(0.0).sqrt();
+        let path = 2.0 * self.ior * thickness * cos_t;
+        // El reflejo de arriba se invierte (media onda): a grosor cero no
+        // hay reflejo, como en el borde negro de una pompa
+        let channel = |lambda: f32| 0.5 - 0.5 * (std::f32::consts::TAU * path / lambda).cos();
+        let [r, g, b] = Self::WAVELENGTHS;
+        Vec3::new(channel(r), channel(g), channel(b)) * 2.0
+    }
+}
+
+/// Brillo anisótropo (metal cepillado): el exponente especular cambia
+/// según la dirección sobre la cara, así el brillo se estira en vez de ser
+/// un punto redondo. Los ejes son los de `u` y `v` de cada cara.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Anisotropy {
+    /// Exponente a lo largo de `u`: más bajo, brillo más estirado en `u`
+    pub along_u: f32,
+    /// Exponente a lo largo de `v`
+    pub along_v: f32,
+}
+
+/// Capa de laca transparente sobre el material (plástico pintado): un
+/// segundo brillo y un reflejo propios que, por Fresnel, cubren más a la
+/// base en ángulos rasantes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Clearcoat {
+    /// Cuánto pesa la capa (0 = sin laca, 1 = laca completa)
+    pub strength: f32,
+    /// Exponente del brillo de la laca (más alto = más pulida)
+    pub gloss: f32,
+    /// Índice de refracción de la laca
+    pub ior: f32,
+}
+
+impl Clearcoat {
+    /// Fracción de la luz que refleja la laca con la vista a coseno `cos`
+    /// de la normal (Schlick), ya multiplicada por `strength`
+    pub fn fresnel(&self, cos: f32) -> f32 {
+        let r0 = ((1.0 - self.ior) / (1.0 + self.ior)).powi(2);
+        self.strength * (r0 + (1.0 - r0) * (1.0 - cos).powi(5))
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct Material {
-    pub diffuse: Vector3,
+    pub diffuse: Vec3,
     pub albedo: [f32; 4],
     pub specular: f32,
     pub refractive_index: f32,
     pub texture_key: Option<char>,
+    /// Si está presente, la normal de las caras superiores ondula con el tiempo
+    pub waves: Option<Waves>,
+    /// Si está presente, se sombrea como agua con Fresnel y tinte por profundidad
+    pub water_optics: Option<WaterOptics>,
+    /// Orientación de la textura en cada cara del cubo
+    pub face_uv: [UvTransform; 6],
+    /// Repeticiones de la textura por unidad del mundo (u, v); 1.0 = una
+    /// vez por bloque, también en cajas más grandes
+    pub uv_scale: (f32, f32),
+    /// Desplazamiento de la textura antes de repetirla
+    pub uv_offset: (f32, f32),
+    /// Luz propia: el color de la textura se suma multiplicado por esto
+    pub emission: f32,
+    /// Animación del brillo propio, si la tiene
+    pub flicker: Option<Flicker>,
+    /// Grupos de luces a los que pertenece (un bit por grupo): solo lo
+    /// iluminan las luces que comparten alguno
+    pub light_groups: u32,
+    /// Tinte según la posición en el mundo, si lo tiene (compartido: el
+    /// material se copia en cada impacto)
+    pub tint: Option<Arc<Tint>>,
+    /// Índice de refracción por canal (r, g, b), si lo separa: el vidrio
+    /// abre la luz en franjas de arcoíris
+    pub dispersion: Option<[f32; 3]>,
+    /// Luz que atraviesa el material, si la deja pasar (ver `Subsurface`)
+    pub subsurface: Option<Subsurface>,
+    /// Iridiscencia de lo especular, si la tiene (ver `ThinFilm`)
+    pub thin_film: Option<ThinFilm>,
+    /// Exponentes especulares por eje en vez de `specular`, si los tiene
+    pub anisotropy: Option<Anisotropy>,
+    /// Laca sobre el material, si la tiene (ver `Clearcoat`)
+    pub clearcoat: Option<Clearcoat>,
+    /// Ancho (en unidades del mundo) del borde redondeado de cada cara; las
+    /// normales se curvan ahí para que las aristas atrapen brillos. 0 = filo
+    pub bevel: f32,
 }
 
 impl Material {
     pub fn with_texture(
-        diffuse: Vector3,
+        diffuse: Vec3,
         specular: f32,
         albedo: [f32; 4],
         refractive_index: f32,
@@ -25,31 +270,261 @@ impl Material {
             specular,
             refractive_index,
             texture_key: Some(key),
+            waves: None,
+            water_optics: None,
+            face_uv: DEFAULT_FACE_UV,
+            uv_scale: (1.0, 1.0),
+            uv_offset: (0.0, 0.0),
+            emission: 0.0,
+            flicker: None,
+            light_groups: ALL_GROUPS,
+            tint: None,
+            dispersion: None,
+            subsurface: None,
+            thin_film: None,
+            anisotropy: None,
+            clearcoat: None,
+            bevel: 0.0,
+        }
+    }
+
+    /// Material de color sólido, sin textura
+    pub fn solid(
+        diffuse: Vec3,
+        specular: f32,
+        albedo: [f32; 4],
+        refractive_index: f32,
+    ) -> Self {
+        Self {
+            diffuse,
+            albedo,
+            specular,
+            refractive_index,
+            texture_key: None,
+            waves: None,
+            water_optics: None,
+            face_uv: DEFAULT_FACE_UV,
+            uv_scale: (1.0, 1.0),
+            uv_offset: (0.0, 0.0),
+            emission: 0.0,
+            flicker: None,
+            light_groups: ALL_GROUPS,
+            tint: None,
+            dispersion: None,
+            subsurface: None,
+            thin_film: None,
+            anisotropy: None,
+            clearcoat: None,
+            bevel: 0.0,
         }
     }
 
-    /// Obtiene el color en coordenadas UV [0,1] usando el TextureManager si hay textura
-    pub fn color_at(&self, tm: &TextureManager, u: f32, v: f32) -> Color {
-        if let Some(k) = self.texture_key {
-            if let Some(tex) = tm.images.get(&k) {
-                // Convertimos UV normalizado a coordenadas de píxel
-                let tx = (u * (tex.width as f32 - 1.0)).clamp(0.0, tex.width as f32 - 1.0) as u32;
-                let ty = ((1.0 - v) * (tex.height as f32 - 1.0))
-                    .clamp(0.0, tex.height as f32 - 1.0) as u32;
-                return tm.get_pixel_color(k, tx, ty);
+    /// Cambia la orientación de la textura en una cara
+    pub fn with_face_uv(mut self, face: CubeFace, transform: UvTransform) -> Self {
+        self.face_uv[face.index()] = transform;
+        self
+    }
+
+    /// Repite la textura `scale` veces por unidad del mundo, desplazada en
+    /// `offset`
+    pub fn with_uv_tiling(mut self, scale: (f32, f32), offset: (f32, f32)) -> Self {
+        self.uv_scale = scale;
+        self.uv_offset = offset;
+        self
+    }
+
+    /// Convierte el material en agua animada
+    pub fn with_waves(mut self, waves: Waves) -> Self {
+        self.waves = Some(waves);
+        self
+    }
+
+    /// Hace que el material brille con luz propia
+    pub fn with_emission(mut self, emission: f32) -> Self {
+        self.emission = emission;
+        self
+    }
+
+    /// Pone el material en los grupos de luces `groups` (máscara de bits)
+    pub fn with_light_groups(mut self, groups: u32) -> Self {
+        self.light_groups = groups;
+        self
+    }
+
+    /// Hace que la luz propia titile (ver `Flicker`)
+    pub fn with_flicker(mut self, flicker: Flicker) -> Self {
+        self.flicker = Some(flicker);
+        self
+    }
+
+    /// `emission` en `time` para el bloque que contiene `point`, con la
+    /// animación si la hay
+    pub fn emission_at(&self, time: f32, point: Vec3) -> f32 {
+        match self.flicker {
+            Some(flicker) if self.emission > 0.0 => self.emission * flicker.factor(time, point),
+            _ => self.emission,
+        }
+    }
+
+    /// Tiñe el material según la posición (ver `Tint`)
+    pub fn with_tint(mut self, tint: Tint) -> Self {
+        self.tint = Some(Arc::new(tint));
+        self
+    }
+
+    /// Refracta cada canal con su propio índice (ver `dispersion`)
+    pub fn with_dispersion(mut self, iors: [f32; 3]) -> Self {
+        self.dispersion = Some(iors);
+        self
+    }
+
+    /// Deja pasar la luz por el material (ver `Subsurface`)
+    pub fn with_subsurface(mut self, subsurface: Subsurface) -> Self {
+        self.subsurface = Some(subsurface);
+        self
+    }
+
+    /// Tiñe lo especular con iridiscencia (ver `ThinFilm`)
+    pub fn with_thin_film(mut self, film: ThinFilm) -> Self {
+        self.thin_film = Some(film);
+        self
+    }
+
+    /// Estira el brillo especular según los ejes de la cara (ver `Anisotropy`)
+    pub fn with_anisotropy(mut self, anisotropy: Anisotropy) -> Self {
+        self.anisotropy = Some(anisotropy);
+        self
+    }
+
+    /// Cubre el material con una laca brillante (ver `Clearcoat`)
+    pub fn with_clearcoat(mut self, clearcoat: Clearcoat) -> Self {
+        self.clearcoat = Some(clearcoat);
+        self
+    }
+
+    /// Redondea las aristas con un bisel de ancho `width` (ver `bevel`)
+    pub fn with_bevel(mut self, width: f32) -> Self {
+        self.bevel = width;
+        self
+    }
+
+    /// Radiancia emitida promedio: el color medio de la textura × `emission`
+    pub fn emitted(&self, tm: &TextureManager) -> Vec3 {
+        let mut sum = Vec3::zero();
+        for i in 0..4 {
+            for j in 0..4 {
+                let c = self.texel(tm, (i as f32 + 0.5) / 4.0, (j as f32 + 0.5) / 4.0);
+                sum += Vec3::new(c.r as f32, c.g as f32, c.b as f32) / 255.0;
             }
         }
+        sum / 16.0 * self.emission
+    }
+
+    /// Sombrea el material como agua con Fresnel (ver `WaterOptics`)
+    pub fn with_water_optics(mut self, optics: WaterOptics) -> Self {
+        self.water_optics = Some(optics);
+        self
+    }
+
+    /// Color en coordenadas UV [0,1] de un punto del mundo (`point`, apenas
+    /// dentro del bloque), con el tinte por posición si el material lo tiene
+    pub fn color_at(&self, tm: &TextureManager, u: f32, v: f32, point: Vec3) -> Rgba {
+        let color = self.texel(tm, u, v);
+        match &self.tint {
+            Some(tint) => vector3_to_color(color_to_vector3(color) * tint.factor(point)),
+            None => color,
+        }
+    }
+
+    /// Obtiene el color en coordenadas UV usando el TextureManager si hay
+    /// textura; fuera de [0,1] (cajas de más de un bloque) la repite
+    fn texel(&self, tm: &TextureManager, u: f32, v: f32) -> Rgba {
+        if let Some(k) = self.texture_key
+            && let Some(tex) = tm.images.get(&k)
+        {
+            let inside = (0.0..=1.0).contains(&u) && (0.0..=1.0).contains(&v);
+            if self.uv_scale != (1.0, 1.0) || self.uv_offset != (0.0, 0.0) || !inside {
+                // Mosaico: `sample` repite las UV fuera de [0,1]
+                let u = u * self.uv_scale.0 + self.uv_offset.0;
+                let v = v * self.uv_scale.1 + self.uv_offset.1;
+                return tex.sample((u, v));
+            }
+            // Convertimos UV normalizado a coordenadas de píxel
+            let tx = (u * (tex.width as f32 - 1.0)).clamp(0.0, tex.width as f32 - 1.0) as u32;
+            let ty = ((1.0 - v) * (tex.height as f32 - 1.0))
+                .clamp(0.0, tex.height as f32 - 1.0) as u32;
+            return tm.get_pixel_color(k, tx, ty);
+        }
         // Fallback: color sólido
         vector3_to_color(self.diffuse)
     }
+
+    /// Color representativo del material: promedio de la textura o el difuso
+    pub fn base_color(&self, tm: &TextureManager) -> Rgba {
+        self.texture_key
+            .and_then(|k| tm.images.get(&k))
+            .map(|tex| tex.average_color())
+            .unwrap_or_else(|| vector3_to_color(self.diffuse))
+    }
 }
 
-/// Convierte un Vector3 (0..1) a Color RGBA
-pub fn vector3_to_color(v: Vector3) -> Color {
-    Color::new(
+/// Materiales indexados por el símbolo usado en las capas.
+/// Los símbolos no distinguen mayúsculas ('W' y 'w' son el mismo material).
+#[derive(Debug, Clone, Default)]
+pub struct MaterialLibrary {
+    materials: HashMap<char, Material>,
+}
+
+impl MaterialLibrary {
+    pub fn insert(&mut self, symbol: char, material: Material) {
+        self.materials.insert(symbol.to_ascii_uppercase(), material);
+    }
+
+    pub fn get(&self, symbol: char) -> Option<&Material> {
+        self.materials.get(&symbol.to_ascii_uppercase())
+    }
+
+    /// Itera (símbolo, material) con el símbolo en mayúscula
+    pub fn iter(&self) -> impl Iterator<Item = (char, &Material)> {
+        self.materials.iter().map(|(k, m)| (*k, m))
+    }
+}
+
+/// Convierte un Vec3 (0..1) a Color RGBA
+pub fn vector3_to_color(v: Vec3) -> Rgba {
+    Rgba::new(
         (v.x * 255.0).clamp(0.0, 255.0) as u8,
         (v.y * 255.0).clamp(0.0, 255.0) as u8,
         (v.z * 255.0).clamp(0.0, 255.0) as u8,
         255,
     )
 }
+
+/// Inversa de `vector3_to_color`: canales de 0..255 a 0..1
+pub fn color_to_vector3(c: Rgba) -> Vec3 {
+    Vec3::new(c.r as f32 / 255.0, c.g as f32 / 255.0, c.b as f32 / 255.0)
+}
+
+/// Matriz de Bayer 4×4 para el tramado ordenado
+const BAYER_4X4: [[f32; 4]; 4] = [
+    [0.0, 8.0, 2.0, 10.0],
+    [12.0, 4.0, 14.0, 6.0],
+    [3.0, 11.0, 1.0, 9.0],
+    [15.0, 7.0, 13.0, 5.0],
+];
+
+/// Como `vector3_to_color` pero con tramado ordenado según la posición del
+/// píxel: antes de truncar suma entre 1/32 y 31/32 de escalón de 8 bits
+/// (medio escalón en promedio, o sea que redondea en promedio), así los
+/// degradados suaves (el cielo) no forman bandas
+pub fn vector3_to_color_dithered(v: Vec3, x: usize, y: usize) -> Rgba {
+    let offset = (BAYER_4X4[y % 4][x % 4] + 0.5) / 16.0;
+    let channel = |c: f32| (c * 255.0 + offset).clamp(0.0, 255.0) as u8;
+    Rgba::new(channel(v.x), channel(v.y), channel(v.z), 255)
+}
+
+/// Cuantiza el píxel (x, y) con `vector3_to_color_dithered` si `dither`, si
+/// no con `vector3_to_color`; todas las salidas de 8 bits pasan por acá
+pub fn vector3_to_color_at(v: Vec3, x: usize, y: usize, dither: bool) -> Rgba {
+    if dither { vector3_to_color_dithered(v, x, y) } else { vector3_to_color(v) }
+}
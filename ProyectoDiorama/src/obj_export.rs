@@ -0,0 +1,88 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::error::Result;
+use crate::material::MaterialLibrary;
+use crate::ray_intersect::CubeFace;
+use crate::texture_manager::TextureManager;
+use crate::voxel_world::VoxelWorld;
+
+/// Exporta las caras visibles del mundo como OBJ (quads con UV y normales,
+/// agrupados por material) junto a un MTL con el mismo nombre base
+pub fn export_obj(
+    path: &str,
+    world: &VoxelWorld,
+    materials: &MaterialLibrary,
+    tm: &TextureManager,
+) -> Result<()> {
+    let mtl_path = Path::new(path).with_extension("mtl");
+    let mtl_name = mtl_path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "diorama.mtl".to_string());
+
+    let mut faces = world.exposed_faces();
+    faces.sort_by_key(|f| f.symbol);
+
+    let mut obj = BufWriter::new(File::create(path)?);
+    writeln!(obj, "# Diorama exportado ({} caras)", faces.len())?;
+    writeln!(obj, "mtllib {}", mtl_name)?;
+
+    // UV y normales compartidas por todas las caras
+    for (u, v) in [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)] {
+        writeln!(obj, "vt {} {}", u, v)?;
+    }
+    for face in CubeFace::ALL {
+        let n = face.normal();
+        writeln!(obj, "vn {} {} {}", n.x, n.y, n.z)?;
+    }
+
+    let mut current = None;
+    for (i, f) in faces.iter().enumerate() {
+        if current != Some(f.symbol) {
+            writeln!(obj, "usemtl mat_{}", f.symbol)?;
+            current = Some(f.symbol);
+        }
+
+        let (x, y, z) = f.position;
+        for c in f.face.corners() {
+            writeln!(obj, "v {} {} {}", x as f32 + c.x, y as f32 + c.y, z as f32 + c.z)?;
+        }
+
+        let normal_index = CubeFace::ALL
+            .iter()
+            .position(|&other| other == f.face)
+            .unwrap_or(0)
+            + 1;
+        let base = i * 4 + 1;
+        writeln!(
+            obj,
+            "f {}/1/{n} {}/2/{n} {}/3/{n} {}/4/{n}",
+            base,
+            base + 1,
+            base + 2,
+            base + 3,
+            n = normal_index
+        )?;
+    }
+    obj.flush()?;
+
+    let mut mtl = BufWriter::new(File::create(&mtl_path)?);
+    let mut symbols: Vec<char> = materials.iter().map(|(s, _)| s).collect();
+    symbols.sort();
+    for symbol in symbols {
+        let Some(mat) = materials.get(symbol) else { continue };
+        writeln!(mtl, "newmtl mat_{}", symbol)?;
+        writeln!(mtl, "Kd {} {} {}", mat.diffuse.x, mat.diffuse.y, mat.diffuse.z)?;
+        writeln!(mtl, "Ks {0} {0} {0}", mat.albedo[1])?;
+        writeln!(mtl, "Ns {}", mat.specular)?;
+        if let Some(tex) = mat.texture_key.and_then(|k| tm.images.get(&k)) {
+            writeln!(mtl, "map_Kd {}", tex.path)?;
+        }
+        writeln!(mtl)?;
+    }
+    mtl.flush()?;
+
+    Ok(())
+}
@@ -0,0 +1,80 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+
+const EMPTY: u32 = u32::MAX;
+
+/// Último oclusor opaco que encontró un rayo de sombra en cada píxel. Entre
+/// cuadros la vista casi no cambia, así que ese objeto suele volver a tapar
+/// la luz: probarlo primero evita recorrer la escena en la mayoría de los
+/// puntos en sombra.
+///
+/// Las casillas cubren la pantalla en coordenadas normalizadas: un render a
+/// otra resolución (GIF, vista reducida o foveada) usa la casilla de la
+/// misma zona de la pantalla en vez de la de otro píxel cualquiera.
+pub struct OccluderCache {
+    width: usize,
+    height: usize,
+    slots: Vec<AtomicU32>,
+}
+
+impl OccluderCache {
+    /// Caché de `width`×`height` casillas (lo normal: una por píxel de la
+    /// resolución principal)
+    pub fn new(width: usize, height: usize) -> Self {
+        OccluderCache {
+            width,
+            height,
+            slots: (0..width * height).map(|_| AtomicU32::new(EMPTY)).collect(),
+        }
+    }
+
+    /// Casilla del píxel (x, y) de una imagen de `frame` = (ancho, alto)
+    fn slot(&self, x: usize, y: usize, frame: (usize, usize)) -> Option<&AtomicU32> {
+        let (frame_width, frame_height) = frame;
+        if frame_width == 0 || frame_height == 0 {
+            return None;
+        }
+        let cx = x * self.width / frame_width;
+        let cy = y * self.height / frame_height;
+        if cx >= self.width || cy >= self.height {
+            return None;
+        }
+        self.slots.get(cy * self.width + cx)
+    }
+
+    /// Índice en `Scene::objects` del último oclusor del píxel, si hay
+    pub fn get(&self, x: usize, y: usize, frame: (usize, usize)) -> Option<usize> {
+        let index = self.slot(x, y, frame)?.load(Ordering::Relaxed);
+        (index != EMPTY).then_some(index as usize)
+    }
+
+    pub fn set(&self, x: usize, y: usize, frame: (usize, usize), index: usize) {
+        if let Some(slot) = self.slot(x, y, frame) {
+            slot.store(index as u32, Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn other_resolutions_share_the_same_screen_region() {
+        let cache = OccluderCache::new(4, 4);
+        cache.set(2, 2, (4, 4), 7);
+        // El mismo punto de la pantalla a media resolución
+        assert_eq!(cache.get(1, 1, (2, 2)), Some(7));
+        // Otra zona de la pantalla no lo ve
+        assert_eq!(cache.get(0, 0, (2, 2)), None);
+        // Y a doble resolución cae en la misma casilla
+        assert_eq!(cache.get(5, 5, (8, 8)), Some(7));
+    }
+
+    #[test]
+    fn empty_frame_has_no_slot() {
+        let cache = OccluderCache::new(4, 4);
+        cache.set(0, 0, (0, 0), 3);
+        assert_eq!(cache.get(0, 0, (0, 0)), None);
+        assert_eq!(cache.get(0, 0, (4, 4)), None);
+    }
+}
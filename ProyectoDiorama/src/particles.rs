@@ -0,0 +1,143 @@
+use crate::math::Vec3;
+
+use crate::geometry;
+use crate::rng::Rng;
+
+/// Emisor de partículas (humo, fuego). No guarda estado: la partícula `i`
+/// nace en `i / spawn_rate` segundos y su posición en cualquier instante se
+/// deriva de `i`, así que cada rayo puede evaluar el emisor en `scene.time`
+/// y la animación es reproducible cuadro a cuadro.
+#[derive(Debug, Clone)]
+pub struct ParticleEmitter {
+    pub position: Vec3,
+    /// Partículas por segundo
+    pub spawn_rate: f32,
+    /// Segundos que vive cada partícula
+    pub lifetime: f32,
+    /// Velocidad media de salida
+    pub velocity: Vec3,
+    /// Variación aleatoria de la velocidad por eje
+    pub spread: f32,
+    /// Radio del billboard al nacer; crece al doble al morir
+    pub size: f32,
+    /// Color (rgb) y opacidad a lo largo de la vida, de nacimiento a muerte
+    pub ramp: Vec<(Vec3, f32)>,
+    pub seed: u64,
+}
+
+/// Impacto de un rayo con una partícula
+#[derive(Debug, Clone, Copy)]
+pub struct ParticleHit {
+    pub distance: f32,
+    pub color: Vec3,
+    pub opacity: f32,
+}
+
+impl ParticleEmitter {
+    /// Fogata: chispas amarillas que pasan a naranja y rojo, casi opacas
+    pub fn fire(position: Vec3) -> Self {
+        ParticleEmitter {
+            position,
+            spawn_rate: 40.0,
+            lifetime: 0.8,
+            velocity: Vec3::new(0.0, 1.6, 0.0),
+            spread: 0.35,
+            size: 0.12,
+            ramp: vec![
+                (Vec3::new(1.0, 0.95, 0.5), 1.0),
+                (Vec3::new(1.0, 0.55, 0.1), 0.9),
+                (Vec3::new(0.7, 0.15, 0.05), 0.5),
+            ],
+            seed: 1,
+        }
+    }
+
+    /// Chimenea: humo gris que sube lento y se desvanece
+    pub fn smoke(position: Vec3) -> Self {
+        ParticleEmitter {
+            position,
+            spawn_rate: 12.0,
+            lifetime: 3.0,
+            velocity: Vec3::new(0.15, 1.0, 0.0),
+            spread: 0.2,
+            size: 0.2,
+            ramp: vec![
+                (Vec3::new(0.35, 0.35, 0.35), 0.7),
+                (Vec3::new(0.6, 0.6, 0.6), 0.4),
+                (Vec3::new(0.8, 0.8, 0.8), 0.0),
+            ],
+            seed: 2,
+        }
+    }
+
+    /// Emisor predefinido por nombre (`fire`, `smoke`)
+    pub fn preset(name: &str, position: Vec3) -> Option<Self> {
+        match name {
+            "fire" => Some(Self::fire(position)),
+            "smoke" => Some(Self::smoke(position)),
+            _ => None,
+        }
+    }
+
+    fn ramp_at(&self, t: f32) -> (Vec3, f32) {
+        match self.ramp.len() {
+            0 => (Vec3::one(), 1.0),
+            1 => self.ramp[0],
+            n => {
+                let x = t.clamp(0.0, 1.0) * (n - 1) as f32;
+                let i = (x as usize).min(n - 2);
+                let k = x - i as f32;
+                let (c0, a0) = self.ramp[i];
+                let (c1, a1) = self.ramp[i + 1];
+                (c0 + (c1 - c0) * k, a0 + (a1 - a0) * k)
+            }
+        }
+    }
+
+    /// Partícula viva más cercana que cruza el rayo antes de `max_distance`.
+    /// Cada una es un disco que siempre mira al rayo (billboard).
+    pub fn hit(&self, origin: &Vec3, direction: &Vec3, time: f32, max_distance: f32) -> Option<ParticleHit> {
+        // Descarte rápido con una esfera que contiene toda la columna
+        let reach = self.velocity.length() + self.spread * 1.8;
+        let bound_center = self.position + self.velocity * (self.lifetime * 0.5);
+        let bound_radius = reach * self.lifetime + self.size * 2.0;
+        geometry::ray_sphere(origin, direction, bound_center, bound_radius)?;
+
+        let first = ((time - self.lifetime) * self.spawn_rate).ceil() as i64;
+        let last = (time * self.spawn_rate).floor() as i64;
+        let mut best: Option<ParticleHit> = None;
+
+        for i in first..=last {
+            let age = time - i as f32 / self.spawn_rate;
+            if !(0.0..self.lifetime).contains(&age) {
+                continue;
+            }
+            let mut rng = Rng::new(self.seed ^ (i as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+            let jitter = Vec3::new(
+                rng.next_f32() * 2.0 - 1.0,
+                rng.next_f32() * 2.0 - 1.0,
+                rng.next_f32() * 2.0 - 1.0,
+            ) * self.spread;
+            let center = self.position + (self.velocity + jitter) * age;
+            let life = age / self.lifetime;
+            let radius = self.size * (1.0 + life);
+
+            let t = (center - *origin).dot(*direction);
+            let limit = best.map_or(max_distance, |b| b.distance);
+            if t <= 0.0 || t >= limit {
+                continue;
+            }
+            let closest = *origin + *direction * t;
+            if (closest - center).length() > radius {
+                continue;
+            }
+
+            let (color, opacity) = self.ramp_at(life);
+            if opacity > 0.0 {
+                best = Some(ParticleHit { distance: t, color, opacity });
+            }
+        }
+
+        best
+    }
+}
@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+use std::f32::consts::PI;
+use crate::math::Vec3;
+
+use crate::rng::Rng;
+use crate::scene::Scene;
+use crate::geometry::{reflect, refract};
+use crate::{nearest_hit, offset_origin};
+
+const MAX_BOUNCES: u32 = 6;
+
+/// Fotón depositado sobre una superficie difusa
+#[derive(Debug, Clone, Copy)]
+struct Photon {
+    position: Vec3,
+    /// Dirección de llegada (hacia la superficie)
+    direction: Vec3,
+    power: Vec3,
+}
+
+/// Mapa de fotones de cáusticas: solo guarda fotones que pasaron por al menos
+/// una superficie reflectiva o refractiva antes de caer en una difusa.
+/// Los fotones se indexan en una grilla hash con celdas del tamaño del radio
+/// de búsqueda.
+pub struct PhotonMap {
+    radius: f32,
+    grid: HashMap<(i32, i32, i32), Vec<Photon>>,
+    stored: usize,
+}
+
+/// Parámetros del pre-pase de fotones
+#[derive(Debug, Clone, Copy)]
+pub struct PhotonSettings {
+    pub count: usize,
+    /// Radio de recolección alrededor del punto sombreado
+    pub radius: f32,
+    /// Esfera que envuelve la escena; los fotones se emiten solo hacia ella
+    pub target_center: Vec3,
+    pub target_radius: f32,
+    pub seed: u64,
+}
+
+impl PhotonMap {
+    fn cell(&self, p: Vec3) -> (i32, i32, i32) {
+        (
+            (p.x / self.radius).floor() as i32,
+            (p.y / self.radius).floor() as i32,
+            (p.z / self.radius).floor() as i32,
+        )
+    }
+
+    /// Cantidad de fotones guardados
+    pub fn photon_count(&self) -> usize {
+        self.stored
+    }
+
+    /// Traza fotones desde la luz de la escena a través de objetos
+    /// reflectivos/transparentes y guarda donde aterrizan
+    pub fn build(scene: &Scene, settings: &PhotonSettings) -> Self {
+        let mut map = PhotonMap {
+            radius: settings.radius.max(1e-3),
+            grid: HashMap::new(),
+            stored: 0,
+        };
+        let light = scene.light;
+        let mut rng = Rng::new(settings.seed);
+
+        // Cono desde la luz que cubre la esfera objetivo
+        let to_target = settings.target_center - light.position;
+        let distance = to_target.length().max(1e-3);
+        let axis = to_target / distance;
+        let cos_max = if settings.target_radius >= distance {
+            -1.0
+        } else {
+            (1.0 - (settings.target_radius / distance).powi(2)).sqrt()
+        };
+        let cone_fraction = (1.0 - cos_max) * 0.5;
+
+        // Flujo tal que la irradiancia a la distancia de la escena coincida
+        // con la intensidad que usa el sombreado directo (sin atenuación)
+        let light_color = Vec3::new(
+            light.color.r as f32 / 255.0,
+            light.color.g as f32 / 255.0,
+            light.color.b as f32 / 255.0,
+        );
+        let flux = light_color * (light.intensity * 4.0 * PI * distance * distance);
+        let photon_power = flux * (cone_fraction / settings.count.max(1) as f32);
+
+        for _ in 0..settings.count {
+            let mut origin = light.position;
+            let mut direction = rng.in_cone(axis, cos_max);
+            let mut power = photon_power;
+            let mut specular_path = false;
+            // Canal que lleva el fotón desde que cruzó un vidrio con
+            // dispersión (longitud de onda al azar)
+            let mut channel: Option<usize> = None;
+
+            for _ in 0..MAX_BOUNCES {
+                let hit = nearest_hit(&origin, &direction, scene);
+                if !hit.is_intersecting {
+                    break;
+                }
+
+                let reflectivity = hit.material.albedo[2].clamp(0.0, 1.0);
+                let transparency = hit.material.albedo[3].clamp(0.0, 1.0 - reflectivity);
+                let xi = rng.next_f32();
+
+                if xi < transparency {
+                    // Refracción, teñida por el color difuso del material
+                    let ior = match hit.material.dispersion {
+                        Some(iors) => {
+                            let c = match channel {
+                                Some(c) => c,
+                                None => {
+                                    let c = ((rng.next_f32() * 3.0) as usize).min(2);
+                                    let mut mask = [0.0; 3];
+                                    mask[c] = 3.0;
+                                    power *= Vec3::new(mask[0], mask[1], mask[2]);
+                                    channel = Some(c);
+                                    c
+                                }
+                            };
+                            iors[c]
+                        }
+                        None => hit.material.refractive_index,
+                    };
+                    let Some(dir) = refract(&direction, &hit.normal, ior) else {
+                        break;
+                    };
+                    direction = dir.normalized();
+                    power *= hit.material.diffuse;
+                } else if xi < transparency + reflectivity {
+                    direction = reflect(&direction, &hit.normal).normalized();
+                } else {
+                    if specular_path {
+                        let photon = Photon { position: hit.point, direction, power };
+                        let cell = map.cell(hit.point);
+                        map.grid.entry(cell).or_default().push(photon);
+                        map.stored += 1;
+                    }
+                    break;
+                }
+
+                specular_path = true;
+                origin = offset_origin(&hit, &direction, scene.bias);
+            }
+        }
+
+        map
+    }
+
+    /// Irradiancia de cáusticas en `point` con normal `normal`
+    pub fn irradiance(&self, point: Vec3, normal: Vec3) -> Vec3 {
+        let (cx, cy, cz) = self.cell(point);
+        let r2 = self.radius * self.radius;
+        let mut sum = Vec3::zero();
+
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                for dz in -1..=1 {
+                    let Some(photons) = self.grid.get(&(cx + dx, cy + dy, cz + dz)) else {
+                        continue;
+                    };
+                    for p in photons {
+                        let d = p.position - point;
+                        if d.dot(d) <= r2 && p.direction.dot(normal) < 0.0 {
+                            sum += p.power;
+                        }
+                    }
+                }
+            }
+        }
+
+        sum / (PI * r2)
+    }
+}
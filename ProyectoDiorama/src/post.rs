@@ -0,0 +1,161 @@
+use crate::error::{Error, Result};
+use crate::math::Vec3;
+use crate::rng::Rng;
+
+/// LUT 3D de gradación de color, en formato `.cube` (Resolve/Adobe)
+#[derive(Debug, Clone)]
+pub struct Lut {
+    pub size: usize,
+    /// `size³` colores con el rojo variando más rápido
+    pub table: Vec<Vec3>,
+}
+
+impl Lut {
+    pub fn load(path: &str) -> Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        Self::parse(&text, path)
+    }
+
+    pub fn parse(text: &str, path: &str) -> Result<Self> {
+        let mut size = 0;
+        let mut table = Vec::new();
+        for (i, raw) in text.lines().enumerate() {
+            let line = raw.split('#').next().unwrap_or("").trim();
+            let err = |msg: String| Error::Parse { path: path.to_string(), line: i + 1, msg };
+            let mut words = line.split_whitespace();
+            match words.next() {
+                None | Some("TITLE") | Some("DOMAIN_MIN") | Some("DOMAIN_MAX") => {}
+                Some("LUT_3D_SIZE") => {
+                    let value = words.next().unwrap_or("");
+                    size = value
+                        .parse()
+                        .ok()
+                        .filter(|s| *s >= 2)
+                        .ok_or_else(|| err(format!("tamaño de LUT inválido: {}", value)))?;
+                }
+                Some(first) => {
+                    let values: Vec<f32> = std::iter::once(first)
+                        .chain(words)
+                        .map(|s| s.parse::<f32>().map_err(|_| err(format!("número inválido: {}", s))))
+                        .collect::<Result<_>>()?;
+                    let [r, g, b] = values[..] else {
+                        return Err(err("se esperan tres valores r g b".to_string()));
+                    };
+                    table.push(Vec3::new(r, g, b));
+                }
+            }
+        }
+
+        if size == 0 || table.len() != size * size * size {
+            return Err(Error::Parse {
+                path: path.to_string(),
+                line: 0,
+                msg: format!("LUT_3D_SIZE {} pero hay {} colores", size, table.len()),
+            });
+        }
+        Ok(Lut { size, table })
+    }
+
+    /// Color gradado con interpolación trilineal; la entrada se recorta a [0, 1]
+    pub fn apply(&self, c: Vec3) -> Vec3 {
+        let n = self.size - 1;
+        let coord = |v: f32| {
+            let x = v.clamp(0.0, 1.0) * n as f32;
+            let i = (x as usize).min(n - 1);
+            (i, x - i as f32)
+        };
+        let (r, fr) = coord(c.x);
+        let (g, fg) = coord(c.y);
+        let (b, fb) = coord(c.z);
+        let at = |r: usize, g: usize, b: usize| self.table[r + self.size * (g + self.size * b)];
+        let lerp = |a: Vec3, b: Vec3, t: f32| a + (b - a) * t;
+
+        let c00 = lerp(at(r, g, b), at(r + 1, g, b), fr);
+        let c10 = lerp(at(r, g + 1, b), at(r + 1, g + 1, b), fr);
+        let c01 = lerp(at(r, g, b + 1), at(r + 1, g, b + 1), fr);
+        let c11 = lerp(at(r, g + 1, b + 1), at(r + 1, g + 1, b + 1), fr);
+        lerp(lerp(c00, c10, fg), lerp(c01, c11, fg), fb)
+    }
+}
+
+/// Efecto de post-proceso sobre el buffer lineal
+#[derive(Debug, Clone)]
+pub enum Effect {
+    /// Oscurece las esquinas; `strength` es cuánto pierden (0..1)
+    Vignette { strength: f32 },
+    /// Separa rojo y azul hacia afuera/adentro; `pixels` en las esquinas
+    ChromaticAberration { pixels: f32 },
+    /// Ruido de luminancia que cambia cada cuadro
+    FilmGrain { amount: f32 },
+    /// Gradación de color con una LUT 3D
+    Lut(Lut),
+}
+
+/// Cadena de efectos aplicada en orden sobre el buffer lineal, antes de
+/// mostrarlo o exportarlo
+#[derive(Debug, Clone, Default)]
+pub struct PostStack {
+    pub effects: Vec<Effect>,
+}
+
+impl PostStack {
+    pub fn is_empty(&self) -> bool {
+        self.effects.is_empty()
+    }
+
+    /// Aplica todos los efectos a `pixels` (por filas); `time` anima el grano
+    pub fn apply(&self, pixels: &mut [Vec3], width: usize, height: usize, time: f32) {
+        for effect in &self.effects {
+            match effect {
+                Effect::Vignette { strength } => vignette(pixels, width, height, *strength),
+                Effect::ChromaticAberration { pixels: amount } => {
+                    chromatic_aberration(pixels, width, height, *amount)
+                }
+                Effect::FilmGrain { amount } => film_grain(pixels, *amount, time),
+                Effect::Lut(lut) => pixels.iter_mut().for_each(|c| *c = lut.apply(*c)),
+            }
+        }
+    }
+}
+
+/// Posición del píxel relativa al centro, con las esquinas a distancia 1
+fn from_center(idx: usize, width: usize, height: usize) -> (f32, f32) {
+    let (half_w, half_h) = (width as f32 * 0.5, height as f32 * 0.5);
+    let diagonal = (half_w * half_w + half_h * half_h).sqrt();
+    let x = (idx % width) as f32 + 0.5 - half_w;
+    let y = (idx / width) as f32 + 0.5 - half_h;
+    (x / diagonal, y / diagonal)
+}
+
+fn vignette(pixels: &mut [Vec3], width: usize, height: usize, strength: f32) {
+    for (idx, c) in pixels.iter_mut().enumerate() {
+        let (x, y) = from_center(idx, width, height);
+        *c *= (1.0 - strength * (x * x + y * y)).max(0.0);
+    }
+}
+
+fn chromatic_aberration(pixels: &mut [Vec3], width: usize, height: usize, amount: f32) {
+    let source = pixels.to_vec();
+    let sample = |x: f32, y: f32| {
+        let x = (x.round().max(0.0) as usize).min(width - 1);
+        let y = (y.round().max(0.0) as usize).min(height - 1);
+        source[y * width + x]
+    };
+    for (idx, c) in pixels.iter_mut().enumerate() {
+        // El desplazamiento crece hacia los bordes, como en un lente real
+        let (dx, dy) = from_center(idx, width, height);
+        let (x, y) = (dx * amount, dy * amount);
+        let px = (idx % width) as f32;
+        let py = (idx / width) as f32;
+        c.x = sample(px + x, py + y).x;
+        c.z = sample(px - x, py - y).z;
+    }
+}
+
+fn film_grain(pixels: &mut [Vec3], amount: f32, time: f32) {
+    let mut rng = Rng::new(time.to_bits() as u64 ^ 0x5EED);
+    for c in pixels.iter_mut() {
+        let noise = (rng.next_f32() - 0.5) * amount;
+        *c += Vec3::new(noise, noise, noise);
+    }
+}
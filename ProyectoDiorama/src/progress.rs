@@ -0,0 +1,81 @@
+use std::io::Write;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::Instant;
+
+use crate::error::Result;
+
+const BAR_WIDTH: usize = 30;
+
+/// Barra de progreso con ETA para renders offline (una unidad = una scanline)
+pub struct Progress {
+    total: usize,
+    done: AtomicUsize,
+    last_percent: AtomicUsize,
+    start: Instant,
+}
+
+impl Progress {
+    pub fn new(total: usize) -> Self {
+        Progress {
+            total: total.max(1),
+            done: AtomicUsize::new(0),
+            last_percent: AtomicUsize::new(usize::MAX),
+            start: Instant::now(),
+        }
+    }
+
+    /// Marca una unidad como terminada; solo imprime cuando cambia el porcentaje
+    pub fn tick(&self) {
+        let done = self.done.fetch_add(1, Ordering::Relaxed) + 1;
+        let percent = done * 100 / self.total;
+        if self.last_percent.swap(percent, Ordering::Relaxed) != percent {
+            self.print(done, percent);
+        }
+    }
+
+    fn print(&self, done: usize, percent: usize) {
+        let elapsed = self.start.elapsed().as_secs_f64();
+        let eta = elapsed * (self.total - done.min(self.total)) as f64 / done as f64;
+        let filled = BAR_WIDTH * percent / 100;
+
+        let mut err = std::io::stderr().lock();
+        let _ = write!(
+            err,
+            "\r[{}{}] {:3}% ETA {}",
+            "#".repeat(filled),
+            " ".repeat(BAR_WIDTH - filled),
+            percent,
+            format_duration(eta),
+        );
+        let _ = err.flush();
+    }
+
+    /// Cierra la línea de progreso e imprime el tiempo total
+    pub fn finish(&self) {
+        eprintln!(
+            "\nTerminado en {}",
+            format_duration(self.start.elapsed().as_secs_f64())
+        );
+    }
+}
+
+/// Formatea segundos como `mm:ss` o `hh:mm:ss`
+fn format_duration(secs: f64) -> String {
+    let secs = secs.round() as u64;
+    let (h, m, s) = (secs / 3600, (secs / 60) % 60, secs % 60);
+    if h > 0 {
+        format!("{:02}:{:02}:{:02}", h, m, s)
+    } else {
+        format!("{:02}:{:02}", m, s)
+    }
+}
+
+/// Instala un manejador de Ctrl+C que solo levanta una bandera, para que el
+/// render offline pueda detenerse y guardar la imagen parcial.
+pub fn interrupt_flag() -> Result<Arc<AtomicBool>> {
+    let flag = Arc::new(AtomicBool::new(false));
+    let handler_flag = Arc::clone(&flag);
+    ctrlc::set_handler(move || handler_flag.store(true, Ordering::SeqCst))?;
+    Ok(flag)
+}
@@ -0,0 +1,127 @@
+use rayon::prelude::*;
+use std::time::{Duration, Instant};
+
+use crate::camera::Camera;
+use crate::material::vector3_to_color_at;
+use crate::math::{Rgba, Vec3};
+use crate::scene::Scene;
+use crate::{apply_post, trace_pixel};
+
+/// Rectángulo de la imagen que se traza de una vez
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tile {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+/// Mosaicos de `size`×`size` que cubren la imagen, ordenados en espiral
+/// desde el centro hacia afuera: primero por anillo (distancia de Chebyshev
+/// en mosaicos al centro) y dentro de cada anillo por ángulo
+pub fn spiral_tiles(width: usize, height: usize, size: usize) -> Vec<Tile> {
+    let size = size.max(1);
+    let (cols, rows) = (width.div_ceil(size), height.div_ceil(size));
+    let center = (cols as f32 * 0.5 - 0.5, rows as f32 * 0.5 - 0.5);
+
+    let mut tiles: Vec<(f32, f32, Tile)> = Vec::with_capacity(cols * rows);
+    for row in 0..rows {
+        for col in 0..cols {
+            let (dx, dy) = (col as f32 - center.0, row as f32 - center.1);
+            let ring = dx.abs().max(dy.abs()).round();
+            let tile = Tile {
+                x: col * size,
+                y: row * size,
+                width: size.min(width - col * size),
+                height: size.min(height - row * size),
+            };
+            tiles.push((ring, dy.atan2(dx), tile));
+        }
+    }
+    tiles.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1)));
+    tiles.into_iter().map(|(_, _, tile)| tile).collect()
+}
+
+/// Render progresivo para la vista interactiva: cada cuadro traza los
+/// mosaicos que entren en el presupuesto de tiempo, empezando por el centro
+/// de la pantalla, así el motivo del diorama se define primero. Lo que aún
+/// no se volvió a trazar conserva la imagen anterior.
+pub struct Progressive {
+    width: usize,
+    height: usize,
+    tiles: Vec<Tile>,
+    next: usize,
+    pixels: Vec<Vec3>,
+}
+
+impl Progressive {
+    pub fn new(width: usize, height: usize, tile_size: usize) -> Self {
+        Progressive {
+            width,
+            height,
+            tiles: spiral_tiles(width, height, tile_size),
+            next: 0,
+            pixels: vec![Vec3::zero(); width * height],
+        }
+    }
+
+    /// Vuelve a empezar desde el mosaico central (la cámara o la escena
+    /// cambiaron)
+    pub fn restart(&mut self) {
+        self.next = 0;
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.next >= self.tiles.len()
+    }
+
+    /// Fracción de mosaicos trazados desde el último `restart`
+    pub fn progress(&self) -> f32 {
+        self.next as f32 / self.tiles.len().max(1) as f32
+    }
+
+    /// Traza tandas de mosaicos en paralelo hasta agotar `budget`
+    pub fn step(&mut self, camera: &Camera, scene: &Scene, budget: Duration) {
+        let start = Instant::now();
+        let batch = rayon::current_num_threads().max(1);
+        while !self.is_done() && start.elapsed() < budget {
+            let end = (self.next + batch).min(self.tiles.len());
+            let (width, height) = (self.width, self.height);
+            let traced: Vec<(Tile, Vec<Vec3>)> = self.tiles[self.next..end]
+                .par_iter()
+                .map(|&tile| {
+                    let colors = (0..tile.width * tile.height)
+                        .map(|i| {
+                            let (x, y) = (tile.x + i % tile.width, tile.y + i / tile.width);
+                            trace_pixel(x, y, width, height, camera, scene)
+                        })
+                        .collect();
+                    (tile, colors)
+                })
+                .collect();
+            for (tile, colors) in traced {
+                for (i, color) in colors.into_iter().enumerate() {
+                    let (x, y) = (tile.x + i % tile.width, tile.y + i / tile.width);
+                    self.pixels[y * width + x] = color;
+                }
+            }
+            self.next = end;
+        }
+    }
+
+    /// Imagen actual en color lineal, con el post-proceso de la escena
+    pub fn linear(&self, camera: &Camera, scene: &Scene) -> Vec<Vec3> {
+        let mut pixels = self.pixels.clone();
+        apply_post(&mut pixels, self.width, self.height, camera, scene);
+        pixels
+    }
+
+    /// Como `linear`, cuantizada a 8 bits
+    pub fn image(&self, camera: &Camera, scene: &Scene) -> Vec<Rgba> {
+        self.linear(camera, scene)
+            .into_iter()
+            .enumerate()
+            .map(|(idx, c)| vector3_to_color_at(c, idx % self.width, idx / self.width, scene.dither))
+            .collect()
+    }
+}
@@ -1,19 +1,21 @@
-use raylib::prelude::Vector3;
+use crate::geometry::Aabb;
+use crate::math::Vec3;
 use crate::material::Material;
 
 #[derive(Debug, Clone)]
 pub struct Intersect {
-    pub point: Vector3,
-    pub normal: Vector3,
+    pub point: Vec3,
+    pub normal: Vec3,
     pub distance: f32,
     pub is_intersecting: bool,
     pub material: Material,
     pub u: f32,
     pub v: f32,
     pub face: CubeFace,   // which face of the cube was hit
+    pub ao: f32,          // oclusión ambiental horneada (1.0 = sin oclusión)
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum CubeFace {
     Front,
     Back,
@@ -23,11 +25,97 @@ pub enum CubeFace {
     Bottom,
 }
 
+impl CubeFace {
+    pub const ALL: [CubeFace; 6] = [
+        CubeFace::Front,
+        CubeFace::Back,
+        CubeFace::Left,
+        CubeFace::Right,
+        CubeFace::Top,
+        CubeFace::Bottom,
+    ];
+
+    /// Cara por nombre en minúsculas, como en los archivos de escena
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "front" => Some(CubeFace::Front),
+            "back" => Some(CubeFace::Back),
+            "left" => Some(CubeFace::Left),
+            "right" => Some(CubeFace::Right),
+            "top" => Some(CubeFace::Top),
+            "bottom" => Some(CubeFace::Bottom),
+            _ => None,
+        }
+    }
+
+    /// Offset entero hacia la celda vecina que tapa esta cara
+    pub fn offset(&self) -> (i32, i32, i32) {
+        match self {
+            CubeFace::Front => (0, 0, 1),
+            CubeFace::Back => (0, 0, -1),
+            CubeFace::Left => (-1, 0, 0),
+            CubeFace::Right => (1, 0, 0),
+            CubeFace::Top => (0, 1, 0),
+            CubeFace::Bottom => (0, -1, 0),
+        }
+    }
+
+    /// Posición en `CubeFace::ALL`
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+
+    /// Ejes enteros a lo largo de los que crecen `u` y `v` en `Cube::ray_intersect`
+    pub fn uv_axes(&self) -> ((i32, i32, i32), (i32, i32, i32)) {
+        match self {
+            CubeFace::Left | CubeFace::Right => ((0, 0, 1), (0, 1, 0)),
+            CubeFace::Top | CubeFace::Bottom => ((1, 0, 0), (0, 0, 1)),
+            CubeFace::Front | CubeFace::Back => ((1, 0, 0), (0, 1, 0)),
+        }
+    }
+
+    /// Tangente y bitangente de la cara (a lo largo de `u` y `v`), ajustadas
+    /// para quedar perpendiculares a `normal` (la de sombreado puede estar
+    /// girada u ondulada)
+    pub fn tangent_frame(&self, normal: Vec3) -> (Vec3, Vec3) {
+        let ((x, y, z), _) = self.uv_axes();
+        let u = Vec3::new(x as f32, y as f32, z as f32);
+        let tangent = u - normal * normal.dot(u);
+        if tangent.length() < 1e-4 {
+            return crate::rng::orthonormal_basis(normal);
+        }
+        let tangent = tangent.normalized();
+        (tangent, normal.cross(tangent))
+    }
+
+    pub fn normal(&self) -> Vec3 {
+        let (x, y, z) = self.offset();
+        Vec3::new(x as f32, y as f32, z as f32)
+    }
+
+    /// Esquinas de la cara de un cubo unitario centrado en el origen, en orden
+    /// antihorario visto desde afuera; corresponden a UV (0,0), (1,0), (1,1), (0,1)
+    pub fn corners(&self) -> [Vec3; 4] {
+        // (u, v) tales que u × v = normal
+        let (u, v) = match self {
+            CubeFace::Front => (Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0)),
+            CubeFace::Back => (Vec3::new(-1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0)),
+            CubeFace::Left => (Vec3::new(0.0, 0.0, 1.0), Vec3::new(0.0, 1.0, 0.0)),
+            CubeFace::Right => (Vec3::new(0.0, 0.0, -1.0), Vec3::new(0.0, 1.0, 0.0)),
+            CubeFace::Top => (Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 0.0, -1.0)),
+            CubeFace::Bottom => (Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 1.0)),
+        };
+        let c = self.normal() * 0.5;
+        let (u, v) = (u * 0.5, v * 0.5);
+        [c - u - v, c + u - v, c + u + v, c - u + v]
+    }
+}
+
 impl Intersect {
     /// Create a filled intersection record
     pub fn new(
-        point: Vector3,
-        normal: Vector3,
+        point: Vec3,
+        normal: Vec3,
         distance: f32,
         material: Material,
         u: f32,
@@ -43,18 +131,19 @@ impl Intersect {
             u,
             v,
             face,
+            ao: 1.0,
         }
     }
 
     /// Empty intersection (no hit)
     pub fn empty() -> Self {
         Intersect {
-            point: Vector3::zero(),
-            normal: Vector3::zero(),
+            point: Vec3::zero(),
+            normal: Vec3::zero(),
             distance: 0.0,
             is_intersecting: false,
             material: Material::with_texture(
-                Vector3::zero(), // diffuse color (black)
+                Vec3::zero(), // diffuse color (black)
                 0.0,             // specular
                 [0.0, 0.0, 0.0, 0.0], // albedo
                 1.0,             // refractive index
@@ -63,10 +152,59 @@ impl Intersect {
             u: 0.0,
             v: 0.0,
             face: CubeFace::Front, // default placeholder
+            ao: 1.0,
         }
     }
+
+    /// Hit point nudged just inside the surface, so it falls in the block
+    /// that was hit (used for per-block material variation)
+    pub fn inner_point(&self) -> Vec3 {
+        self.point - self.normal * 1e-3
+    }
+}
+
+/// Respuesta de un objeto a un rayo de sombra
+#[derive(Debug, Clone)]
+pub enum Occlusion {
+    /// No hay nada antes de la luz
+    Clear,
+    /// Un bloque opaco tapa la luz
+    Opaque,
+    /// El impacto más cercano es transparente: tiñe la luz que pasa
+    Tinted(Box<Intersect>),
 }
 
 pub trait RayIntersect: Sync {
-    fn ray_intersect(&self, ray_origin: &Vector3, ray_direction: &Vector3) -> Intersect;
+    fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Intersect;
+
+    /// Prueba de sombra hasta `max_t`. Por defecto usa `ray_intersect`; las
+    /// primitivas pueden responder sin armar el `Intersect` completo y los
+    /// contenedores cortar en cuanto encuentran algo opaco
+    fn occlusion(&self, ray_origin: &Vec3, ray_direction: &Vec3, max_t: f32) -> Occlusion {
+        let hit = self.ray_intersect(ray_origin, ray_direction);
+        if !hit.is_intersecting || hit.distance >= max_t {
+            Occlusion::Clear
+        } else if hit.material.albedo[3] <= 0.0 {
+            Occlusion::Opaque
+        } else {
+            Occlusion::Tinted(Box::new(hit))
+        }
+    }
+
+    /// Caja que envuelve al objeto; `None` si no está acotado (se prueba
+    /// siempre)
+    fn bounds(&self) -> Option<Aabb> {
+        None
+    }
+
+    /// Primitivas que se intersectan al recorrer el objeto (para
+    /// estadísticas); los contenedores suman las de sus hijos
+    fn primitive_count(&self) -> usize {
+        1
+    }
+
+    /// Memoria aproximada que ocupa el objeto, incluidos sus hijos propios
+    fn memory_bytes(&self) -> usize {
+        std::mem::size_of_val(self)
+    }
 }
@@ -0,0 +1,73 @@
+use std::f32::consts::PI;
+use crate::math::Vec3;
+use crate::sampling;
+
+/// Divisiones por unidad de la grilla de `Rng::at_point`
+const POINT_GRID: f32 = 4096.0;
+
+/// Generador pseudoaleatorio pequeño y rápido (xorshift64*), suficiente para
+/// muestreo en el renderizador y reproducible a partir de una semilla
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // El estado nunca puede ser cero
+        Rng { state: seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1 }
+    }
+
+    /// Generador propio de un punto de la escena: el mismo punto con la misma
+    /// semilla da siempre la misma secuencia, sin importar el orden en que
+    /// los hilos lo visiten. El punto se redondea a una grilla fina antes
+    /// del hash: así un último bit distinto (otro build, otro camino de
+    /// cálculo) no cambia la secuencia
+    pub fn at_point(seed: u64, point: Vec3) -> Self {
+        let snap = |v: f32| (v * POINT_GRID).round() as i64 as u64;
+        let hash = (snap(point.x) << 32) ^ (snap(point.y) << 16) ^ snap(point.z);
+        Rng::new(hash ^ seed.rotate_left(29))
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Flotante uniforme en [0, 1)
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Dirección uniforme dentro de un cono alrededor de `axis` (normalizado)
+    /// con coseno del semiángulo `cos_max`
+    pub fn in_cone(&mut self, axis: Vec3, cos_max: f32) -> Vec3 {
+        let cos_theta = 1.0 - self.next_f32() * (1.0 - cos_max);
+        let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+        let phi = 2.0 * PI * self.next_f32();
+
+        let (tangent, bitangent) = orthonormal_basis(axis);
+        (tangent * (sin_theta * phi.cos()) + bitangent * (sin_theta * phi.sin()) + axis * cos_theta)
+            .normalized()
+    }
+
+    /// Dirección con distribución coseno en el hemisferio de `normal`
+    pub fn cosine_hemisphere(&mut self, normal: Vec3) -> Vec3 {
+        let u = self.next_f32();
+        let v = self.next_f32();
+        sampling::cosine_hemisphere(u, v, normal)
+    }
+}
+
+/// Dos vectores unitarios perpendiculares a `n` (y entre sí)
+pub fn orthonormal_basis(n: Vec3) -> (Vec3, Vec3) {
+    let helper = if n.x.abs() > 0.9 {
+        Vec3::new(0.0, 1.0, 0.0)
+    } else {
+        Vec3::new(1.0, 0.0, 0.0)
+    };
+    let tangent = n.cross(helper).normalized();
+    (tangent, n.cross(tangent))
+}
@@ -0,0 +1,57 @@
+//! Secuencias de baja discrepancia para repartir las muestras de un mismo
+//! punto (sombras suaves, hemisferio de la GI, sub-píxeles del AA). Cubren
+//! el dominio mucho más parejo que números aleatorios independientes, así
+//! que con pocas muestras el ruido baja bastante más rápido.
+
+use std::f32::consts::PI;
+use crate::math::Vec3;
+use crate::rng::orthonormal_basis;
+
+/// Inverso radical de `index` en la base `base` (secuencia de van der
+/// Corput): 1 → 1/2, 2 → 1/4, 3 → 3/4, ...
+pub fn radical_inverse(mut index: u32, base: u32) -> f32 {
+    let inv_base = 1.0 / base as f32;
+    let mut factor = inv_base;
+    let mut result = 0.0;
+    while index > 0 {
+        result += (index % base) as f32 * factor;
+        index /= base;
+        factor *= inv_base;
+    }
+    result
+}
+
+/// Punto `index` de la secuencia de Halton 2D (bases 2 y 3); no necesita
+/// saber de antemano cuántas muestras habrá
+pub fn halton(index: u32) -> (f32, f32) {
+    (radical_inverse(index, 2), radical_inverse(index, 3))
+}
+
+/// Punto `index` de `count` del conjunto de Hammersley: estratificado en la
+/// primera coordenada y van der Corput en la segunda
+pub fn hammersley(index: u32, count: u32) -> (f32, f32) {
+    ((index as f32 + 0.5) / count.max(1) as f32, radical_inverse(index, 2))
+}
+
+/// Rotación de Cranley-Patterson: desplaza el punto por `shift` módulo 1,
+/// para que cada punto sombreado use una copia distinta del mismo patrón
+pub fn rotate(point: (f32, f32), shift: (f32, f32)) -> (f32, f32) {
+    ((point.0 + shift.0).fract(), (point.1 + shift.1).fract())
+}
+
+/// Punto del disco unidad con área uniforme a partir de (u, v) en [0, 1)²
+pub fn disk(u: f32, v: f32) -> (f32, f32) {
+    let r = u.sqrt();
+    let phi = 2.0 * PI * v;
+    (r * phi.cos(), r * phi.sin())
+}
+
+/// Dirección con distribución coseno en el hemisferio de `normal` a partir
+/// de (u, v) en [0, 1)²
+pub fn cosine_hemisphere(u: f32, v: f32, normal: Vec3) -> Vec3 {
+    let (x, y) = disk(u, v);
+    let z = (1.0 - x * x - y * y).max(0.0).sqrt();
+
+    let (tangent, bitangent) = orthonormal_basis(normal);
+    (tangent * x + bitangent * y + normal * z).normalized()
+}
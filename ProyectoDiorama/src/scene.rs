@@ -0,0 +1,139 @@
+use crate::emissive::EmissiveBlock;
+use crate::irradiance_cache::IrradianceCache;
+use crate::math::Vec3;
+
+use crate::light::Light;
+use crate::lightmap::Lightmaps;
+use crate::matcap::Matcap;
+use crate::material::Material;
+use crate::particles::ParticleEmitter;
+use crate::occluder_cache::OccluderCache;
+use crate::photon_map::PhotonMap;
+use crate::post::PostStack;
+use crate::scene_bounds::SceneBounds;
+use crate::sky::{self, HorizonFog, Sky};
+use crate::sky_light::SkyIrradiance;
+use crate::ray_intersect::RayIntersect;
+use crate::texture_manager::TextureManager;
+use crate::toon::{Cel, Toon};
+use crate::weather::Weather;
+
+/// Sesgo base de los orígenes de rayos secundarios
+pub const ORIGIN_BIAS: f32 = 1e-4;
+
+/// Tope de rebotes por defecto (la ruleta rusa suele cortar mucho antes)
+pub const MAX_DEPTH: u32 = 32;
+
+/// Todo lo que `cast_ray` necesita además del rayo: geometría, luz,
+/// texturas y el tiempo global para materiales animados
+#[derive(Clone, Copy)]
+pub struct Scene<'a> {
+    pub objects: &'a [&'a dyn RayIntersect],
+    /// Cajas envolventes de `objects` (armadas sobre el mismo slice) para
+    /// descartar rayos sin recorrer la lista; `None` = recorrido completo
+    pub bounds: Option<&'a SceneBounds>,
+    pub light: &'a Light,
+    /// Luces además de la principal (p. ej. de relleno, sin sombras)
+    pub extra_lights: &'a [Light],
+    /// Último oclusor de sombra por píxel, que se prueba primero
+    pub occluders: Option<&'a OccluderCache>,
+    pub tm: &'a TextureManager,
+    /// Segundos desde el inicio (o del cuadro, en animaciones offline)
+    pub time: f32,
+    /// Mapa de fotones de cáusticas, si se calculó
+    pub photons: Option<&'a PhotonMap>,
+    /// Caché de luz indirecta difusa, si la iluminación global está activa
+    pub irradiance: Option<&'a IrradianceCache>,
+    /// Cielo que ven los rayos que no chocan con nada
+    pub sky: &'a Sky,
+    /// Niebla hacia el horizonte del cielo, si la escena la pide
+    pub fog: Option<HorizonFog>,
+    /// Iluminación difusa del cielo, si está activa
+    pub sky_light: Option<&'a SkyIrradiance>,
+    /// Luz ambiente constante (color × intensidad) sumada al término difuso
+    pub ambient: Vec3,
+    /// Iluminación horneada por cara, si se precalculó
+    pub lightmaps: Option<&'a Lightmaps>,
+    /// Rayos de sombra por punto (1 = sombras duras)
+    pub shadow_samples: u32,
+    /// Rayos primarios por píxel (antialiasing); 1 = uno por esquina de píxel
+    pub pixel_samples: u32,
+    /// Índice de la primera sub-muestra de cada píxel; al acumular cuadros
+    /// cada uno sigue la secuencia donde la dejó el anterior
+    pub sample_offset: u32,
+    /// Píxel y número de sub-muestra que se está trazando, para tomar el
+    /// ruido azul de pantalla; `None` fuera del render (p. ej. al hornear)
+    pub pixel: Option<(usize, usize, u32)>,
+    /// Ancho y alto de la imagen a la que pertenece `pixel`
+    pub frame: (usize, usize),
+    /// Emisores de partículas, evaluados en `time`
+    pub emitters: &'a [ParticleEmitter],
+    /// Lluvia o nieve, también evaluada en `time`
+    pub weather: Option<&'a Weather>,
+    /// Material que cubre las caras de arriba a cielo abierto (nieve)
+    pub snow: Option<&'a Material>,
+    /// Sombreado matcap de depuración en vez de luces y materiales
+    pub matcap: Option<&'a Matcap>,
+    /// Luz difusa en bandas, si está activa
+    pub cel: Option<&'a Cel>,
+    /// Estilo caricatura: brillos duros y contornos, si está activo
+    pub toon: Option<Toon>,
+    /// Sesgo base para alejar rayos secundarios de la superficie
+    pub bias: f32,
+    /// Rebotes máximos de reflexión/refracción por rayo
+    pub max_depth: u32,
+    /// Semilla de todo el muestreo aleatorio (sombras suaves, GI)
+    pub seed: u64,
+    /// Efectos de post-proceso sobre la imagen final
+    pub post: Option<&'a PostStack>,
+    /// Bloques emisivos muestreados como luces de área en modo GI
+    pub emissive: &'a [EmissiveBlock],
+    /// Irradiancia mínima que debe poder aportar un bloque emisivo para
+    /// considerarlo en un punto; 0 = todos, sin importar la distancia
+    pub light_cutoff: f32,
+    /// Radiancia máxima por muestra estocástica (GI); `INFINITY` = sin límite
+    pub firefly_clamp: f32,
+    /// Tramado ordenado al cuantizar a 8 bits (`--dither`)
+    pub dither: bool,
+}
+
+impl<'a> Scene<'a> {
+    /// Escena con solo iluminación directa y sin extras
+    pub fn new(objects: &'a [&'a dyn RayIntersect], light: &'a Light, tm: &'a TextureManager) -> Self {
+        Scene {
+            objects,
+            bounds: None,
+            light,
+            extra_lights: &[],
+            occluders: None,
+            tm,
+            time: 0.0,
+            photons: None,
+            irradiance: None,
+            sky: &sky::PLAIN,
+            fog: None,
+            sky_light: None,
+            ambient: Vec3::zero(),
+            lightmaps: None,
+            shadow_samples: 1,
+            pixel_samples: 1,
+            sample_offset: 0,
+            pixel: None,
+            frame: (0, 0),
+            emitters: &[],
+            weather: None,
+            snow: None,
+            matcap: None,
+            cel: None,
+            toon: None,
+            bias: ORIGIN_BIAS,
+            max_depth: MAX_DEPTH,
+            seed: 1,
+            post: None,
+            emissive: &[],
+            light_cutoff: 0.0,
+            firefly_clamp: f32::INFINITY,
+            dither: false,
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use std::ops::ControlFlow;
+
+use crate::geometry::Aabb;
+use crate::math::Vec3;
+use crate::ray_intersect::RayIntersect;
+
+/// Objetos cuya caja está centrada en una misma altura entera
+#[derive(Debug, Clone)]
+struct Layer {
+    bounds: Aabb,
+    objects: Vec<usize>,
+}
+
+/// Cajas envolventes de los objetos de una escena: una para toda la escena y
+/// una por capa de altura. Un rayo que no toca la caja de la escena (el
+/// cielo, casi todo el cuadro) no recorre ningún objeto, y uno que sí la
+/// toca solo recorre las capas que cruza.
+#[derive(Debug, Clone, Default)]
+pub struct SceneBounds {
+    whole: Option<Aabb>,
+    layers: Vec<Layer>,
+    /// Objetos sin caja (se prueban siempre)
+    unbounded: Vec<usize>,
+}
+
+impl SceneBounds {
+    /// Agrupa los índices de `objects` por capa
+    pub fn new(objects: &[&dyn RayIntersect]) -> Self {
+        let mut bounds = SceneBounds::default();
+        let mut layers: Vec<(i32, Layer)> = Vec::new();
+        for (index, object) in objects.iter().enumerate() {
+            let Some(b) = object.bounds() else {
+                bounds.unbounded.push(index);
+                continue;
+            };
+            bounds.whole = Some(bounds.whole.map_or(b, |w| w.union(&b)));
+            let y = b.center().y.round() as i32;
+            match layers.iter_mut().find(|(ly, _)| *ly == y) {
+                Some((_, layer)) => {
+                    layer.bounds = layer.bounds.union(&b);
+                    layer.objects.push(index);
+                }
+                None => layers.push((y, Layer { bounds: b, objects: vec![index] })),
+            }
+        }
+        layers.sort_by_key(|(y, _)| *y);
+        bounds.layers = layers.into_iter().map(|(_, layer)| layer).collect();
+        bounds
+    }
+
+    /// Capas con caja propia
+    pub fn layer_count(&self) -> usize {
+        self.layers.len()
+    }
+
+    /// Llama a `visit` con el índice de cada objeto que el rayo puede tocar
+    /// antes de `max_t`; si `visit` devuelve `Break` no sigue con el resto
+    pub fn visit(
+        &self,
+        origin: &Vec3,
+        direction: &Vec3,
+        max_t: f32,
+        mut visit: impl FnMut(usize) -> ControlFlow<()>,
+    ) -> ControlFlow<()> {
+        self.unbounded.iter().try_for_each(|&i| visit(i))?;
+        let Some(whole) = self.whole else {
+            return ControlFlow::Continue(());
+        };
+        if !whole.hit(origin, direction, max_t) {
+            return ControlFlow::Continue(());
+        }
+        for layer in &self.layers {
+            if layer.bounds.hit(origin, direction, max_t) {
+                layer.objects.iter().try_for_each(|&i| visit(i))?;
+            }
+        }
+        ControlFlow::Continue(())
+    }
+}
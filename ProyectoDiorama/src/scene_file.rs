@@ -0,0 +1,1052 @@
+use crate::light::ALL_GROUPS;
+use crate::material::{Anisotropy, Clearcoat, Flicker, Subsurface, ThinFilm, Tint, UvTransform};
+use crate::ray_intersect::CubeFace;
+use crate::math::Vec3;
+
+use crate::error::{Error, Result};
+use crate::particles::ParticleEmitter;
+use crate::post::{Effect, Lut};
+use crate::shapes;
+use crate::sky::{Clouds, HorizonFog, Night, Sun};
+use crate::stamps;
+use crate::toon::Cel;
+use crate::weather::Precipitation;
+
+/// Estructura prefabricada a estampar en una posición del mundo
+#[derive(Debug, Clone)]
+pub struct StampPlacement {
+    pub name: String,
+    pub position: (i32, i32, i32),
+}
+
+/// Malla OBJ a voxelizar e insertar en el mundo
+#[derive(Debug, Clone)]
+pub struct VoxelizeJob {
+    pub obj_path: String,
+    pub resolution: u32,
+    pub position: (i32, i32, i32),
+    /// Textura de la que se toman los colores para elegir materiales
+    pub texture: Option<String>,
+    /// Material de los vóxeles sin color de textura; `None` =
+    /// `voxelizer::DEFAULT_SYMBOL`
+    pub default_symbol: Option<char>,
+}
+
+/// Texto a escribir con vóxeles
+#[derive(Debug, Clone)]
+pub struct TextLabel {
+    pub text: String,
+    pub position: (i32, i32, i32),
+    pub symbol: char,
+}
+
+/// Plano de agua con Fresnel: altura y rectángulo (x0, z0)–(x1, z1)
+#[derive(Debug, Clone, Copy)]
+pub struct WaterRect {
+    pub height: f32,
+    pub min: (f32, f32),
+    pub max: (f32, f32),
+}
+
+/// Caja de un solo material entre dos esquinas, p. ej. una plataforma
+#[derive(Debug, Clone, Copy)]
+pub struct BoxPlacement {
+    pub min: Vec3,
+    pub max: Vec3,
+    pub symbol: char,
+}
+
+/// Mosaico de la textura de un material (ver `Material::with_uv_tiling`)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tiling {
+    /// Repeticiones por unidad del mundo en (u, v)
+    pub scale: (f32, f32),
+    pub offset: (f32, f32),
+}
+
+/// Caja girada `yaw` grados alrededor del eje Y de su centro
+#[derive(Debug, Clone, Copy)]
+pub struct ObbPlacement {
+    pub center: Vec3,
+    pub size: Vec3,
+    pub yaw: f32,
+    pub symbol: char,
+}
+
+/// Copia instanciada (compartida) de una estructura prefabricada
+#[derive(Debug, Clone)]
+pub struct InstancePlacement {
+    pub name: String,
+    pub position: Vec3,
+    pub yaw: f32,
+    pub scale: f32,
+}
+
+/// Emisor de partículas predefinido (`fire`, `smoke`) en una posición
+#[derive(Debug, Clone)]
+pub struct EmitterPlacement {
+    pub kind: String,
+    pub position: Vec3,
+}
+
+/// Luz adicional a la principal
+#[derive(Debug, Clone)]
+pub struct LightPlacement {
+    pub position: Vec3,
+    pub intensity: f32,
+    pub color: Vec3,
+    /// `false` = luz de relleno, sin rayos de sombra
+    pub shadows: bool,
+    /// Grupos de objetos que ilumina (máscara de bits)
+    pub groups: u32,
+}
+
+/// Contenido de un archivo de escena.
+///
+/// Formato de texto, una directiva por línea (`#` inicia un comentario):
+///
+/// ```text
+/// stamp tree 12 0 3
+/// stamp rock -4 0 8
+/// instance tree -8 0 -6 30 0.8
+/// voxelize modelos/pato.obj 16 -10 0 -2 modelos/pato.png
+/// text -6 0 -4 R Pokeball 2025
+/// water -0.6 -12 -12 0 20
+/// box -10.5 -1.5 -10.5 20.5 -0.5 20.5 B
+/// obb 16 0.5 6 1 1 1 45 M
+/// emitter smoke 4 12 4
+/// ambient 0.6 0.7 1.0 0.15
+/// light 10 6 -8 0.8 1 0.9 0.8 noshadow
+/// light 4 2 4 1.5 groups=1
+/// group 1 G Y
+/// sun -1 0.6 0.8 0.04 60
+/// clouds 0.5 0.9 0.02 1.5
+/// night 0.4 0.5 -1 0.1
+/// fog 0.02 0 0.2
+/// weather rain 1.5
+/// snow W
+/// flicker G noise 0.25 6
+/// dispersion V 1.50 1.52 1.55
+/// subsurface Y 1 0.8 0.4 0.5 0.6
+/// film Q 380 120 1.33
+/// aniso S 4 120
+/// clearcoat R 0.8 200
+/// bevel W 0.06
+/// partial _ P
+/// cel 0.05 0.35 0.75
+/// tint P 0.08 -10 0 1.1 1.05 0.8 20 0 0.85 1.0 0.9
+/// bias 0.0005
+/// max_depth 12
+/// script scripts/espiral.rhai
+/// post vignette 0.35
+/// post aberration 1.5
+/// post grain 0.04
+/// post lut grading/calido.cube
+/// ```
+#[derive(Debug, Clone)]
+pub struct SceneFile {
+    pub stamps: Vec<StampPlacement>,
+    pub instances: Vec<InstancePlacement>,
+    pub voxelize: Vec<VoxelizeJob>,
+    pub labels: Vec<TextLabel>,
+    pub water: Vec<WaterRect>,
+    pub boxes: Vec<BoxPlacement>,
+    pub obbs: Vec<ObbPlacement>,
+    pub emitters: Vec<EmitterPlacement>,
+    /// Luz ambiente (color × intensidad); cero si la escena no la define
+    pub ambient: Vec3,
+    /// Luces además de la principal
+    pub lights: Vec<LightPlacement>,
+    /// Grupos de luces de los materiales de esos símbolos (máscara de bits)
+    pub light_groups: Vec<(char, u32)>,
+    /// Disco del sol en el cielo; `None` = cielo sin sol
+    pub sun: Option<Sun>,
+    /// Capa de nubes animada; `None` = cielo despejado
+    pub clouds: Option<Clouds>,
+    /// Cielo nocturno (luna y estrellas); con `sun`, según la altura del sol
+    pub night: Option<Night>,
+    /// Niebla hacia el horizonte; `None` = sin niebla
+    pub fog: Option<HorizonFog>,
+    /// Lluvia o nieve con su intensidad (1 = moderada)
+    pub weather: Option<(Precipitation, f32)>,
+    /// Símbolo del material que cubre de nieve las caras de arriba expuestas
+    pub snow: Option<char>,
+    /// Tintes por posición para los materiales de esos símbolos
+    pub tints: Vec<(char, Tint)>,
+    /// Animaciones de la luz propia para los materiales de esos símbolos
+    pub flickers: Vec<(char, Flicker)>,
+    /// Índices de refracción por canal (r, g, b) de esos símbolos
+    pub dispersions: Vec<(char, [f32; 3])>,
+    /// Luz bajo la superficie para los materiales de esos símbolos
+    pub subsurfaces: Vec<(char, Subsurface)>,
+    /// Películas delgadas (iridiscencia) para los materiales de esos símbolos
+    pub films: Vec<(char, ThinFilm)>,
+    /// Brillos anisótropos para los materiales de esos símbolos
+    pub anisotropies: Vec<(char, Anisotropy)>,
+    /// Lacas para los materiales de esos símbolos
+    pub clearcoats: Vec<(char, Clearcoat)>,
+    /// Ancho del bisel de las aristas para los materiales de esos símbolos
+    pub bevels: Vec<(char, f32)>,
+    /// Mosaico de la textura de los materiales de esos símbolos
+    pub tiles: Vec<(char, Tiling)>,
+    /// Orientación de la textura en una cara para esos símbolos
+    pub face_uvs: Vec<(char, (CubeFace, UvTransform))>,
+    /// Material de cada bloque parcial (símbolo de forma, símbolo de
+    /// material); los que no aparecen usan el de `shapes::PARTIAL_BLOCKS`
+    pub partials: Vec<(char, char)>,
+    /// Luz difusa en bandas; `None` = continua
+    pub cel: Option<Cel>,
+    /// Sesgo de rayos secundarios; `None` usa el valor por defecto
+    pub bias: Option<f32>,
+    /// Rebotes máximos por rayo; `None` usa el valor por defecto
+    pub max_depth: Option<u32>,
+    /// Scripts Rhai que generan bloques, materiales y luz (ver `script::run`)
+    pub scripts: Vec<String>,
+    /// Post-proceso, en el orden en que se aplica
+    pub post: Vec<Effect>,
+}
+
+impl Default for SceneFile {
+    fn default() -> Self {
+        SceneFile {
+            stamps: Vec::new(),
+            instances: Vec::new(),
+            voxelize: Vec::new(),
+            labels: Vec::new(),
+            water: Vec::new(),
+            boxes: Vec::new(),
+            obbs: Vec::new(),
+            emitters: Vec::new(),
+            ambient: Vec3::zero(),
+            lights: Vec::new(),
+            light_groups: Vec::new(),
+            sun: None,
+            clouds: None,
+            night: None,
+            fog: None,
+            weather: None,
+            snow: None,
+            tints: Vec::new(),
+            flickers: Vec::new(),
+            dispersions: Vec::new(),
+            subsurfaces: Vec::new(),
+            films: Vec::new(),
+            anisotropies: Vec::new(),
+            clearcoats: Vec::new(),
+            bevels: Vec::new(),
+            tiles: Vec::new(),
+            face_uvs: Vec::new(),
+            partials: Vec::new(),
+            cel: None,
+            bias: None,
+            max_depth: None,
+            scripts: Vec::new(),
+            post: Vec::new(),
+        }
+    }
+}
+
+/// Símbolo de material: exactamente un carácter
+fn parse_symbol(s: &str) -> std::result::Result<char, String> {
+    let mut chars = s.chars();
+    match (chars.next(), chars.next()) {
+        (Some(symbol), None) => Ok(symbol),
+        _ => Err(format!("símbolo inválido: {}", s)),
+    }
+}
+
+fn parse_f32(s: &str) -> std::result::Result<f32, String> {
+    s.parse::<f32>().map_err(|_| format!("número inválido: {}", s))
+}
+
+fn parse_vec3(x: &str, y: &str, z: &str) -> std::result::Result<Vec3, String> {
+    Ok(Vec3::new(parse_f32(x)?, parse_f32(y)?, parse_f32(z)?))
+}
+
+fn parse_floats(values: &[&str]) -> std::result::Result<Vec<f32>, String> {
+    values.iter().map(|s| parse_f32(s)).collect()
+}
+
+/// Máscara de una lista de grupos separados por comas (`0,3`), 0 a 31
+fn group_mask(list: &str) -> Option<u32> {
+    list.split(',').try_fold(0, |mask, g| {
+        let g = g.trim().parse::<u32>().ok().filter(|g| *g < 32)?;
+        Some(mask | 1 << g)
+    })
+}
+
+impl SceneFile {
+    pub fn load(path: &str) -> Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        Self::parse(&text, path)
+    }
+
+    pub fn parse(text: &str, path: &str) -> Result<Self> {
+        let mut scene = SceneFile::default();
+
+        for (i, raw) in text.lines().enumerate() {
+            let line = raw.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let err = |msg: String| Error::Parse {
+                path: path.to_string(),
+                line: i + 1,
+                msg,
+            };
+
+            let mut words = line.split_whitespace();
+            let directive = words.next().unwrap_or("");
+            let params: Vec<&str> = words.collect();
+
+            match directive {
+                "stamp" => {
+                    let [name, x, y, z] = params[..] else {
+                        return Err(err("uso: stamp <nombre> <x> <y> <z>".to_string()));
+                    };
+                    if stamps::find(name).is_none() {
+                        return Err(err(format!("estructura desconocida: {}", name)));
+                    }
+                    let coord = |s: &str| {
+                        s.parse::<i32>()
+                            .map_err(|_| err(format!("coordenada inválida: {}", s)))
+                    };
+                    scene.stamps.push(StampPlacement {
+                        name: name.to_string(),
+                        position: (coord(x)?, coord(y)?, coord(z)?),
+                    });
+                }
+                "instance" => {
+                    let (name, x, y, z, yaw, scale) = match params[..] {
+                        [name, x, y, z] => (name, x, y, z, "0", "1"),
+                        [name, x, y, z, yaw] => (name, x, y, z, yaw, "1"),
+                        [name, x, y, z, yaw, scale] => (name, x, y, z, yaw, scale),
+                        _ => {
+                            return Err(err(
+                                "uso: instance <nombre> <x> <y> <z> [giro_y] [escala]".to_string(),
+                            ))
+                        }
+                    };
+                    if stamps::find(name).is_none() {
+                        return Err(err(format!("estructura desconocida: {}", name)));
+                    }
+                    scene.instances.push(InstancePlacement {
+                        name: name.to_string(),
+                        position: parse_vec3(x, y, z).map_err(err)?,
+                        yaw: parse_f32(yaw).map_err(err)?,
+                        scale: parse_f32(scale).map_err(err)?,
+                    });
+                }
+                "voxelize" => {
+                    let mut default_symbol = None;
+                    let mut positional = Vec::new();
+                    for param in &params {
+                        match param.strip_prefix("material=") {
+                            Some(symbol) => default_symbol = Some(parse_symbol(symbol).map_err(err)?),
+                            None => positional.push(*param),
+                        }
+                    }
+                    let (obj, res, x, y, z, texture) = match positional[..] {
+                        [obj, res, x, y, z] => (obj, res, x, y, z, None),
+                        [obj, res, x, y, z, tex] => (obj, res, x, y, z, Some(tex.to_string())),
+                        _ => {
+                            return Err(err(
+                                "uso: voxelize <obj> <resolución> <x> <y> <z> [textura] [material=<símbolo>]"
+                                    .to_string(),
+                            ))
+                        }
+                    };
+                    let resolution = res
+                        .parse::<u32>()
+                        .ok()
+                        .filter(|r| *r > 0)
+                        .ok_or_else(|| err(format!("resolución inválida: {}", res)))?;
+                    let coord = |s: &str| {
+                        s.parse::<i32>()
+                            .map_err(|_| err(format!("coordenada inválida: {}", s)))
+                    };
+                    scene.voxelize.push(VoxelizeJob {
+                        obj_path: obj.to_string(),
+                        resolution,
+                        position: (coord(x)?, coord(y)?, coord(z)?),
+                        texture,
+                        default_symbol,
+                    });
+                }
+                "text" => {
+                    if params.len() < 5 {
+                        return Err(err("uso: text <x> <y> <z> <símbolo> <texto...>".to_string()));
+                    }
+                    let coord = |s: &str| {
+                        s.parse::<i32>()
+                            .map_err(|_| err(format!("coordenada inválida: {}", s)))
+                    };
+                    let symbol = parse_symbol(params[3]).map_err(err)?;
+                    scene.labels.push(TextLabel {
+                        text: params[4..].join(" "),
+                        position: (coord(params[0])?, coord(params[1])?, coord(params[2])?),
+                        symbol,
+                    });
+                }
+                "water" => {
+                    let values = parse_floats(&params).map_err(err)?;
+                    let [height, x0, z0, x1, z1] = values[..] else {
+                        return Err(err("uso: water <altura> <x0> <z0> <x1> <z1>".to_string()));
+                    };
+                    scene.water.push(WaterRect {
+                        height,
+                        min: (x0.min(x1), z0.min(z1)),
+                        max: (x0.max(x1), z0.max(z1)),
+                    });
+                }
+                "box" => {
+                    let [x0, y0, z0, x1, y1, z1, symbol] = params[..] else {
+                        return Err(err("uso: box <x0> <y0> <z0> <x1> <y1> <z1> <símbolo>".to_string()));
+                    };
+                    let symbol = parse_symbol(symbol).map_err(err)?;
+                    let (a, b) = (
+                        parse_vec3(x0, y0, z0).map_err(err)?,
+                        parse_vec3(x1, y1, z1).map_err(err)?,
+                    );
+                    // Una caja sin volumen no tiene caras que sombrear (sus UV dividirían por cero)
+                    if a.x == b.x || a.y == b.y || a.z == b.z {
+                        return Err(err("la caja no puede tener un lado nulo".to_string()));
+                    }
+                    scene.boxes.push(BoxPlacement {
+                        min: Vec3::new(a.x.min(b.x), a.y.min(b.y), a.z.min(b.z)),
+                        max: Vec3::new(a.x.max(b.x), a.y.max(b.y), a.z.max(b.z)),
+                        symbol,
+                    });
+                }
+                "obb" => {
+                    let [cx, cy, cz, sx, sy, sz, yaw, symbol] = params[..] else {
+                        return Err(err("uso: obb <cx> <cy> <cz> <ancho> <alto> <fondo> <giro_y> <símbolo>".to_string()));
+                    };
+                    let symbol = parse_symbol(symbol).map_err(err)?;
+                    let size = parse_vec3(sx, sy, sz).map_err(err)?;
+                    if size.x <= 0.0 || size.y <= 0.0 || size.z <= 0.0 {
+                        return Err(err(format!("tamaño inválido: {} {} {}", sx, sy, sz)));
+                    }
+                    scene.obbs.push(ObbPlacement {
+                        center: parse_vec3(cx, cy, cz).map_err(err)?,
+                        size,
+                        yaw: parse_f32(yaw).map_err(err)?,
+                        symbol,
+                    });
+                }
+                "emitter" => {
+                    let [kind, x, y, z] = params[..] else {
+                        return Err(err("uso: emitter <fire|smoke> <x> <y> <z>".to_string()));
+                    };
+                    let position = parse_vec3(x, y, z).map_err(err)?;
+                    if ParticleEmitter::preset(kind, position).is_none() {
+                        return Err(err(format!("emisor desconocido: {}", kind)));
+                    }
+                    scene.emitters.push(EmitterPlacement { kind: kind.to_string(), position });
+                }
+                "ambient" => {
+                    let values = parse_floats(&params).map_err(err)?;
+                    let (r, g, b, intensity) = match values[..] {
+                        [r, g, b] => (r, g, b, 1.0),
+                        [r, g, b, i] => (r, g, b, i),
+                        _ => return Err(err("uso: ambient <r> <g> <b> [intensidad]".to_string())),
+                    };
+                    scene.ambient = Vec3::new(r, g, b) * intensity;
+                }
+                "light" => {
+                    let usage = "uso: light <x> <y> <z> <intensidad> [<r> <g> <b>] [noshadow] [groups=<g>,...]";
+                    let mut shadows = true;
+                    let mut groups = ALL_GROUPS;
+                    let mut values = Vec::new();
+                    for param in &params {
+                        if *param == "noshadow" {
+                            shadows = false;
+                        } else if let Some(list) = param.strip_prefix("groups=") {
+                            groups = group_mask(list).ok_or_else(|| err(format!("grupos inválidos: {}", list)))?;
+                        } else {
+                            values.push(parse_f32(param).map_err(err)?);
+                        }
+                    }
+                    let (x, y, z, intensity, color) = match values[..] {
+                        [x, y, z, i] => (x, y, z, i, Vec3::one()),
+                        [x, y, z, i, r, g, b] => (x, y, z, i, Vec3::new(r, g, b)),
+                        _ => return Err(err(usage.to_string())),
+                    };
+                    scene.lights.push(LightPlacement { position: Vec3::new(x, y, z), intensity, color, shadows, groups });
+                }
+                "group" => {
+                    let Some((group, symbols)) = params.split_first().filter(|(_, s)| !s.is_empty()) else {
+                        return Err(err("uso: group <0-31> <símbolos...>".to_string()));
+                    };
+                    let bit = group_mask(group).ok_or_else(|| err(format!("grupo inválido: {}", group)))?;
+                    for symbol in symbols {
+                        let symbol = parse_symbol(symbol).map_err(err)?;
+                        scene.light_groups.push((symbol, bit));
+                    }
+                }
+                "sun" => {
+                    let values = parse_floats(&params).map_err(err)?;
+                    let [x, y, z, ref rest @ ..] = values[..] else {
+                        return Err(err("uso: sun <x> <y> <z> [radio] [intensidad]".to_string()));
+                    };
+                    let direction = Vec3::new(x, y, z);
+                    if direction.length() == 0.0 {
+                        return Err(err("la dirección del sol no puede ser nula".to_string()));
+                    }
+                    let mut sun = Sun::new(direction);
+                    match rest {
+                        [] => {}
+                        [radius] => sun = sun.with_radius(*radius),
+                        [radius, intensity] => sun = sun.with_radius(*radius).with_intensity(*intensity),
+                        _ => return Err(err("uso: sun <x> <y> <z> [radio] [intensidad]".to_string())),
+                    }
+                    scene.sun = Some(sun);
+                }
+                "clouds" => {
+                    let values = parse_floats(&params).map_err(err)?;
+                    let clouds = match values[..] {
+                        [coverage, density, speed] => Clouds::new(coverage, density, speed),
+                        [coverage, density, speed, scale] => Clouds::new(coverage, density, speed).with_scale(scale),
+                        _ => return Err(err("uso: clouds <cobertura> <densidad> <velocidad> [escala]".to_string())),
+                    };
+                    scene.clouds = Some(clouds);
+                }
+                "night" => {
+                    let values = parse_floats(&params).map_err(err)?;
+                    let night = match values[..] {
+                        [] => Night::new(Vec3::new(0.4, 0.5, -1.0)),
+                        [x, y, z] => Night::new(Vec3::new(x, y, z)),
+                        [x, y, z, stars] => Night::new(Vec3::new(x, y, z)).with_stars(stars),
+                        _ => return Err(err("uso: night [<luna_x> <luna_y> <luna_z> [estrellas]]".to_string())),
+                    };
+                    if night.moon.length() == 0.0 {
+                        return Err(err("la dirección de la luna no puede ser nula".to_string()));
+                    }
+                    scene.night = Some(night);
+                }
+                "fog" => {
+                    let values = parse_floats(&params).map_err(err)?;
+                    let fog = match values[..] {
+                        [density] => HorizonFog::new(density),
+                        [density, height] => HorizonFog::new(density).with_height(height, 0.15),
+                        [density, height, falloff] => HorizonFog::new(density).with_height(height, falloff),
+                        _ => return Err(err("uso: fog <densidad> [altura] [caída]".to_string())),
+                    };
+                    scene.fog = Some(fog);
+                }
+                "weather" => {
+                    let (kind, intensity) = match params[..] {
+                        [kind] => (kind, "1"),
+                        [kind, intensity] => (kind, intensity),
+                        _ => return Err(err("uso: weather <rain|snow> [intensidad]".to_string())),
+                    };
+                    let kind = Precipitation::parse(kind)
+                        .ok_or_else(|| err(format!("clima desconocido: {}", kind)))?;
+                    let intensity = intensity
+                        .parse::<f32>()
+                        .ok()
+                        .filter(|i| *i > 0.0)
+                        .ok_or_else(|| err(format!("intensidad inválida: {}", intensity)))?;
+                    scene.weather = Some((kind, intensity));
+                }
+                "snow" => {
+                    let symbol = match params[..] {
+                        [] => "W",
+                        [symbol] => symbol,
+                        _ => return Err(err("uso: snow [símbolo]".to_string())),
+                    };
+                    let symbol = parse_symbol(symbol).map_err(err)?;
+                    scene.snow = Some(symbol);
+                }
+                "flicker" => {
+                    let [symbol, kind, amplitude, frequency] = params[..] else {
+                        return Err(err("uso: flicker <símbolo> <sine|noise> <amplitud> <frecuencia>".to_string()));
+                    };
+                    let symbol = parse_symbol(symbol).map_err(err)?;
+                    let noise = match kind {
+                        "sine" => false,
+                        "noise" => true,
+                        other => return Err(err(format!("animación desconocida: {}", other))),
+                    };
+                    let flicker = Flicker { amplitude: parse_f32(amplitude).map_err(err)?, frequency: parse_f32(frequency).map_err(err)?, noise };
+                    scene.flickers.push((symbol, flicker));
+                }
+                "dispersion" => {
+                    let [symbol, r, g, b] = params[..] else {
+                        return Err(err("uso: dispersion <símbolo> <ior_r> <ior_g> <ior_b>".to_string()));
+                    };
+                    let symbol = parse_symbol(symbol).map_err(err)?;
+                    let ior = |s: &str| {
+                        s.parse::<f32>()
+                            .ok()
+                            .filter(|i| *i > 0.0)
+                            .ok_or_else(|| err(format!("índice inválido: {}", s)))
+                    };
+                    scene.dispersions.push((symbol, [ior(r)?, ior(g)?, ior(b)?]));
+                }
+                "subsurface" => {
+                    let [symbol, r, g, b, wrap, depth] = params[..] else {
+                        return Err(err("uso: subsurface <símbolo> <r> <g> <b> <envoltura> <grosor>".to_string()));
+                    };
+                    let symbol = parse_symbol(symbol).map_err(err)?;
+                    let depth = parse_f32(depth).map_err(err)?;
+                    if depth <= 0.0 {
+                        return Err(err(format!("grosor inválido: {}", depth)));
+                    }
+                    let subsurface = Subsurface {
+                        color: parse_vec3(r, g, b).map_err(err)?,
+                        wrap: parse_f32(wrap).map_err(err)?.max(0.0),
+                        depth,
+                    };
+                    scene.subsurfaces.push((symbol, subsurface));
+                }
+                "film" => {
+                    let usage = "uso: film <símbolo> <grosor_nm> [variación_nm] [ior]";
+                    let Some((symbol, rest)) = params.split_first() else {
+                        return Err(err(usage.to_string()));
+                    };
+                    let symbol = parse_symbol(symbol).map_err(err)?;
+                    let values = parse_floats(rest).map_err(err)?;
+                    let (thickness, variation, ior) = match values[..] {
+                        [thickness] => (thickness, 0.0, 1.33),
+                        [thickness, variation] => (thickness, variation, 1.33),
+                        [thickness, variation, ior] => (thickness, variation, ior),
+                        _ => return Err(err(usage.to_string())),
+                    };
+                    if thickness < 0.0 || ior < 1.0 {
+                        return Err(err(usage.to_string()));
+                    }
+                    scene.films.push((symbol, ThinFilm { thickness, variation, ior }));
+                }
+                "aniso" => {
+                    let [symbol, along_u, along_v] = params[..] else {
+                        return Err(err("uso: aniso <símbolo> <exponente_u> <exponente_v>".to_string()));
+                    };
+                    let symbol = parse_symbol(symbol).map_err(err)?;
+                    let exponent = |s: &str| {
+                        s.parse::<f32>()
+                            .ok()
+                            .filter(|e| *e > 0.0)
+                            .ok_or_else(|| err(format!("exponente inválido: {}", s)))
+                    };
+                    let anisotropy = Anisotropy { along_u: exponent(along_u)?, along_v: exponent(along_v)? };
+                    scene.anisotropies.push((symbol, anisotropy));
+                }
+                "clearcoat" => {
+                    let usage = "uso: clearcoat <símbolo> <intensidad> [brillo] [ior]";
+                    let Some((symbol, rest)) = params.split_first() else {
+                        return Err(err(usage.to_string()));
+                    };
+                    let symbol = parse_symbol(symbol).map_err(err)?;
+                    let values = parse_floats(rest).map_err(err)?;
+                    let (strength, gloss, ior) = match values[..] {
+                        [strength] => (strength, 150.0, 1.5),
+                        [strength, gloss] => (strength, gloss, 1.5),
+                        [strength, gloss, ior] => (strength, gloss, ior),
+                        _ => return Err(err(usage.to_string())),
+                    };
+                    if !(0.0..=1.0).contains(&strength) || gloss <= 0.0 || ior < 1.0 {
+                        return Err(err(usage.to_string()));
+                    }
+                    scene.clearcoats.push((symbol, Clearcoat { strength, gloss, ior }));
+                }
+                "bevel" => {
+                    let [symbol, width] = params[..] else {
+                        return Err(err("uso: bevel <símbolo> <ancho>".to_string()));
+                    };
+                    let symbol = parse_symbol(symbol).map_err(err)?;
+                    let width = width
+                        .parse::<f32>()
+                        .ok()
+                        .filter(|w| (0.0..=0.5).contains(w))
+                        .ok_or_else(|| err(format!("ancho inválido: {}", width)))?;
+                    scene.bevels.push((symbol, width));
+                }
+                "partial" => {
+                    let [shape, material] = params[..] else {
+                        return Err(err("uso: partial <forma> <material>".to_string()));
+                    };
+                    let shape = parse_symbol(shape).map_err(err)?;
+                    if shapes::find(shape).is_none() {
+                        return Err(err(format!("no hay bloque parcial '{}'", shape)));
+                    }
+                    let material = parse_symbol(material).map_err(err)?;
+                    if shapes::find(material).is_some() {
+                        return Err(err(format!("'{}' es una forma, no un material", material)));
+                    }
+                    scene.partials.push((shape, material));
+                }
+                "tile" => {
+                    let usage = "uso: tile <símbolo> <repeticiones_u> <repeticiones_v> [<desplazamiento_u> <desplazamiento_v>]";
+                    let Some((symbol, rest)) = params.split_first() else {
+                        return Err(err(usage.to_string()));
+                    };
+                    let symbol = parse_symbol(symbol).map_err(err)?;
+                    let values = parse_floats(rest).map_err(err)?;
+                    let (scale, offset) = match values[..] {
+                        [u, v] => ((u, v), (0.0, 0.0)),
+                        [u, v, du, dv] => ((u, v), (du, dv)),
+                        _ => return Err(err(usage.to_string())),
+                    };
+                    if scale.0 <= 0.0 || scale.1 <= 0.0 {
+                        return Err(err(format!("repeticiones inválidas: {} {}", scale.0, scale.1)));
+                    }
+                    scene.tiles.push((symbol, Tiling { scale, offset }));
+                }
+                "face_uv" => {
+                    let usage = "uso: face_uv <símbolo> <front|back|left|right|top|bottom|all> <giros> [flip_u] [flip_v]";
+                    let [symbol, face, turns, ref flags @ ..] = params[..] else {
+                        return Err(err(usage.to_string()));
+                    };
+                    let symbol = parse_symbol(symbol).map_err(err)?;
+                    let faces = match face {
+                        "all" => CubeFace::ALL.to_vec(),
+                        name => vec![CubeFace::parse(name).ok_or_else(|| err(format!("cara desconocida: {}", name)))?],
+                    };
+                    let quarter_turns = turns
+                        .parse::<u8>()
+                        .ok()
+                        .filter(|t| *t < 4)
+                        .ok_or_else(|| err(format!("giros inválidos (0 a 3): {}", turns)))?;
+                    let mut transform = UvTransform { quarter_turns, flip_u: false, flip_v: false };
+                    for flag in flags {
+                        match *flag {
+                            "flip_u" => transform.flip_u = true,
+                            "flip_v" => transform.flip_v = true,
+                            _ => return Err(err(usage.to_string())),
+                        }
+                    }
+                    scene.face_uvs.extend(faces.into_iter().map(|face| (symbol, (face, transform))));
+                }
+                "cel" => {
+                    let [first, ..] = params[..] else {
+                        return Err(err("uso: cel <bandas> | cel <umbral> [umbral...]".to_string()));
+                    };
+                    // Un entero: tantas bandas parejas; si no, los umbrales
+                    let cel = match (params.len(), first.parse::<u32>()) {
+                        (1, Ok(bands)) if bands > 0 => Cel::even(bands),
+                        _ => Cel::with_thresholds(parse_floats(&params).map_err(err)?),
+                    };
+                    scene.cel = Some(cel);
+                }
+                "tint" => {
+                    let usage = "uso: tint <símbolo> <variación> [<x0> <z0> <r> <g> <b> <x1> <z1> <r> <g> <b>]";
+                    let Some((symbol, rest)) = params.split_first() else {
+                        return Err(err(usage.to_string()));
+                    };
+                    let symbol = parse_symbol(symbol).map_err(err)?;
+                    let values = parse_floats(rest).map_err(err)?;
+                    let tint = match values[..] {
+                        [jitter] => Tint::jitter(jitter),
+                        [jitter, x0, z0, r0, g0, b0, x1, z1, r1, g1, b1] => Tint::jitter(jitter).with_gradient(
+                            (x0, z0),
+                            Vec3::new(r0, g0, b0),
+                            (x1, z1),
+                            Vec3::new(r1, g1, b1),
+                        ),
+                        _ => return Err(err(usage.to_string())),
+                    };
+                    scene.tints.push((symbol, tint));
+                }
+                "bias" => {
+                    let [value] = params[..] else {
+                        return Err(err("uso: bias <valor>".to_string()));
+                    };
+                    scene.bias = Some(parse_f32(value).map_err(err)?);
+                }
+                "max_depth" => {
+                    let [value] = params[..] else {
+                        return Err(err("uso: max_depth <rebotes>".to_string()));
+                    };
+                    let depth = value
+                        .parse::<u32>()
+                        .map_err(|_| err(format!("número inválido: {}", value)))?;
+                    scene.max_depth = Some(depth);
+                }
+                "script" => {
+                    let [file] = params[..] else {
+                        return Err(err("uso: script <archivo.rhai>".to_string()));
+                    };
+                    scene.scripts.push(file.to_string());
+                }
+                "post" => {
+                    let [kind, value] = params[..] else {
+                        return Err(err("uso: post <vignette|aberration|grain|lut> <valor>".to_string()));
+                    };
+                    let effect = match kind {
+                        "vignette" => Effect::Vignette { strength: parse_f32(value).map_err(err)? },
+                        "aberration" => Effect::ChromaticAberration { pixels: parse_f32(value).map_err(err)? },
+                        "grain" => Effect::FilmGrain { amount: parse_f32(value).map_err(err)? },
+                        "lut" => Effect::Lut(Lut::load(value)?),
+                        other => return Err(err(format!("efecto desconocido: {}", other))),
+                    };
+                    scene.post.push(effect);
+                }
+                other => return Err(err(format!("directiva desconocida: {}", other))),
+            }
+        }
+
+        Ok(scene)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> SceneFile {
+        SceneFile::parse(text, "test.scene").unwrap_or_else(|e| panic!("{}: {}", text, e))
+    }
+
+    /// Línea que reporta el error de sintaxis de `text`
+    fn error_line(text: &str) -> usize {
+        match SceneFile::parse(text, "test.scene") {
+            Err(Error::Parse { line, .. }) => line,
+            Err(e) => panic!("{}: se esperaba un error de sintaxis, no {}", text, e),
+            Ok(_) => panic!("{}: debería fallar", text),
+        }
+    }
+
+    #[test]
+    fn comments_and_blank_lines_are_skipped() {
+        let scene = parse("# estructuras\n\n  stamp tree 12 0 3   # árbol\n");
+        assert_eq!(scene.stamps.len(), 1);
+        assert_eq!((scene.stamps[0].name.as_str(), scene.stamps[0].position), ("tree", (12, 0, 3)));
+    }
+
+    #[test]
+    fn placements() {
+        let scene = parse(
+            "instance rock -8 0 -6\n\
+             instance tree 1 2 3 30\n\
+             instance bush 1 2 3 30 0.8\n\
+             voxelize pato.obj 16 -10 0 -2\n\
+             voxelize pato.obj 8 0 0 0 pato.png material=Q\n\
+             voxelize pato.obj 8 0 0 0 material=Q pato.png\n\
+             text -6 0 -4 R Pokeball 2025\n\
+             water -0.6 20 -12 0 20\n\
+             box 20.5 -0.5 -10.5 -10.5 -1.5 20.5 B\n\
+             obb 16 0.5 6 1 2 3 45 M\n\
+             emitter smoke 4 12 4",
+        );
+        let yaw_scale: Vec<_> = scene.instances.iter().map(|i| (i.yaw, i.scale)).collect();
+        assert_eq!(yaw_scale, [(0.0, 1.0), (30.0, 1.0), (30.0, 0.8)]);
+        assert_eq!(scene.instances[1].position, Vec3::new(1.0, 2.0, 3.0));
+
+        let [plain, textured, reordered] = &scene.voxelize[..] else { panic!() };
+        assert_eq!((plain.resolution, plain.position), (16, (-10, 0, -2)));
+        assert_eq!((plain.texture.as_deref(), plain.default_symbol), (None, None));
+        for job in [textured, reordered] {
+            assert_eq!((job.texture.as_deref(), job.default_symbol), (Some("pato.png"), Some('Q')));
+        }
+
+        assert_eq!(scene.labels[0].text, "Pokeball 2025");
+        assert_eq!((scene.labels[0].position, scene.labels[0].symbol), ((-6, 0, -4), 'R'));
+        // Las esquinas se ordenan
+        assert_eq!((scene.water[0].min, scene.water[0].max), ((0.0, -12.0), (20.0, 20.0)));
+        assert_eq!(scene.boxes[0].min, Vec3::new(-10.5, -1.5, -10.5));
+        assert_eq!(scene.boxes[0].max, Vec3::new(20.5, -0.5, 20.5));
+        assert_eq!((scene.obbs[0].size, scene.obbs[0].yaw, scene.obbs[0].symbol), (Vec3::new(1.0, 2.0, 3.0), 45.0, 'M'));
+        assert_eq!(scene.emitters[0].kind, "smoke");
+    }
+
+    #[test]
+    fn lights() {
+        let scene = parse(
+            "ambient 0.6 0.7 1.0\n\
+             ambient 0.5 1 0 0.5\n\
+             light 10 6 -8 0.8\n\
+             light 4 2 4 1.5 1 0.9 0.8 noshadow groups=0,3\n\
+             group 1 G y",
+        );
+        // La última `ambient` gana y se multiplica por la intensidad
+        assert_eq!(scene.ambient, Vec3::new(0.25, 0.5, 0.0));
+        let [plain, custom] = &scene.lights[..] else { panic!() };
+        assert_eq!((plain.color, plain.shadows, plain.groups), (Vec3::one(), true, ALL_GROUPS));
+        assert_eq!((custom.position, custom.intensity), (Vec3::new(4.0, 2.0, 4.0), 1.5));
+        assert_eq!((custom.color, custom.shadows, custom.groups), (Vec3::new(1.0, 0.9, 0.8), false, 0b1001));
+        assert_eq!(scene.light_groups, [('G', 0b10), ('y', 0b10)]);
+    }
+
+    #[test]
+    fn sky_and_weather() {
+        let scene = parse("sun -1 0.6 0.8\nclouds 0.5 0.9 0.02\nnight\nfog 0.02\nweather rain\nsnow");
+        assert_eq!(scene.sun, Some(Sun::new(Vec3::new(-1.0, 0.6, 0.8))));
+        assert_eq!(scene.clouds.map(|c| c.scale), Some(Clouds::new(0.5, 0.9, 0.02).scale));
+        assert_eq!(scene.night, Some(Night::new(Vec3::new(0.4, 0.5, -1.0))));
+        assert_eq!(scene.fog, Some(HorizonFog::new(0.02)));
+        assert_eq!((scene.weather, scene.snow), (Some((Precipitation::Rain, 1.0)), Some('W')));
+
+        let scene = parse(
+            "sun -1 0.6 0.8 0.04 60\n\
+             clouds 0.5 0.9 0.02 1.5\n\
+             night 0 1 0 0.1\n\
+             fog 0.02 0 0.2\n\
+             weather snow 1.5\n\
+             snow Q",
+        );
+        let sun = Sun::new(Vec3::new(-1.0, 0.6, 0.8)).with_radius(0.04).with_intensity(60.0);
+        assert_eq!(scene.sun, Some(sun));
+        assert_eq!(scene.clouds.map(|c| c.scale), Some(1.5));
+        assert_eq!(scene.night, Some(Night::new(Vec3::new(0.0, 1.0, 0.0)).with_stars(0.1)));
+        assert_eq!(scene.fog, Some(HorizonFog::new(0.02).with_height(0.0, 0.2)));
+        assert_eq!(parse("fog 0.02 3").fog, Some(HorizonFog::new(0.02).with_height(3.0, 0.15)));
+        assert_eq!((scene.weather, scene.snow), (Some((Precipitation::Snow, 1.5)), Some('Q')));
+    }
+
+    #[test]
+    fn material_directives() {
+        let scene = parse(
+            "flicker G noise 0.25 6\n\
+             dispersion V 1.50 1.52 1.55\n\
+             subsurface Y 1 0.8 0.4 -0.5 0.6\n\
+             film Q 380\n\
+             film R 380 120 1.4\n\
+             aniso S 4 120\n\
+             clearcoat R 0.8\n\
+             clearcoat S 0.8 200 1.6\n\
+             bevel W 0.06\n\
+             partial _ P\n\
+             tile B 2 0.5\n\
+             tile Q 1 1 0.25 0.5\n\
+             tint P 0.08\n\
+             tint N 0.08 -10 0 1.1 1.05 0.8 20 0 0.85 1.0 0.9",
+        );
+        assert_eq!(scene.flickers, [('G', Flicker { amplitude: 0.25, frequency: 6.0, noise: true })]);
+        assert_eq!(scene.dispersions, [('V', [1.50, 1.52, 1.55])]);
+        // La envoltura negativa se recorta a 0
+        let subsurface = Subsurface { color: Vec3::new(1.0, 0.8, 0.4), wrap: 0.0, depth: 0.6 };
+        assert_eq!(scene.subsurfaces, [('Y', subsurface)]);
+        assert_eq!(
+            scene.films,
+            [
+                ('Q', ThinFilm { thickness: 380.0, variation: 0.0, ior: 1.33 }),
+                ('R', ThinFilm { thickness: 380.0, variation: 120.0, ior: 1.4 }),
+            ]
+        );
+        assert_eq!(scene.anisotropies, [('S', Anisotropy { along_u: 4.0, along_v: 120.0 })]);
+        assert_eq!(
+            scene.clearcoats,
+            [
+                ('R', Clearcoat { strength: 0.8, gloss: 150.0, ior: 1.5 }),
+                ('S', Clearcoat { strength: 0.8, gloss: 200.0, ior: 1.6 }),
+            ]
+        );
+        assert_eq!(scene.bevels, [('W', 0.06)]);
+        assert_eq!(scene.partials, [('_', 'P')]);
+        assert_eq!(
+            scene.tiles,
+            [
+                ('B', Tiling { scale: (2.0, 0.5), offset: (0.0, 0.0) }),
+                ('Q', Tiling { scale: (1.0, 1.0), offset: (0.25, 0.5) }),
+            ]
+        );
+        let gradient = Tint::jitter(0.08).with_gradient(
+            (-10.0, 0.0),
+            Vec3::new(1.1, 1.05, 0.8),
+            (20.0, 0.0),
+            Vec3::new(0.85, 1.0, 0.9),
+        );
+        assert_eq!(scene.tints, [('P', Tint::jitter(0.08)), ('N', gradient)]);
+    }
+
+    #[test]
+    fn face_uv_one_face_or_all() {
+        let scene = parse("face_uv W top 1 flip_v\nface_uv Q all 0 flip_u flip_v");
+        let top = UvTransform { quarter_turns: 1, flip_u: false, flip_v: true };
+        assert_eq!(scene.face_uvs[0], ('W', (CubeFace::Top, top)));
+        let flipped = UvTransform { quarter_turns: 0, flip_u: true, flip_v: true };
+        let all: Vec<_> = CubeFace::ALL.iter().map(|face| ('Q', (*face, flipped))).collect();
+        assert_eq!(scene.face_uvs[1..], all[..]);
+    }
+
+    #[test]
+    fn cel_bands_or_thresholds() {
+        // Un solo entero positivo: bandas parejas
+        assert_eq!(parse("cel 3").cel, Some(Cel::even(3)));
+        // Si no, umbrales (ordenados), aunque alguno sea entero
+        assert_eq!(parse("cel 0.75 0.05 0.35").cel, Some(Cel::with_thresholds(vec![0.05, 0.35, 0.75])));
+        assert_eq!(parse("cel 2 0.5").cel, Some(Cel::with_thresholds(vec![0.5, 2.0])));
+        assert_eq!(parse("cel 0").cel, Some(Cel::with_thresholds(vec![0.0])));
+    }
+
+    #[test]
+    fn render_settings() {
+        let scene = parse(
+            "bias 0.0005\n\
+             max_depth 12\n\
+             script scripts/espiral.rhai\n\
+             post vignette 0.35\n\
+             post aberration 1.5\n\
+             post grain 0.04",
+        );
+        assert_eq!((scene.bias, scene.max_depth), (Some(0.0005), Some(12)));
+        assert_eq!(scene.scripts, ["scripts/espiral.rhai"]);
+        assert!(matches!(
+            scene.post[..],
+            [
+                Effect::Vignette { strength: 0.35 },
+                Effect::ChromaticAberration { pixels: 1.5 },
+                Effect::FilmGrain { amount: 0.04 },
+            ]
+        ));
+    }
+
+    #[test]
+    fn malformed_lines_report_their_line() {
+        for line in [
+            "sombra 1 2 3",
+            "stamp castillo 0 0 0",
+            "stamp tree 0 0.5 0",
+            "instance tree 0 0 0 0 1 9",
+            "voxelize pato.obj 0 0 0 0",
+            "voxelize pato.obj 8 0 0 0 material=QQ",
+            "voxelize pato.obj 8 0 0",
+            "text 0 0 0 R",
+            "water 0 0 0 1",
+            "box 0 0 0 1 0 1 B",
+            "box 0 0 0 1 1 1 BB",
+            "obb 0 0 0 1 0 1 0 M",
+            "emitter lava 0 0 0",
+            "ambient 1 1",
+            "light 0 0 0",
+            "light 0 0 0 1 groups=32",
+            "light 0 0 0 1 sombra",
+            "group 1",
+            "group x G",
+            "sun 0 0 0",
+            "sun 0 1 0 0.1 2 3",
+            "clouds 0.5 0.9",
+            "night 0 0 0",
+            "night 0 1",
+            "fog",
+            "weather hail",
+            "weather rain 0",
+            "snow W Q",
+            "flicker G blink 0.2 1",
+            "dispersion V 1.5 0 1.5",
+            "subsurface Y 1 1 1 0.5 0",
+            "film Q",
+            "film Q 380 0 0.9",
+            "aniso S 0 4",
+            "clearcoat R 2",
+            "bevel W 0.6",
+            "partial W Q",
+            "partial _ >",
+            "tile B 0 1",
+            "tile B 1 1 0.5",
+            "face_uv W side 0",
+            "face_uv W top 4",
+            "face_uv W top 1 flip_w",
+            "cel",
+            "cel 0.2 x",
+            "tint P 0.1 1 2",
+            "bias pequeño",
+            "max_depth -1",
+            "script",
+            "post blur 2",
+        ] {
+            assert_eq!(error_line(&format!("bias 0.001\n# comentario\n{}\nbias 0.002", line)), 3, "{}", line);
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::math::Vec3;
+use rhai::{Engine, EvalAltResult, FLOAT, INT};
+
+use crate::error::{Error, Result};
+
+/// Luz pedida por un script: posición e intensidad
+#[derive(Debug, Clone, Copy)]
+pub struct ScriptLight {
+    pub position: Vec3,
+    pub intensity: f32,
+}
+
+/// Material de color sólido definido por un script
+#[derive(Debug, Clone, Copy)]
+pub struct ScriptMaterial {
+    pub symbol: char,
+    pub color: Vec3,
+    pub reflectivity: f32,
+}
+
+/// Todo lo que un script pidió construir
+#[derive(Debug, Clone, Default)]
+pub struct ScriptOutput {
+    pub blocks: Vec<((i32, i32, i32), char)>,
+    pub materials: Vec<ScriptMaterial>,
+    pub light: Option<ScriptLight>,
+}
+
+fn symbol(s: &str) -> std::result::Result<char, Box<EvalAltResult>> {
+    let mut chars = s.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Ok(c.to_ascii_uppercase()),
+        _ => Err(format!("símbolo inválido: {:?}", s).into()),
+    }
+}
+
+/// Ejecuta un script Rhai que genera el diorama proceduralmente.
+///
+/// Funciones disponibles en el script:
+///
+/// ```text
+/// block(x, y, z, "W")                 // un vóxel (coordenadas enteras o reales)
+/// fill(x0, y0, z0, x1, y1, z1, "Q")   // caja de vóxeles, extremos incluidos
+/// material("A", r, g, b)              // material sólido nuevo (0..1)
+/// material("A", r, g, b, reflejo)
+/// light(x, y, z, intensidad)          // reemplaza la luz principal
+/// ```
+pub fn run(path: &str) -> Result<ScriptOutput> {
+    let output = Rc::new(RefCell::new(ScriptOutput::default()));
+    let mut engine = Engine::new();
+
+    let out = Rc::clone(&output);
+    engine.register_fn("block", move |x: INT, y: INT, z: INT, s: &str| {
+        out.borrow_mut().blocks.push(((x as i32, y as i32, z as i32), symbol(s)?));
+        Ok::<_, Box<EvalAltResult>>(())
+    });
+    let out = Rc::clone(&output);
+    engine.register_fn("block", move |x: FLOAT, y: FLOAT, z: FLOAT, s: &str| {
+        let pos = (x.floor() as i32, y.floor() as i32, z.floor() as i32);
+        out.borrow_mut().blocks.push((pos, symbol(s)?));
+        Ok::<_, Box<EvalAltResult>>(())
+    });
+    let out = Rc::clone(&output);
+    engine.register_fn(
+        "fill",
+        move |x0: INT, y0: INT, z0: INT, x1: INT, y1: INT, z1: INT, s: &str| {
+            let c = symbol(s)?;
+            let mut out = out.borrow_mut();
+            for x in x0.min(x1)..=x0.max(x1) {
+                for y in y0.min(y1)..=y0.max(y1) {
+                    for z in z0.min(z1)..=z0.max(z1) {
+                        out.blocks.push(((x as i32, y as i32, z as i32), c));
+                    }
+                }
+            }
+            Ok::<_, Box<EvalAltResult>>(())
+        },
+    );
+    let out = Rc::clone(&output);
+    engine.register_fn("material", move |s: &str, r: FLOAT, g: FLOAT, b: FLOAT| {
+        let color = Vec3::new(r as f32, g as f32, b as f32);
+        out.borrow_mut().materials.push(ScriptMaterial { symbol: symbol(s)?, color, reflectivity: 0.0 });
+        Ok::<_, Box<EvalAltResult>>(())
+    });
+    let out = Rc::clone(&output);
+    engine.register_fn("material", move |s: &str, r: FLOAT, g: FLOAT, b: FLOAT, reflectivity: FLOAT| {
+        let color = Vec3::new(r as f32, g as f32, b as f32);
+        let reflectivity = reflectivity as f32;
+        out.borrow_mut().materials.push(ScriptMaterial { symbol: symbol(s)?, color, reflectivity });
+        Ok::<_, Box<EvalAltResult>>(())
+    });
+    let out = Rc::clone(&output);
+    engine.register_fn("light", move |x: FLOAT, y: FLOAT, z: FLOAT, intensity: FLOAT| {
+        out.borrow_mut().light = Some(ScriptLight {
+            position: Vec3::new(x as f32, y as f32, z as f32),
+            intensity: intensity as f32,
+        });
+    });
+
+    engine
+        .run_file(path.into())
+        .map_err(|e| Error::Script { path: path.to_string(), msg: e.to_string() })?;
+    Ok(output.take())
+}
@@ -0,0 +1,69 @@
+use crate::math::Vec3;
+
+/// Forma de un bloque parcial, armada con una o dos cajas
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockShape {
+    /// Media losa en la mitad inferior de la celda
+    SlabBottom,
+    /// Media losa en la mitad superior de la celda
+    SlabTop,
+    /// Escalón: losa inferior más medio bloque arriba del lado alto,
+    /// indicado como offset en X o Z (p. ej. `(1, 0)` sube hacia +X)
+    Stairs(i32, i32),
+    /// Columna delgada de lado 0.5 y altura completa
+    Pillar,
+}
+
+/// Símbolo de capa dedicado a un bloque parcial hecho del material `material`
+#[derive(Debug, Clone, Copy)]
+pub struct PartialBlock {
+    pub symbol: char,
+    pub shape: BlockShape,
+    pub material: char,
+}
+
+/// Bloques parciales disponibles en las capas. Por defecto son de cuarzo
+/// ('Q') para techos y escalones; la directiva `partial` de la escena les
+/// da otro material. Los símbolos no chocan con los de material.
+pub const PARTIAL_BLOCKS: [PartialBlock; 7] = [
+    PartialBlock { symbol: '_', shape: BlockShape::SlabBottom, material: 'Q' },
+    PartialBlock { symbol: '=', shape: BlockShape::SlabTop, material: 'Q' },
+    PartialBlock { symbol: '>', shape: BlockShape::Stairs(1, 0), material: 'Q' },
+    PartialBlock { symbol: '<', shape: BlockShape::Stairs(-1, 0), material: 'Q' },
+    PartialBlock { symbol: '}', shape: BlockShape::Stairs(0, 1), material: 'Q' },
+    PartialBlock { symbol: '{', shape: BlockShape::Stairs(0, -1), material: 'Q' },
+    PartialBlock { symbol: '|', shape: BlockShape::Pillar, material: 'Q' },
+];
+
+/// Busca el bloque parcial de un símbolo de capa
+pub fn find(symbol: char) -> Option<&'static PartialBlock> {
+    PARTIAL_BLOCKS.iter().find(|b| b.symbol == symbol)
+}
+
+impl BlockShape {
+    /// Cajas (mínimo, máximo) que forman la forma en la celda centrada en `center`
+    pub fn boxes(&self, center: Vec3) -> Vec<(Vec3, Vec3)> {
+        let min = center - Vec3::new(0.5, 0.5, 0.5);
+        let max = center + Vec3::new(0.5, 0.5, 0.5);
+        match *self {
+            BlockShape::SlabBottom => vec![(min, Vec3::new(max.x, center.y, max.z))],
+            BlockShape::SlabTop => vec![(Vec3::new(min.x, center.y, min.z), max)],
+            BlockShape::Stairs(dx, dz) => {
+                let slab = (min, Vec3::new(max.x, center.y, max.z));
+                let mut step_min = Vec3::new(min.x, center.y, min.z);
+                let mut step_max = max;
+                match (dx.signum(), dz.signum()) {
+                    (1, _) => step_min.x = center.x,
+                    (-1, _) => step_max.x = center.x,
+                    (_, 1) => step_min.z = center.z,
+                    _ => step_max.z = center.z,
+                }
+                vec![slab, (step_min, step_max)]
+            }
+            BlockShape::Pillar => {
+                let r = Vec3::new(0.25, 0.5, 0.25);
+                vec![(center - r, center + r)]
+            }
+        }
+    }
+}
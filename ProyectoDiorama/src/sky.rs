@@ -0,0 +1,310 @@
+use crate::math::Vec3;
+use crate::procedural_sky;
+use crate::rng::Rng;
+use crate::terrain::Perlin;
+
+/// Sol del cielo: un disco muy brillante con un halo suave alrededor. Es
+/// solo lo que se ve (en el fondo y en los reflejos); la luz que proyecta
+/// sombras sigue siendo `scene.light`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sun {
+    /// Dirección hacia el sol (normalizada)
+    pub direction: Vec3,
+    /// Radio angular del disco, en radianes
+    pub radius: f32,
+    /// Radiancia del disco; muy por encima de 1 para que los reflejos
+    /// en materiales pulidos salgan como un brillo caliente
+    pub intensity: f32,
+    pub color: Vec3,
+}
+
+impl Sun {
+    /// Radio angular por defecto: un poco más grande que el sol real para
+    /// que el disco se vea a resoluciones de ventana
+    pub const RADIUS: f32 = 0.03;
+
+    pub fn new(direction: Vec3) -> Self {
+        Sun {
+            direction: direction.normalized(),
+            radius: Self::RADIUS,
+            intensity: 40.0,
+            color: Vec3::new(1.0, 0.95, 0.85),
+        }
+    }
+
+    pub fn with_radius(mut self, radius: f32) -> Self {
+        self.radius = radius;
+        self
+    }
+
+    pub fn with_intensity(mut self, intensity: f32) -> Self {
+        self.intensity = intensity;
+        self
+    }
+
+    /// Resplandor alrededor del disco, que cae con la distancia angular
+    fn halo(&self, dir: Vec3) -> Vec3 {
+        let angle = dir.dot(self.direction).clamp(-1.0, 1.0).acos();
+        let outside = (angle - self.radius).max(0.0) / self.radius;
+        self.color * (0.8 * (-outside * 0.35).exp() + 0.15 * (-outside * 0.04).exp())
+    }
+
+    /// El disco en sí, con el borde suavizado en un 10 % del radio
+    fn disk(&self, dir: Vec3) -> Vec3 {
+        let angle = dir.dot(self.direction).clamp(-1.0, 1.0).acos();
+        let edge = ((self.radius - angle) / (self.radius * 0.1)).clamp(0.0, 1.0);
+        self.color * (self.intensity * edge)
+    }
+}
+
+/// Capa de nubes: ruido fractal 2D proyectado sobre una cúpula a altura
+/// fija, que el viento desplaza con el tiempo
+#[derive(Debug, Clone)]
+pub struct Clouds {
+    /// Fracción del cielo cubierta (0 = despejado, 1 = cubierto)
+    pub coverage: f32,
+    /// Opacidad máxima de las nubes
+    pub density: f32,
+    /// Velocidad del viento, en unidades de la cúpula por segundo
+    pub speed: f32,
+    /// Tamaño de las nubes; más grande, menos nubes y más anchas
+    pub scale: f32,
+    noise: Perlin,
+}
+
+impl Clouds {
+    pub fn new(coverage: f32, density: f32, speed: f32) -> Self {
+        Clouds {
+            coverage: coverage.clamp(0.0, 1.0),
+            density: density.clamp(0.0, 1.0),
+            speed,
+            scale: 1.0,
+            noise: Perlin::new(0xC10D),
+        }
+    }
+
+    pub fn with_scale(mut self, scale: f32) -> Self {
+        self.scale = scale.max(1e-3);
+        self
+    }
+
+    /// Opacidad de las nubes en la dirección `d` (normalizada) en `time`
+    fn opacity(&self, d: Vec3, time: f32) -> f32 {
+        if d.y <= 0.0 || self.coverage <= 0.0 {
+            return 0.0;
+        }
+        // Punto de la cúpula: cerca del horizonte las nubes se comprimen
+        let dome = 1.0 / (d.y + 0.15);
+        let drift = time * self.speed;
+        let x = d.x * dome / self.scale + drift;
+        let z = d.z * dome / self.scale + drift * 0.3;
+        let n = self.noise.fbm2(x * 1.5, z * 1.5, 5) * 0.5 + 0.5;
+
+        let threshold = 1.0 - self.coverage;
+        let t = ((n - threshold) / 0.25).clamp(0.0, 1.0);
+        let shape = t * t * (3.0 - 2.0 * t);
+        // Se desvanecen hacia el horizonte para no cortar el degradado
+        let horizon = (d.y / 0.12).clamp(0.0, 1.0);
+        shape * self.density * horizon
+    }
+}
+
+/// Cielo nocturno: degradado oscuro, estrellas y el disco de la luna
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Night {
+    /// Dirección hacia la luna (normalizada)
+    pub moon: Vec3,
+    /// Fracción de celdas de la bóveda con una estrella (0..1)
+    pub stars: f32,
+}
+
+impl Night {
+    /// Celdas por unidad de dirección en que se reparte el campo de estrellas
+    const STAR_GRID: f32 = 90.0;
+    const MOON_RADIUS: f32 = 0.035;
+
+    pub fn new(moon: Vec3) -> Self {
+        Night { moon: moon.normalized(), stars: 0.08 }
+    }
+
+    pub fn with_stars(mut self, stars: f32) -> Self {
+        self.stars = stars.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Degradado nocturno con las estrellas y el resplandor de la luna
+    fn background(&self, d: Vec3) -> Vec3 {
+        if d.y < 0.0 {
+            return Vec3::new(0.01, 0.02, 0.015);
+        }
+        let horizon = Vec3::new(0.05, 0.07, 0.12);
+        let zenith = Vec3::new(0.005, 0.01, 0.03);
+        let k = d.y.sqrt();
+        let angle = d.dot(self.moon).clamp(-1.0, 1.0).acos();
+        let glow = Vec3::new(0.6, 0.65, 0.8) * (0.12 * (-(angle / Self::MOON_RADIUS - 1.0).max(0.0) * 0.25).exp());
+        horizon * (1.0 - k) + zenith * k + glow + Vec3::one() * self.star(d)
+    }
+
+    /// Brillo de la estrella de la celda de `d`, si tiene una: cada celda
+    /// decide con un hash si hay estrella, dónde cae y cuánto brilla
+    fn star(&self, d: Vec3) -> f32 {
+        let p = d * Self::STAR_GRID;
+        let cell = Vec3::new(p.x.floor(), p.y.floor(), p.z.floor());
+        let mut rng = Rng::at_point(0x57A2, cell);
+        if rng.next_f32() >= self.stars {
+            return 0.0;
+        }
+        let jitter = Vec3::new(rng.next_f32(), rng.next_f32(), rng.next_f32());
+        let position = ((cell + jitter) / Self::STAR_GRID).normalized();
+        let magnitude = rng.next_f32().powi(3) * 4.0 + 0.3;
+        let size = 1.2 / Self::STAR_GRID;
+        let falloff = (1.0 - (d - position).length() / size).max(0.0);
+        // Se apagan cerca del horizonte, donde el aire es más espeso
+        falloff * falloff * magnitude * (d.y / 0.1).clamp(0.0, 1.0)
+    }
+
+    fn moon_disk(&self, d: Vec3) -> Vec3 {
+        let angle = d.dot(self.moon).clamp(-1.0, 1.0).acos();
+        let edge = ((Self::MOON_RADIUS - angle) / (Self::MOON_RADIUS * 0.1)).clamp(0.0, 1.0);
+        Vec3::new(0.9, 0.92, 1.0) * (3.0 * edge)
+    }
+}
+
+/// Cielo de fondo de la escena: el degradado de `procedural_sky` más los
+/// elementos que defina la escena
+#[derive(Debug, Clone, Default)]
+pub struct Sky {
+    pub sun: Option<Sun>,
+    pub clouds: Option<Clouds>,
+    /// Versión nocturna del cielo. Con sol, se pasa de una a otra según su
+    /// altura sobre el horizonte; sin sol, es siempre de noche
+    pub night: Option<Night>,
+}
+
+/// Cielo sin extras, el de siempre
+pub const PLAIN: Sky = Sky { sun: None, clouds: None, night: None };
+
+impl Sky {
+    pub fn with_sun(mut self, sun: Sun) -> Self {
+        self.sun = Some(sun);
+        self
+    }
+
+    pub fn with_clouds(mut self, clouds: Clouds) -> Self {
+        self.clouds = Some(clouds);
+        self
+    }
+
+    pub fn with_night(mut self, night: Night) -> Self {
+        self.night = Some(night);
+        self
+    }
+
+    /// Cuánto es de noche: 0 = día, 1 = noche cerrada. Con sol y noche, el
+    /// cambio ocurre mientras el sol cruza el horizonte
+    pub fn darkness(&self) -> f32 {
+        match (self.night, self.sun) {
+            (None, _) => 0.0,
+            (Some(_), None) => 1.0,
+            (Some(_), Some(sun)) => {
+                let t = ((0.1 - sun.direction.y) / 0.2).clamp(0.0, 1.0);
+                t * t * (3.0 - 2.0 * t)
+            }
+        }
+    }
+
+    /// Color del cielo en la dirección `dir` en el instante `time`, tal
+    /// como lo ve un rayo
+    pub fn color(&self, dir: Vec3, time: f32) -> Vec3 {
+        let d = dir.normalized();
+        let (background, cover) = self.layers(d, time);
+        let darkness = self.darkness();
+        let sun = self.sun.map_or(Vec3::zero(), |sun| sun.disk(d) * (1.0 - darkness));
+        let moon = self.night.map_or(Vec3::zero(), |night| night.moon_disk(d) * darkness);
+        background + (sun + moon) * (1.0 - cover)
+    }
+
+    /// Color del cielo justo sobre el horizonte en la dirección de `dir`, el
+    /// que toma la niebla; se mira un poco arriba de y = 0, donde está la
+    /// banda clara del degradado
+    pub fn horizon(&self, dir: Vec3, time: f32) -> Vec3 {
+        let flat = Vec3::new(dir.x, 0.0, dir.z).normalized();
+        self.background(Vec3::new(flat.x, 0.09, flat.z), time)
+    }
+
+    /// El cielo sin el disco del sol: es lo que se integra como luz difusa
+    /// del cielo, donde un disco tan chico y brillante solo metería ruido
+    pub fn background(&self, dir: Vec3, time: f32) -> Vec3 {
+        self.layers(dir.normalized(), time).0
+    }
+
+    /// Degradado, halo y nubes en `d`, junto con la opacidad de las nubes
+    fn layers(&self, d: Vec3, time: f32) -> (Vec3, f32) {
+        let darkness = self.darkness();
+        let halo = self.sun.map_or(Vec3::zero(), |sun| sun.halo(d)) * (1.0 - darkness);
+        let mut clear = procedural_sky(d) + halo;
+        if let Some(night) = &self.night
+            && darkness > 0.0
+        {
+            clear = clear * (1.0 - darkness) + night.background(d) * darkness;
+        }
+        let cover = self.clouds.as_ref().map_or(0.0, |c| c.opacity(d, time));
+        if cover <= 0.0 {
+            return (clear, 0.0);
+        }
+        // Base gris y bordes más claros del lado del sol
+        let lit = self.sun.map_or(0.0, |sun| d.dot(sun.direction).max(0.0).powi(8));
+        let cloud = (Vec3::new(0.78, 0.8, 0.85) * (1.0 - 0.35 * cover) + Vec3::one() * (0.4 * lit))
+            * (1.0 - 0.9 * darkness)
+            + halo * 0.5;
+        (clear * (1.0 - cover) + cloud * cover, cover)
+    }
+}
+
+/// Niebla que funde la geometría lejana con el horizonte del cielo. Es
+/// más espesa abajo: la densidad cae exponencialmente por encima de
+/// `height`, así que los rayos que suben atraviesan menos niebla.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HorizonFog {
+    /// Densidad a la altura de referencia (por unidad de distancia)
+    pub density: f32,
+    /// Altura de referencia, donde la densidad vale `density`
+    pub height: f32,
+    /// Qué tan rápido se afina la niebla al subir (por unidad de altura)
+    pub falloff: f32,
+}
+
+impl HorizonFog {
+    pub fn new(density: f32) -> Self {
+        HorizonFog { density: density.max(0.0), height: 0.0, falloff: 0.15 }
+    }
+
+    pub fn with_height(mut self, height: f32, falloff: f32) -> Self {
+        self.height = height;
+        self.falloff = falloff.max(0.0);
+        self
+    }
+
+    /// Fracción (0..1) de niebla entre `origin` y `distance` a lo largo de
+    /// `dir`: la integral de la densidad exponencial en altura, cerrada
+    pub fn amount(&self, origin: Vec3, dir: Vec3, distance: f32) -> f32 {
+        let base = self.density * (-self.falloff * (origin.y - self.height)).exp();
+        let rise = self.falloff * dir.y * distance;
+        let depth = if rise.abs() < 1e-4 {
+            base * distance
+        } else {
+            base * distance * (1.0 - (-rise).exp()) / rise
+        };
+        1.0 - (-depth).exp()
+    }
+
+    /// `color` visto a `distance` de `origin`, mezclado con el horizonte de
+    /// `sky`
+    pub fn apply(&self, color: Vec3, origin: Vec3, dir: Vec3, distance: f32, sky: &Sky, time: f32) -> Vec3 {
+        let amount = self.amount(origin, dir, distance);
+        if amount <= 0.0 {
+            return color;
+        }
+        color * (1.0 - amount) + sky.horizon(dir, time) * amount
+    }
+}
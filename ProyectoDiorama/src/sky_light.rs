@@ -0,0 +1,77 @@
+use std::f32::consts::PI;
+use crate::math::Vec3;
+
+/// Base de armónicos esféricos de orden 2 (9 coeficientes) evaluada en `d`
+fn sh_basis(d: Vec3) -> [f32; 9] {
+    let (x, y, z) = (d.x, d.y, d.z);
+    [
+        0.282_095,
+        0.488_603 * y,
+        0.488_603 * z,
+        0.488_603 * x,
+        1.092_548 * x * y,
+        1.092_548 * y * z,
+        0.315_392 * (3.0 * z * z - 1.0),
+        1.092_548 * x * z,
+        0.546_274 * (x * x - y * y),
+    ]
+}
+
+/// Convolución coseno por banda (Ramamoorthi & Hanrahan)
+const BAND_FACTORS: [f32; 9] = [
+    PI,
+    2.0 * PI / 3.0,
+    2.0 * PI / 3.0,
+    2.0 * PI / 3.0,
+    PI / 4.0,
+    PI / 4.0,
+    PI / 4.0,
+    PI / 4.0,
+    PI / 4.0,
+];
+
+/// Iluminación difusa del cielo pre-filtrada: el cielo se proyecta una sola
+/// vez a armónicos esféricos y la irradiancia para cualquier normal cuesta
+/// nueve multiplicaciones por punto sombreado
+#[derive(Debug, Clone)]
+pub struct SkyIrradiance {
+    coefficients: [Vec3; 9],
+    /// Multiplicador de la contribución del cielo
+    pub strength: f32,
+}
+
+impl SkyIrradiance {
+    /// Proyecta `sky` muestreando la esfera en una grilla uniforme en ángulo sólido
+    pub fn from_sky<F: Fn(Vec3) -> Vec3>(sky: F, strength: f32) -> Self {
+        const THETA_STEPS: usize = 64;
+        const PHI_STEPS: usize = 128;
+        let weight = 4.0 * PI / (THETA_STEPS * PHI_STEPS) as f32;
+
+        let mut coefficients = [Vec3::zero(); 9];
+        for i in 0..THETA_STEPS {
+            let cos_theta = 1.0 - 2.0 * (i as f32 + 0.5) / THETA_STEPS as f32;
+            let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+            for j in 0..PHI_STEPS {
+                let phi = 2.0 * PI * (j as f32 + 0.5) / PHI_STEPS as f32;
+                let dir = Vec3::new(sin_theta * phi.cos(), cos_theta, sin_theta * phi.sin());
+                let radiance = sky(dir);
+                for (c, y) in coefficients.iter_mut().zip(sh_basis(dir)) {
+                    *c += radiance * (y * weight);
+                }
+            }
+        }
+
+        SkyIrradiance { coefficients, strength }
+    }
+
+    /// Radiancia difusa que aporta el cielo a una superficie con normal `n`
+    /// (irradiancia / π, en las mismas unidades que el término difuso)
+    pub fn diffuse(&self, n: Vec3) -> Vec3 {
+        let mut e = Vec3::zero();
+        for ((c, y), a) in self.coefficients.iter().zip(sh_basis(n)).zip(BAND_FACTORS) {
+            e += *c * (y * a);
+        }
+        let e = Vec3::new(e.x.max(0.0), e.y.max(0.0), e.z.max(0.0));
+        e * (self.strength / PI)
+    }
+}
@@ -0,0 +1,178 @@
+use std::sync::Arc;
+use crate::math::Vec3;
+
+use crate::geometry::Aabb;
+use crate::material::Material;
+use crate::ray_intersect::{CubeFace, Intersect, RayIntersect};
+use crate::textures::Texture;
+
+/// Planta o adorno dibujado como sprite en cruz. El dibujo es una grilla de
+/// caracteres (de arriba hacia abajo); '.' es transparente y el resto se
+/// busca en la paleta.
+pub struct SpriteArt {
+    pub symbol: char,
+    pub name: &'static str,
+    pub pixels: &'static [&'static str],
+    pub palette: &'static [(char, [u8; 3])],
+}
+
+pub const FLOWER: SpriteArt = SpriteArt {
+    symbol: '*',
+    name: "flor",
+    pixels: &[
+        "..rrr...",
+        ".rryrr..",
+        "..rrr...",
+        "...g....",
+        "...g.g..",
+        ".g.gg...",
+        "..gg....",
+        "...g....",
+    ],
+    palette: &[('r', [200, 30, 40]), ('y', [250, 220, 60]), ('g', [60, 150, 50])],
+};
+
+pub const GRASS_TUFT: SpriteArt = SpriteArt {
+    symbol: '"',
+    name: "pasto",
+    pixels: &[
+        "........",
+        ".g....g.",
+        ".g..g.g.",
+        "gg.g..g.",
+        ".g.g.gg.",
+        ".gggg.g.",
+        "..ggggg.",
+        "..gggg..",
+    ],
+    palette: &[('g', [70, 160, 55])],
+};
+
+pub const TORCH: SpriteArt = SpriteArt {
+    symbol: '!',
+    name: "antorcha",
+    pixels: &[
+        "...yy...",
+        "..yooy..",
+        "...oo...",
+        "...bb...",
+        "...bb...",
+        "...bb...",
+        "...bb...",
+        "...bb...",
+    ],
+    palette: &[('y', [250, 220, 60]), ('o', [255, 140, 20]), ('b', [110, 75, 40])],
+};
+
+pub const ALL: [&SpriteArt; 3] = [&FLOWER, &GRASS_TUFT, &TORCH];
+
+impl SpriteArt {
+    /// Textura RGBA del dibujo, con alpha 0 en las celdas transparentes
+    pub fn texture(&self) -> Texture {
+        let height = self.pixels.len() as u32;
+        let width = self.pixels.iter().map(|row| row.len()).max().unwrap_or(0) as u32;
+        let mut data = vec![0u8; (width * height * 4) as usize];
+        for (y, row) in self.pixels.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                if let Some((_, rgb)) = self.palette.iter().find(|(p, _)| *p == c) {
+                    let idx = (y * width as usize + x) * 4;
+                    data[idx..idx + 4].copy_from_slice(&[rgb[0], rgb[1], rgb[2], 255]);
+                }
+            }
+        }
+        Texture {
+            path: format!("<sprite {}>", self.name),
+            width,
+            height,
+            data,
+        }
+    }
+}
+
+/// Mitad del largo de cada plano: va de esquina a esquina de la celda
+const HALF_DIAGONAL: f32 = std::f32::consts::FRAC_1_SQRT_2;
+
+/// Dos planos verticales texturizados cruzados en diagonal dentro de una
+/// celda, visibles por ambos lados. Los texels transparentes no cuentan
+/// como impacto, así que el rayo (y la sombra) pasan a través.
+pub struct CrossSprite {
+    pub center: Vec3,
+    pub material: Material,
+    mask: Arc<Texture>,
+}
+
+impl CrossSprite {
+    pub fn new(center: Vec3, material: Material, mask: Arc<Texture>) -> Self {
+        CrossSprite { center, material, mask }
+    }
+
+    fn is_opaque(&self, u: f32, v: f32) -> bool {
+        let tex = &self.mask;
+        let x = ((u * tex.width as f32) as u32).min(tex.width.saturating_sub(1));
+        let y = (((1.0 - v) * tex.height as f32) as u32).min(tex.height.saturating_sub(1));
+        let idx = ((y * tex.width + x) * 4 + 3) as usize;
+        tex.data.get(idx).is_some_and(|a| *a >= 128)
+    }
+}
+
+impl RayIntersect for CrossSprite {
+    fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Intersect {
+        let mut best = Intersect::empty();
+        let diagonals = [
+            Vec3::new(HALF_DIAGONAL, 0.0, HALF_DIAGONAL),
+            Vec3::new(HALF_DIAGONAL, 0.0, -HALF_DIAGONAL),
+        ];
+
+        for tangent in diagonals {
+            let normal = Vec3::new(tangent.z, 0.0, -tangent.x);
+            let denom = ray_direction.dot(normal);
+            if denom.abs() < 1e-6 {
+                continue;
+            }
+            let t = (self.center - *ray_origin).dot(normal) / denom;
+            if t <= 0.0 || (best.is_intersecting && t >= best.distance) {
+                continue;
+            }
+
+            let point = *ray_origin + *ray_direction * t;
+            let local = point - self.center;
+            let along = local.dot(tangent);
+            if along.abs() > HALF_DIAGONAL || local.y.abs() > 0.5 {
+                continue;
+            }
+
+            let u = along / (2.0 * HALF_DIAGONAL) + 0.5;
+            let v = local.y + 0.5;
+            if !self.is_opaque(u, v) {
+                continue;
+            }
+
+            // Doble cara: la normal siempre mira hacia el rayo
+            let facing = if denom > 0.0 { -normal } else { normal };
+            best = Intersect::new(point, facing, t, self.material.clone(), u, v, CubeFace::Front);
+        }
+
+        best
+    }
+
+    fn bounds(&self) -> Option<Aabb> {
+        let half = Vec3::new(HALF_DIAGONAL, 0.5, HALF_DIAGONAL);
+        Some(Aabb::new(self.center - half, self.center + half))
+    }
+}
+
+/// Sprites de las capas del diorama (mismo formato que `VoxelWorld::from_layers`)
+pub fn from_layers(layers: &[Vec<&str>], materials: &[(char, Material, Arc<Texture>)]) -> Vec<CrossSprite> {
+    let mut sprites = Vec::new();
+    for (y, layer) in layers.iter().enumerate() {
+        for (z, row) in layer.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                if let Some((_, material, mask)) = materials.iter().find(|(s, _, _)| *s == c) {
+                    let center = Vec3::new(x as f32, y as f32, z as f32);
+                    sprites.push(CrossSprite::new(center, material.clone(), Arc::clone(mask)));
+                }
+            }
+        }
+    }
+    sprites
+}
@@ -0,0 +1,71 @@
+use crate::voxel_world::VoxelWorld;
+
+/// Símbolos de material usados por las estructuras
+pub const WOOD: char = 'M';
+pub const LEAVES: char = 'H';
+
+/// Estructura prefabricada definida como capas (igual que el diorama):
+/// una capa por altura de abajo hacia arriba, filas en `z`, columnas en `x`.
+/// '0' y '.' son celdas vacías.
+pub struct Stamp {
+    pub name: &'static str,
+    pub layers: &'static [&'static [&'static str]],
+}
+
+pub const TREE: Stamp = Stamp {
+    name: "tree",
+    layers: &[
+        &["00000", "00000", "00M00", "00000", "00000"],
+        &["00000", "00000", "00M00", "00000", "00000"],
+        &["00000", "00000", "00M00", "00000", "00000"],
+        &["HHHHH", "HHHHH", "HHMHH", "HHHHH", "HHHHH"],
+        &["0HHH0", "HHHHH", "HHMHH", "HHHHH", "0HHH0"],
+        &["00000", "0HHH0", "0HHH0", "0HHH0", "00000"],
+        &["00000", "00H00", "0HHH0", "00H00", "00000"],
+    ],
+};
+
+pub const ROCK: Stamp = Stamp {
+    name: "rock",
+    layers: &[
+        &["0EE0", "EEEE", "EEEE", "0EE0"],
+        &["00E0", "0EEE", "EEE0", "0E00"],
+        &["0000", "0EE0", "0E00", "0000"],
+    ],
+};
+
+pub const BUSH: Stamp = Stamp {
+    name: "bush",
+    layers: &[
+        &["0H0", "HHH", "0H0"],
+        &["000", "0H0", "000"],
+    ],
+};
+
+pub const ALL: [&Stamp; 3] = [&TREE, &ROCK, &BUSH];
+
+/// Busca una estructura por nombre
+pub fn find(name: &str) -> Option<&'static Stamp> {
+    ALL.iter().copied().find(|s| s.name == name)
+}
+
+impl Stamp {
+    /// Estampa la estructura con su esquina mínima en `origin`,
+    /// sobreescribiendo lo que haya. Devuelve los vóxeles colocados.
+    pub fn apply(&self, world: &mut VoxelWorld, origin: (i32, i32, i32)) -> usize {
+        let (ox, oy, oz) = origin;
+        let mut placed = 0;
+        for (y, layer) in self.layers.iter().enumerate() {
+            for (z, row) in layer.iter().enumerate() {
+                for (x, c) in row.chars().enumerate() {
+                    if c == '0' || c == '.' {
+                        continue;
+                    }
+                    world.set((ox + x as i32, oy + y as i32, oz + z as i32), c.to_ascii_uppercase());
+                    placed += 1;
+                }
+            }
+        }
+        placed
+    }
+}
@@ -0,0 +1,59 @@
+use std::fmt;
+
+use crate::scene::Scene;
+
+/// Resumen de lo que hay que trazar en una escena, para razonar sobre el
+/// costo de un cuadro y el efecto de las optimizaciones
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SceneStats {
+    /// Objetos de primer nivel en `Scene::objects`
+    pub objects: usize,
+    /// Vóxeles del mundo antes de convertirlos en cubos (si se conocen)
+    pub voxels: Option<usize>,
+    /// Primitivas que recorre un rayo (cubos, planos, sprites, incluidas las
+    /// de nodos e instancias)
+    pub primitives: usize,
+    pub textures: usize,
+    pub texture_bytes: usize,
+    /// Capas de `SceneBounds` que se prueban antes que sus objetos; `None`
+    /// si la escena se recorre entera en cada rayo
+    pub layers: Option<usize>,
+    /// Estimación de memoria de geometría + texturas
+    pub memory_bytes: usize,
+}
+
+impl Scene<'_> {
+    /// Cuenta objetos, primitivas y texturas de la escena
+    pub fn stats(&self) -> SceneStats {
+        let texture_bytes = self.tm.images.values().map(|t| t.data.len()).sum();
+        let geometry_bytes: usize = self.objects.iter().map(|o| o.memory_bytes()).sum();
+        SceneStats {
+            objects: self.objects.len(),
+            voxels: None,
+            primitives: self.objects.iter().map(|o| o.primitive_count()).sum(),
+            textures: self.tm.images.len(),
+            texture_bytes,
+            layers: self.bounds.map(|b| b.layer_count()),
+            memory_bytes: geometry_bytes + texture_bytes,
+        }
+    }
+}
+
+fn mib(bytes: usize) -> f64 {
+    bytes as f64 / (1024.0 * 1024.0)
+}
+
+impl fmt::Display for SceneStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.voxels {
+            Some(voxels) => writeln!(f, "Geometría: {} vóxeles → {} primitivas en {} objetos", voxels, self.primitives, self.objects)?,
+            None => writeln!(f, "Geometría: {} primitivas en {} objetos", self.primitives, self.objects)?,
+        }
+        writeln!(f, "Texturas: {} ({:.1} MiB)", self.textures, mib(self.texture_bytes))?;
+        match self.layers {
+            Some(layers) => writeln!(f, "Aceleración: {} capas con caja", layers)?,
+            None => writeln!(f, "Aceleración: no hay (recorrido lineal)")?,
+        }
+        write!(f, "Memoria estimada: {:.1} MiB", mib(self.memory_bytes))
+    }
+}
@@ -0,0 +1,164 @@
+use crate::voxel_world::VoxelWorld;
+
+/// Símbolos de material del terreno procedural
+pub const GRASS: char = 'P';
+pub const DIRT: char = 'T';
+pub const STONE: char = 'E';
+pub const WATER: char = 'A';
+
+/// Ruido de Perlin 2D (versión "improved" de Ken Perlin) con tabla de
+/// permutación barajada a partir de una semilla
+#[derive(Debug, Clone)]
+pub struct Perlin {
+    perm: [u8; 512],
+}
+
+impl Perlin {
+    pub fn new(seed: u64) -> Self {
+        let mut p: Vec<u8> = (0..=255).collect();
+
+        // xorshift64* para barajar de forma reproducible
+        let mut state = seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1;
+        for i in (1..256).rev() {
+            state ^= state >> 12;
+            state ^= state << 25;
+            state ^= state >> 27;
+            let r = state.wrapping_mul(0x2545_F491_4F6C_DD1D);
+            p.swap(i, (r % (i as u64 + 1)) as usize);
+        }
+
+        let mut perm = [0u8; 512];
+        for (i, slot) in perm.iter_mut().enumerate() {
+            *slot = p[i & 255];
+        }
+        Perlin { perm }
+    }
+
+    fn grad(hash: u8, x: f32, y: f32) -> f32 {
+        match hash & 7 {
+            0 => x + y,
+            1 => -x + y,
+            2 => x - y,
+            3 => -x - y,
+            4 => x,
+            5 => -x,
+            6 => y,
+            _ => -y,
+        }
+    }
+
+    /// Ruido en aproximadamente [-1, 1]
+    pub fn noise2(&self, x: f32, y: f32) -> f32 {
+        let fade = |t: f32| t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+        let lerp = |a: f32, b: f32, t: f32| a + (b - a) * t;
+
+        let xi = (x.floor() as i32 & 255) as usize;
+        let yi = (y.floor() as i32 & 255) as usize;
+        let xf = x - x.floor();
+        let yf = y - y.floor();
+        let (u, v) = (fade(xf), fade(yf));
+
+        let p = &self.perm;
+        let aa = p[p[xi] as usize + yi];
+        let ab = p[p[xi] as usize + yi + 1];
+        let ba = p[p[xi + 1] as usize + yi];
+        let bb = p[p[xi + 1] as usize + yi + 1];
+
+        let x1 = lerp(Self::grad(aa, xf, yf), Self::grad(ba, xf - 1.0, yf), u);
+        let x2 = lerp(Self::grad(ab, xf, yf - 1.0), Self::grad(bb, xf - 1.0, yf - 1.0), u);
+        lerp(x1, x2, v)
+    }
+
+    /// Suma de octavas (fractal Brownian motion), normalizada a ~[-1, 1]
+    pub fn fbm2(&self, x: f32, y: f32, octaves: u32) -> f32 {
+        let (mut sum, mut amp, mut freq, mut norm) = (0.0, 1.0, 1.0, 0.0);
+        for _ in 0..octaves {
+            sum += self.noise2(x * freq, y * freq) * amp;
+            norm += amp;
+            amp *= 0.5;
+            freq *= 2.0;
+        }
+        sum / norm.max(1e-6)
+    }
+}
+
+/// Parámetros del terreno procedural
+#[derive(Debug, Clone)]
+pub struct TerrainOptions {
+    /// Lado del terreno en vóxeles, centrado en `center`
+    pub size: i32,
+    pub seed: u64,
+    /// Centro (x, z) del terreno; normalmente el centro del diorama
+    pub center: (i32, i32),
+    /// Radio alrededor del centro que queda plano para apoyar el diorama
+    pub flat_radius: f32,
+    /// Variación máxima de altura sobre/bajo el nivel del suelo (y = -1)
+    pub amplitude: f32,
+    /// Frecuencia del ruido (vóxeles por período = 1 / scale)
+    pub scale: f32,
+    /// Profundidad de las columnas bajo el suelo
+    pub depth: i32,
+    /// Altura del agua; las columnas más bajas quedan sumergidas
+    pub water_level: i32,
+}
+
+impl Default for TerrainOptions {
+    fn default() -> Self {
+        TerrainOptions {
+            size: 48,
+            seed: 1,
+            center: (5, 5),
+            flat_radius: 7.0,
+            amplitude: 4.0,
+            scale: 0.06,
+            depth: 4,
+            water_level: -2,
+        }
+    }
+}
+
+/// Genera un paisaje de pasto/tierra/piedra/agua alrededor del diorama.
+/// No sobreescribe celdas ocupadas. Devuelve la cantidad de vóxeles colocados.
+pub fn generate_terrain(world: &mut VoxelWorld, options: &TerrainOptions) -> usize {
+    let perlin = Perlin::new(options.seed);
+    let half = options.size / 2;
+    let (cx, cz) = options.center;
+    let ground = -1;
+    let mut placed = 0;
+
+    let mut place = |world: &mut VoxelWorld, pos: (i32, i32, i32), symbol: char| {
+        if !world.is_solid(pos) {
+            world.set(pos, symbol);
+            placed += 1;
+        }
+    };
+
+    for z in (cz - half)..(cz + half) {
+        for x in (cx - half)..(cx + half) {
+            // El ruido entra suavemente fuera del radio plano
+            let dist = (((x - cx) * (x - cx) + (z - cz) * (z - cz)) as f32).sqrt();
+            let t = ((dist - options.flat_radius) / 4.0).clamp(0.0, 1.0);
+            let weight = t * t * (3.0 - 2.0 * t);
+
+            let n = perlin.fbm2(x as f32 * options.scale, z as f32 * options.scale, 4);
+            let top = ground + (n * options.amplitude * weight).round() as i32;
+            let bottom = ground - options.depth;
+
+            for y in bottom..=top {
+                let symbol = if y == top && top >= options.water_level {
+                    GRASS
+                } else if y >= top - 2 {
+                    DIRT
+                } else {
+                    STONE
+                };
+                place(world, (x, y, z), symbol);
+            }
+            for y in (top + 1)..=options.water_level {
+                place(world, (x, y, z), WATER);
+            }
+        }
+    }
+
+    placed
+}
@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 use crate::textures::Texture;
-use raylib::prelude::Color;
+use crate::math::Rgba;
+use crate::error::Result;
 
 /// Gestor de texturas
 #[derive(Default)]
@@ -15,18 +16,37 @@ impl TextureManager {
         self.images.insert(key, tex);
     }
 
+    /// Vuelve a leer del disco cada textura registrada (las generadas, como
+    /// las de los sprites, no tienen archivo) sin tocar las cargadas.
+    /// Devuelve cuántas se leyeron o el primer error: sirve para comprobar
+    /// que recargar los recursos no va a fallar a mitad de camino
+    pub fn check_files(&self) -> Result<usize> {
+        let mut paths: Vec<&str> = self
+            .images
+            .values()
+            .map(|t| t.path.as_str())
+            .filter(|p| !p.starts_with('<'))
+            .collect();
+        paths.sort_unstable();
+        paths.dedup();
+        for path in &paths {
+            Texture::load(path)?;
+        }
+        Ok(paths.len())
+    }
+
     /// Obtiene el color de la textura en coordenadas de píxel
-    pub fn get_pixel_color(&self, key: char, x: u32, y: u32) -> Color {
+    pub fn get_pixel_color(&self, key: char, x: u32, y: u32) -> Rgba {
         if let Some(tex) = self.images.get(&key) {
             // Clamp para evitar overflow
             let x = x.min(tex.width - 1);
             let y = y.min(tex.height - 1);
             let idx = ((y * tex.width + x) * 4) as usize;
             let data = &tex.data;
-            Color::new(data[idx], data[idx + 1], data[idx + 2], data[idx + 3])
+            Rgba::new(data[idx], data[idx + 1], data[idx + 2], data[idx + 3])
         } else {
             // Magenta para debug si no existe la textura
-            Color::new(255, 0, 255, 255)
+            Rgba::new(255, 0, 255, 255)
         }
     }
 }
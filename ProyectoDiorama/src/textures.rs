@@ -1,9 +1,11 @@
-use raylib::color::Color;
+use crate::math::Rgba;
+use crate::error::{Error, Result};
 
 
 /// Textura en memoria (RGBA8)
 #[derive(Clone)]
 pub struct Texture {
+    pub path: String,
     pub width: u32,
     pub height: u32,
     pub data: Vec<u8>, // RGBA8 plano
@@ -12,23 +14,24 @@ pub struct Texture {
 impl Texture {
     /// Carga la textura desde un archivo de imagen usando la crate `image`
     /// (PNG, JPG, etc. soportados por `image`).
-    pub fn load(path: &str) -> Self {
+    pub fn load(path: &str) -> Result<Self> {
         // Abrimos y convertimos a RGBA8
         let img = image::open(path)
-            .unwrap_or_else(|_| panic!("No pude abrir textura: {}", path))
+            .map_err(|source| Error::Texture { path: path.to_string(), source })?
             .to_rgba8();
 
         let (w, h) = img.dimensions();
-        Self {
+        Ok(Self {
+            path: path.to_string(),
             width: w,
             height: h,
             data: img.into_raw(),
-        }
+        })
     }
 
     /// Muestra el color en coordenadas UV normalizadas [0,1] con wrapping
     /// y nearest-neighbor sampling.
-    pub fn sample(&self, uv: (f32, f32)) -> Color {
+    pub fn sample(&self, uv: (f32, f32)) -> Rgba {
         let (mut u, mut v) = uv;
 
         // Wrap para que valores fuera de [0,1] se repitan
@@ -46,7 +49,7 @@ impl Texture {
         let idx = ((y * self.width + x) * 4) as usize;
 
         // Convierte RGBA8 a tu tipo Color (ignora alpha si no lo usas)
-        Color::new(
+        Rgba::new(
             self.data[idx],
             self.data[idx + 1],
             self.data[idx + 2],
@@ -54,6 +57,23 @@ impl Texture {
         )
     }
 
+    /// Color promedio de todos los texels (alpha ignorado)
+    pub fn average_color(&self) -> Rgba {
+        let count = (self.width * self.height).max(1) as u64;
+        let mut sum = [0u64; 3];
+        for px in self.data.chunks_exact(4) {
+            sum[0] += px[0] as u64;
+            sum[1] += px[1] as u64;
+            sum[2] += px[2] as u64;
+        }
+        Rgba::new(
+            (sum[0] / count) as u8,
+            (sum[1] / count) as u8,
+            (sum[2] / count) as u8,
+            255,
+        )
+    }
+
     /// Devuelve una nueva textura rotada 180° (útil si tu sistema de coords
     /// está invertido).
     pub fn rotated_180(self) -> Self {
@@ -65,6 +85,7 @@ impl Texture {
         let (w, h) = rot.dimensions();
 
         Self {
+            path: self.path,
             width: w,
             height: h,
             data: rot.into_raw(),
@@ -0,0 +1,41 @@
+use crate::error::Result;
+
+/// Variable de entorno con la cantidad de hilos si no se pasa `--threads`
+pub const THREADS_ENV: &str = "DIORAMA_THREADS";
+
+/// Hilos por defecto: todos los núcleos, menos uno que queda libre para la
+/// ventana (entrada y dibujo) si hay interfaz
+pub fn default_threads(leave_ui_core: bool) -> usize {
+    let cores = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+    if leave_ui_core { cores.saturating_sub(1).max(1) } else { cores }
+}
+
+/// Arma el pool global de rayon con `threads` hilos (o `DIORAMA_THREADS`,
+/// o `default_threads`). Con `pin` cada hilo queda fijo a un núcleo, lo que
+/// evita que el sistema los mueva y ensucien la caché a mitad de un cuadro.
+/// Devuelve la cantidad de hilos usada.
+pub fn configure(threads: Option<usize>, pin: bool, leave_ui_core: bool) -> Result<usize> {
+    let threads = threads
+        .or_else(|| std::env::var(THREADS_ENV).ok()?.parse().ok())
+        .unwrap_or_else(|| default_threads(leave_ui_core))
+        .max(1);
+
+    let builder = rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .thread_name(|i| format!("render-{}", i));
+    let builder = if pin { pinned(builder) } else { builder };
+    builder.build_global()?;
+    Ok(threads)
+}
+
+fn pinned(builder: rayon::ThreadPoolBuilder) -> rayon::ThreadPoolBuilder {
+    let Some(cores) = core_affinity::get_core_ids().filter(|c| !c.is_empty()) else {
+        eprintln!("No pude leer los núcleos; los hilos de render quedan sin fijar");
+        return builder;
+    };
+    builder.start_handler(move |i| {
+        // El último núcleo se deja para la ventana
+        let core = cores[i % cores.len().saturating_sub(1).max(1)];
+        core_affinity::set_for_current(core);
+    })
+}
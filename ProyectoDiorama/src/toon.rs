@@ -0,0 +1,96 @@
+use rayon::prelude::*;
+
+use crate::camera::Camera;
+use crate::math::Vec3;
+use crate::scene::Scene;
+use crate::{nearest_hit, primary_direction};
+
+/// Luz difusa en bandas (cel shading): el coseno entre normal y luz se
+/// compara con `thresholds` y el término difuso toma el nivel de la banda
+/// en la que cae, repartidos parejo entre 0 y 1.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cel {
+    /// Umbrales crecientes; pasar el `k`-ésimo (desde 0) da el nivel
+    /// `(k + 1) / thresholds.len()`
+    pub thresholds: Vec<f32>,
+}
+
+impl Cel {
+    /// `bands` bandas del mismo ancho, redondeando hacia arriba
+    pub fn even(bands: u32) -> Self {
+        let bands = bands.max(1);
+        Cel { thresholds: (0..bands).map(|k| k as f32 / bands as f32).collect() }
+    }
+
+    /// Umbrales dados (se ordenan)
+    pub fn with_thresholds(mut thresholds: Vec<f32>) -> Self {
+        thresholds.sort_by(f32::total_cmp);
+        Cel { thresholds }
+    }
+
+    /// Nivel de luz para el coseno `cos`
+    pub fn band(&self, cos: f32) -> f32 {
+        let passed = self.thresholds.iter().take_while(|t| cos > **t).count();
+        passed as f32 / self.thresholds.len().max(1) as f32
+    }
+}
+
+/// Estilo de caricatura: el brillo es todo o nada y los bordes del dibujo
+/// (cambios bruscos de profundidad o de normal entre píxeles vecinos) se
+/// pintan con `outline`. Las bandas de luz van aparte (ver `Cel`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Toon {
+    /// Color de los contornos
+    pub outline: Vec3,
+    /// Salto relativo de profundidad que cuenta como borde
+    pub depth_threshold: f32,
+    /// Coseno mínimo entre normales vecinas para no dibujar borde
+    pub normal_threshold: f32,
+}
+
+impl Default for Toon {
+    /// Contornos negros
+    fn default() -> Self {
+        Toon { outline: Vec3::zero(), depth_threshold: 0.08, normal_threshold: 0.7 }
+    }
+}
+
+impl Toon {
+    /// Brillo especular de borde duro
+    pub fn highlight(&self, specular: f32) -> f32 {
+        if specular > 0.5 { 1.0 } else { 0.0 }
+    }
+
+    /// Pinta los contornos sobre `pixels` (por filas). Traza un rayo por
+    /// píxel (el de la esquina, como la primera muestra de `trace_pixel`)
+    /// para armar profundidad y normal, y marca los píxeles que difieren de
+    /// algún vecino
+    pub fn outline(&self, pixels: &mut [Vec3], width: usize, height: usize, camera: &Camera, scene: &Scene) {
+        let surface: Vec<Option<(f32, Vec3)>> = (0..width * height)
+            .into_par_iter()
+            .map(|idx| {
+                let direction = primary_direction((idx % width) as f32, (idx / width) as f32, width, height, camera);
+                let hit = nearest_hit(&camera.eye, &direction, scene);
+                hit.is_intersecting.then_some((hit.distance, hit.normal))
+            })
+            .collect();
+
+        let edge = |a: Option<(f32, Vec3)>, b: Option<(f32, Vec3)>| match (a, b) {
+            (None, None) => false,
+            (Some((da, na)), Some((db, nb))) => {
+                (da - db).abs() > self.depth_threshold * da.min(db) || na.dot(nb) < self.normal_threshold
+            }
+            _ => true,
+        };
+        for y in 0..height {
+            for x in 0..width {
+                let here = surface[y * width + x];
+                let right = (x + 1 < width).then(|| surface[y * width + x + 1]);
+                let below = (y + 1 < height).then(|| surface[(y + 1) * width + x]);
+                if right.is_some_and(|r| edge(here, r)) || below.is_some_and(|b| edge(here, b)) {
+                    pixels[y * width + x] = self.outline;
+                }
+            }
+        }
+    }
+}
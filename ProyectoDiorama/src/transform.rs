@@ -0,0 +1,232 @@
+use crate::math::Vec3;
+
+use crate::geometry::Aabb;
+use crate::ray_intersect::{Intersect, Occlusion, RayIntersect};
+
+/// Traslación, rotación (ángulos de Euler) y escala uniforme de un nodo
+#[derive(Debug, Clone, Copy)]
+pub struct Transform {
+    pub translation: Vec3,
+    /// Ejes locales X, Y, Z expresados en el espacio del padre
+    axes: [Vec3; 3],
+    pub scale: f32,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform::new(Vec3::zero(), 0.0, 0.0, 0.0, 1.0)
+    }
+}
+
+impl Transform {
+    /// Rotación en grados: primero `roll` en Z, luego `pitch` en X y por
+    /// último `yaw` en Y; después escala y traslación
+    pub fn new(translation: Vec3, yaw: f32, pitch: f32, roll: f32, scale: f32) -> Self {
+        let (sy, cy) = yaw.to_radians().sin_cos();
+        let (sp, cp) = pitch.to_radians().sin_cos();
+        let (sr, cr) = roll.to_radians().sin_cos();
+
+        let rotate = |v: Vec3| {
+            let v = Vec3::new(v.x * cr - v.y * sr, v.x * sr + v.y * cr, v.z);
+            let v = Vec3::new(v.x, v.y * cp - v.z * sp, v.y * sp + v.z * cp);
+            Vec3::new(v.x * cy + v.z * sy, v.y, -v.x * sy + v.z * cy)
+        };
+
+        let axes = [
+            rotate(Vec3::new(1.0, 0.0, 0.0)),
+            rotate(Vec3::new(0.0, 1.0, 0.0)),
+            rotate(Vec3::new(0.0, 0.0, 1.0)),
+        ];
+        Transform { translation, axes, scale }
+    }
+
+    /// Dirección del espacio del padre al local (sin escala)
+    pub fn inverse_rotate(&self, v: Vec3) -> Vec3 {
+        Vec3::new(v.dot(self.axes[0]), v.dot(self.axes[1]), v.dot(self.axes[2]))
+    }
+
+    /// Dirección del espacio local al del padre (sin escala)
+    fn rotate(&self, v: Vec3) -> Vec3 {
+        self.axes[0] * v.x + self.axes[1] * v.y + self.axes[2] * v.z
+    }
+
+    pub fn point_to_parent(&self, p: Vec3) -> Vec3 {
+        self.translation + self.rotate(p * self.scale)
+    }
+
+    pub fn point_to_local(&self, p: Vec3) -> Vec3 {
+        self.inverse_rotate(p - self.translation) / self.scale
+    }
+
+    /// Caja alineada en el espacio del padre que contiene a `local` girada
+    pub fn bounds_to_parent(&self, local: &Aabb) -> Aabb {
+        let corners = local.corners().map(|c| self.point_to_parent(c));
+        Aabb::from_points(corners).unwrap_or(*local)
+    }
+
+    /// Prueba de sombra de `object` con el rayo llevado al espacio local
+    /// (mismo `t` que en el padre, ver `intersect`)
+    pub fn occlusion(&self, object: &dyn RayIntersect, ray_origin: &Vec3, ray_direction: &Vec3, max_t: f32) -> Occlusion {
+        let origin = self.point_to_local(*ray_origin);
+        let direction = self.inverse_rotate(*ray_direction) / self.scale;
+        object.occlusion(&origin, &direction, max_t)
+    }
+
+    /// Intersecta `object` con el rayo llevado al espacio local (la
+    /// dirección también se divide por la escala, así el parámetro `t` y por
+    /// lo tanto `distance` siguen siendo los del padre) y devuelve el punto y
+    /// la normal en el espacio del padre
+    pub fn intersect(&self, object: &dyn RayIntersect, ray_origin: &Vec3, ray_direction: &Vec3) -> Intersect {
+        let origin = self.point_to_local(*ray_origin);
+        let direction = self.inverse_rotate(*ray_direction) / self.scale;
+
+        let mut hit = object.ray_intersect(&origin, &direction);
+        if hit.is_intersecting {
+            hit.point = self.point_to_parent(hit.point);
+            hit.normal = self.rotate(hit.normal);
+        }
+        hit
+    }
+}
+
+/// Nodo del grafo de escena: objetos y nodos hijos que se mueven juntos con
+/// la transformación del nodo
+#[derive(Default)]
+pub struct Node {
+    pub transform: Transform,
+    pub objects: Vec<Box<dyn RayIntersect>>,
+    pub children: Vec<Node>,
+}
+
+impl Node {
+    pub fn new(transform: Transform) -> Self {
+        Node { transform, ..Default::default() }
+    }
+
+    pub fn with_object(mut self, object: impl RayIntersect + 'static) -> Self {
+        self.objects.push(Box::new(object));
+        self
+    }
+
+    pub fn with_child(mut self, child: Node) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    /// Impacto más cercano entre los objetos e hijos, en espacio local
+    fn nearest_local(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Intersect {
+        let mut best = Intersect::empty();
+        let mut zbuffer = f32::INFINITY;
+        let objects = self.objects.iter().map(|o| o.as_ref() as &dyn RayIntersect);
+        let children = self.children.iter().map(|c| c as &dyn RayIntersect);
+        for object in objects.chain(children) {
+            let hit = object.ray_intersect(ray_origin, ray_direction);
+            if hit.is_intersecting && hit.distance < zbuffer {
+                zbuffer = hit.distance;
+                best = hit;
+            }
+        }
+        best
+    }
+}
+
+/// Contenido de un nodo sin su transformación, para intersectarlo desde
+/// `Transform::intersect`
+struct NodeContents<'a>(&'a Node);
+
+impl RayIntersect for NodeContents<'_> {
+    fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Intersect {
+        self.0.nearest_local(ray_origin, ray_direction)
+    }
+
+    /// Corta en el primer contenido opaco; si solo hay transparentes, tiñe
+    /// el más cercano (como `ray_intersect`)
+    fn occlusion(&self, ray_origin: &Vec3, ray_direction: &Vec3, max_t: f32) -> Occlusion {
+        let objects = self.0.objects.iter().map(|o| o.as_ref() as &dyn RayIntersect);
+        let children = self.0.children.iter().map(|c| c as &dyn RayIntersect);
+        let mut nearest: Option<Box<Intersect>> = None;
+        for object in objects.chain(children) {
+            match object.occlusion(ray_origin, ray_direction, max_t) {
+                Occlusion::Clear => {}
+                Occlusion::Opaque => return Occlusion::Opaque,
+                Occlusion::Tinted(hit) => {
+                    if nearest.as_ref().is_none_or(|n| hit.distance < n.distance) {
+                        nearest = Some(hit);
+                    }
+                }
+            }
+        }
+        nearest.map_or(Occlusion::Clear, Occlusion::Tinted)
+    }
+}
+
+impl RayIntersect for Node {
+    fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Intersect {
+        self.transform.intersect(&NodeContents(self), ray_origin, ray_direction)
+    }
+
+    fn primitive_count(&self) -> usize {
+        self.objects.iter().map(|o| o.primitive_count()).sum::<usize>()
+            + self.children.iter().map(Node::primitive_count).sum::<usize>()
+    }
+
+    fn memory_bytes(&self) -> usize {
+        std::mem::size_of::<Node>()
+            + self.objects.iter().map(|o| o.memory_bytes() + std::mem::size_of::<Box<dyn RayIntersect>>()).sum::<usize>()
+            + self.children.iter().map(Node::memory_bytes).sum::<usize>()
+    }
+
+    /// Unión de las cajas de objetos e hijos, llevada al espacio del padre;
+    /// `None` si algún contenido no está acotado
+    fn bounds(&self) -> Option<Aabb> {
+        let objects = self.objects.iter().map(|o| o.bounds());
+        let children = self.children.iter().map(|c| c.bounds());
+        let mut local: Option<Aabb> = None;
+        for b in objects.chain(children) {
+            let b = b?;
+            local = Some(local.map_or(b, |l| l.union(&b)));
+        }
+        local.map(|b| self.transform.bounds_to_parent(&b))
+    }
+
+    fn occlusion(&self, ray_origin: &Vec3, ray_direction: &Vec3, max_t: f32) -> Occlusion {
+        self.transform.occlusion(&NodeContents(self), ray_origin, ray_direction, max_t)
+    }
+}
+
+/// Copia de una sub-escena compartida (p. ej. un árbol): cada instancia
+/// guarda solo su transformación y una referencia al prototipo, así cien
+/// árboles no ocupan cien veces los cubos
+pub struct Instance<'a> {
+    pub prototype: &'a dyn RayIntersect,
+    pub transform: Transform,
+}
+
+impl<'a> Instance<'a> {
+    pub fn new(prototype: &'a dyn RayIntersect, transform: Transform) -> Self {
+        Instance { prototype, transform }
+    }
+}
+
+impl RayIntersect for Instance<'_> {
+    fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Intersect {
+        self.transform.intersect(self.prototype, ray_origin, ray_direction)
+    }
+
+    fn primitive_count(&self) -> usize {
+        self.prototype.primitive_count()
+    }
+
+    /// Solo la instancia: el prototipo es compartido y se cuenta aparte
+    fn memory_bytes(&self) -> usize {
+        std::mem::size_of::<Self>()
+    }
+
+    fn bounds(&self) -> Option<Aabb> {
+        self.prototype.bounds().map(|b| self.transform.bounds_to_parent(&b))
+    }
+
+    fn occlusion(&self, ray_origin: &Vec3, ray_direction: &Vec3, max_t: f32) -> Occlusion {
+        self.transform.occlusion(self.prototype, ray_origin, ray_direction, max_t)
+    }
+}
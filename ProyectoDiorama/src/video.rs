@@ -0,0 +1,61 @@
+use std::io::Write;
+use std::process::{Child, ChildStdin, Command, Stdio};
+
+use crate::error::{Error, Result};
+
+/// Proceso de ffmpeg que recibe cuadros RGBA crudos por stdin y los codifica
+/// directamente al video de salida
+pub struct VideoWriter {
+    child: Child,
+    stdin: Option<ChildStdin>,
+    frame_len: usize,
+}
+
+impl VideoWriter {
+    pub fn spawn(path: &str, width: u32, height: u32, fps: u32) -> Result<Self> {
+        let mut child = Command::new("ffmpeg")
+            .args(["-y", "-loglevel", "error"])
+            .args(["-f", "rawvideo", "-pix_fmt", "rgba"])
+            .args(["-s", &format!("{}x{}", width, height)])
+            .args(["-r", &fps.to_string()])
+            .args(["-i", "-"])
+            .args(["-c:v", "libx264", "-pix_fmt", "yuv420p"])
+            .arg(path)
+            .stdin(Stdio::piped())
+            .spawn()
+            .map_err(|e| Error::Video(format!("no pude ejecutar ffmpeg: {}", e)))?;
+
+        let stdin = child.stdin.take();
+        Ok(VideoWriter {
+            child,
+            stdin,
+            frame_len: (width * height * 4) as usize,
+        })
+    }
+
+    /// Envía un cuadro RGBA8 (ancho * alto * 4 bytes)
+    pub fn write_frame(&mut self, rgba: &[u8]) -> Result<()> {
+        if rgba.len() != self.frame_len {
+            return Err(Error::Video(format!(
+                "cuadro de {} bytes, se esperaban {}",
+                rgba.len(),
+                self.frame_len
+            )));
+        }
+        match self.stdin.as_mut() {
+            Some(stdin) => Ok(stdin.write_all(rgba)?),
+            None => Err(Error::Video("ffmpeg ya fue cerrado".to_string())),
+        }
+    }
+
+    /// Cierra stdin y espera a que ffmpeg termine de escribir el archivo
+    pub fn finish(mut self) -> Result<()> {
+        drop(self.stdin.take());
+        let status = self.child.wait()?;
+        if status.success() {
+            Ok(())
+        } else {
+            Err(Error::Video(format!("ffmpeg terminó con {}", status)))
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use crate::math::Rgba;
+
+use crate::error::{Error, Result};
+use crate::material::MaterialLibrary;
+use crate::texture_manager::TextureManager;
+use crate::voxel_world::VoxelWorld;
+
+const VOX_VERSION: i32 = 150;
+const VOX_MAX_SIZE: i32 = 256;
+
+fn write_chunk_header<W: Write>(out: &mut W, id: &[u8; 4], content: usize, children: usize) -> Result<()> {
+    out.write_all(id)?;
+    out.write_all(&(content as i32).to_le_bytes())?;
+    out.write_all(&(children as i32).to_le_bytes())?;
+    Ok(())
+}
+
+/// Exporta el mundo a un archivo MagicaVoxel `.vox`.
+/// La paleta sale del color difuso de cada material (el promedio de su
+/// textura si tiene). MagicaVoxel usa Z hacia arriba, así que nuestro Y pasa
+/// a ser Z y nuestro Z se invierte en Y para conservar la orientación.
+pub fn export_vox(
+    path: &str,
+    world: &VoxelWorld,
+    materials: &MaterialLibrary,
+    tm: &TextureManager,
+) -> Result<()> {
+    let voxels = world.sorted();
+    if voxels.is_empty() {
+        return Err(Error::Export("el mundo no tiene vóxeles".to_string()));
+    }
+
+    let mut min = (i32::MAX, i32::MAX, i32::MAX);
+    let mut max = (i32::MIN, i32::MIN, i32::MIN);
+    for &((x, y, z), _) in &voxels {
+        min = (min.0.min(x), min.1.min(y), min.2.min(z));
+        max = (max.0.max(x), max.1.max(y), max.2.max(z));
+    }
+    let size = (max.0 - min.0 + 1, max.2 - min.2 + 1, max.1 - min.1 + 1);
+    if size.0 > VOX_MAX_SIZE || size.1 > VOX_MAX_SIZE || size.2 > VOX_MAX_SIZE {
+        return Err(Error::Export(format!(
+            "el mundo mide {}x{}x{}, .vox admite hasta {}",
+            size.0, size.1, size.2, VOX_MAX_SIZE
+        )));
+    }
+
+    // Índice de paleta (1..=255) por símbolo
+    let mut palette: Vec<[u8; 4]> = Vec::new();
+    let mut indices: HashMap<char, u8> = HashMap::new();
+    for &(_, symbol) in &voxels {
+        if indices.contains_key(&symbol) {
+            continue;
+        }
+        if palette.len() >= 255 {
+            return Err(Error::Export("más de 255 materiales distintos".to_string()));
+        }
+        let color = materials
+            .get(symbol)
+            .map(|m| m.base_color(tm))
+            .unwrap_or(Rgba::WHITE);
+        palette.push([color.r, color.g, color.b, 255]);
+        indices.insert(symbol, palette.len() as u8);
+    }
+
+    let size_len = 12;
+    let xyzi_len = 4 + 4 * voxels.len();
+    let rgba_len = 256 * 4;
+    let children = (12 + size_len) + (12 + xyzi_len) + (12 + rgba_len);
+
+    let mut out = BufWriter::new(File::create(path)?);
+    out.write_all(b"VOX ")?;
+    out.write_all(&VOX_VERSION.to_le_bytes())?;
+    write_chunk_header(&mut out, b"MAIN", 0, children)?;
+
+    write_chunk_header(&mut out, b"SIZE", size_len, 0)?;
+    for d in [size.0, size.1, size.2] {
+        out.write_all(&d.to_le_bytes())?;
+    }
+
+    write_chunk_header(&mut out, b"XYZI", xyzi_len, 0)?;
+    out.write_all(&(voxels.len() as i32).to_le_bytes())?;
+    for ((x, y, z), symbol) in voxels {
+        out.write_all(&[
+            (x - min.0) as u8,
+            (max.2 - z) as u8,
+            (y - min.1) as u8,
+            indices[&symbol],
+        ])?;
+    }
+
+    // La entrada i del chunk RGBA corresponde al índice de color i + 1
+    write_chunk_header(&mut out, b"RGBA", rgba_len, 0)?;
+    for i in 0..256 {
+        out.write_all(&palette.get(i).copied().unwrap_or([0, 0, 0, 255]))?;
+    }
+    out.flush()?;
+
+    Ok(())
+}
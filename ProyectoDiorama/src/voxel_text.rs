@@ -0,0 +1,82 @@
+use crate::voxel_world::VoxelWorld;
+
+/// Alto y ancho de los glifos de la fuente bitmap (5x7)
+pub const GLYPH_HEIGHT: i32 = 7;
+pub const GLYPH_WIDTH: i32 = 5;
+
+/// Glifo 5x7 de la fuente, filas de arriba hacia abajo ('1' = lleno).
+/// Las minúsculas se dibujan como mayúsculas; los caracteres sin glifo
+/// se dejan como espacio.
+fn glyph(c: char) -> Option<[&'static str; 7]> {
+    let rows = match c.to_ascii_uppercase() {
+        'A' => ["01110", "10001", "10001", "11111", "10001", "10001", "10001"],
+        'B' => ["11110", "10001", "10001", "11110", "10001", "10001", "11110"],
+        'C' => ["01110", "10001", "10000", "10000", "10000", "10001", "01110"],
+        'D' => ["11110", "10001", "10001", "10001", "10001", "10001", "11110"],
+        'E' => ["11111", "10000", "10000", "11110", "10000", "10000", "11111"],
+        'F' => ["11111", "10000", "10000", "11110", "10000", "10000", "10000"],
+        'G' => ["01110", "10001", "10000", "10111", "10001", "10001", "01111"],
+        'H' => ["10001", "10001", "10001", "11111", "10001", "10001", "10001"],
+        'I' => ["01110", "00100", "00100", "00100", "00100", "00100", "01110"],
+        'J' => ["00111", "00010", "00010", "00010", "00010", "10010", "01100"],
+        'K' => ["10001", "10010", "10100", "11000", "10100", "10010", "10001"],
+        'L' => ["10000", "10000", "10000", "10000", "10000", "10000", "11111"],
+        'M' => ["10001", "11011", "10101", "10101", "10001", "10001", "10001"],
+        'N' => ["10001", "10001", "11001", "10101", "10011", "10001", "10001"],
+        'O' => ["01110", "10001", "10001", "10001", "10001", "10001", "01110"],
+        'P' => ["11110", "10001", "10001", "11110", "10000", "10000", "10000"],
+        'Q' => ["01110", "10001", "10001", "10001", "10101", "10010", "01101"],
+        'R' => ["11110", "10001", "10001", "11110", "10100", "10010", "10001"],
+        'S' => ["01111", "10000", "10000", "01110", "00001", "00001", "11110"],
+        'T' => ["11111", "00100", "00100", "00100", "00100", "00100", "00100"],
+        'U' => ["10001", "10001", "10001", "10001", "10001", "10001", "01110"],
+        'V' => ["10001", "10001", "10001", "10001", "10001", "01010", "00100"],
+        'W' => ["10001", "10001", "10001", "10101", "10101", "10101", "01010"],
+        'X' => ["10001", "10001", "01010", "00100", "01010", "10001", "10001"],
+        'Y' => ["10001", "10001", "01010", "00100", "00100", "00100", "00100"],
+        'Z' => ["11111", "00001", "00010", "00100", "01000", "10000", "11111"],
+        '0' => ["01110", "10001", "10011", "10101", "11001", "10001", "01110"],
+        '1' => ["00100", "01100", "00100", "00100", "00100", "00100", "01110"],
+        '2' => ["01110", "10001", "00001", "00010", "00100", "01000", "11111"],
+        '3' => ["11111", "00010", "00100", "00010", "00001", "10001", "01110"],
+        '4' => ["00010", "00110", "01010", "10010", "11111", "00010", "00010"],
+        '5' => ["11111", "10000", "11110", "00001", "00001", "10001", "01110"],
+        '6' => ["00110", "01000", "10000", "11110", "10001", "10001", "01110"],
+        '7' => ["11111", "00001", "00010", "00100", "01000", "01000", "01000"],
+        '8' => ["01110", "10001", "10001", "01110", "10001", "10001", "01110"],
+        '9' => ["01110", "10001", "10001", "01111", "00001", "00010", "01100"],
+        '!' => ["00100", "00100", "00100", "00100", "00100", "00000", "00100"],
+        '?' => ["01110", "10001", "00001", "00010", "00100", "00000", "00100"],
+        '.' => ["00000", "00000", "00000", "00000", "00000", "01100", "01100"],
+        ',' => ["00000", "00000", "00000", "00000", "01100", "00100", "01000"],
+        '-' => ["00000", "00000", "00000", "11111", "00000", "00000", "00000"],
+        ':' => ["00000", "01100", "01100", "00000", "01100", "01100", "00000"],
+        _ => return None,
+    };
+    Some(rows)
+}
+
+/// Escribe `text` como una pared de un vóxel de grosor en el plano XY.
+/// `origin` es la esquina inferior izquierda del primer carácter; el texto
+/// avanza hacia +X con una columna de separación entre letras.
+/// Devuelve la cantidad de vóxeles colocados.
+pub fn stamp_text(world: &mut VoxelWorld, text: &str, origin: (i32, i32, i32), symbol: char) -> usize {
+    let (ox, oy, oz) = origin;
+    let symbol = symbol.to_ascii_uppercase();
+    let mut placed = 0;
+
+    for (i, c) in text.chars().enumerate() {
+        let Some(rows) = glyph(c) else { continue };
+        let x0 = ox + i as i32 * (GLYPH_WIDTH + 1);
+        for (row, bits) in rows.iter().enumerate() {
+            let y = oy + GLYPH_HEIGHT - 1 - row as i32;
+            for (col, bit) in bits.chars().enumerate() {
+                if bit == '1' {
+                    world.set((x0 + col as i32, y, oz), symbol);
+                    placed += 1;
+                }
+            }
+        }
+    }
+    placed
+}
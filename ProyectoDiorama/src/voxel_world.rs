@@ -0,0 +1,143 @@
+use std::collections::HashMap;
+use crate::math::Vec3;
+
+use crate::cube::Cube;
+use crate::material::MaterialLibrary;
+use crate::ray_intersect::CubeFace;
+use crate::shapes;
+
+/// Cara de un vóxel cuyo vecino en la dirección de la normal está vacío
+#[derive(Debug, Clone, Copy)]
+pub struct ExposedFace {
+    pub position: (i32, i32, i32),
+    pub symbol: char,
+    pub face: CubeFace,
+}
+
+/// Mundo de vóxeles: símbolo de material por celda entera (x, y, z).
+/// El centro del cubo de la celda (x, y, z) está en (x, y, z) con tamaño 1.
+#[derive(Debug, Clone, Default)]
+pub struct VoxelWorld {
+    voxels: HashMap<(i32, i32, i32), char>,
+}
+
+impl VoxelWorld {
+    /// Construye el mundo a partir de capas (una por altura `y`, filas en `z`,
+    /// columnas en `x`). Los símbolos sin material en `materials` son vacío.
+    pub fn from_layers(layers: &[Vec<&str>], materials: &MaterialLibrary) -> Self {
+        let mut world = VoxelWorld::default();
+        for (y, layer) in layers.iter().enumerate() {
+            for (z, row) in layer.iter().enumerate() {
+                for (x, c) in row.chars().enumerate() {
+                    if materials.get(c).is_some() {
+                        world.set((x as i32, y as i32, z as i32), c.to_ascii_uppercase());
+                    }
+                }
+            }
+        }
+        world
+    }
+
+    pub fn set(&mut self, pos: (i32, i32, i32), symbol: char) {
+        self.voxels.insert(pos, symbol);
+    }
+
+    /// Símbolo de material de la celda, si está ocupada
+    pub fn get(&self, pos: (i32, i32, i32)) -> Option<char> {
+        self.voxels.get(&pos).copied()
+    }
+
+    pub fn is_solid(&self, pos: (i32, i32, i32)) -> bool {
+        self.voxels.contains_key(&pos)
+    }
+
+    /// Cantidad de celdas ocupadas
+    pub fn len(&self) -> usize {
+        self.voxels.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.voxels.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = ((i32, i32, i32), char)> + '_ {
+        self.voxels.iter().map(|(p, c)| (*p, *c))
+    }
+
+    /// Celdas ocupadas en orden determinista (y, z, x)
+    pub fn sorted(&self) -> Vec<((i32, i32, i32), char)> {
+        let mut voxels: Vec<_> = self.iter().collect();
+        voxels.sort_by_key(|&((x, y, z), _)| (y, z, x));
+        voxels
+    }
+
+    /// Caras visibles: las que no están tapadas por otro vóxel
+    pub fn exposed_faces(&self) -> Vec<ExposedFace> {
+        let mut faces = Vec::new();
+        for ((x, y, z), symbol) in self.sorted() {
+            for face in CubeFace::ALL {
+                let (dx, dy, dz) = face.offset();
+                if !self.is_solid((x + dx, y + dy, z + dz)) {
+                    faces.push(ExposedFace { position: (x, y, z), symbol, face });
+                }
+            }
+        }
+        faces
+    }
+
+    /// Oclusión ambiental estilo Minecraft en una esquina de la cara: mira las
+    /// dos celdas laterales y la diagonal en la capa que está frente a la cara
+    fn corner_ao(&self, pos: (i32, i32, i32), face: CubeFace, su: i32, sv: i32) -> f32 {
+        let (nx, ny, nz) = face.offset();
+        let ((ux, uy, uz), (vx, vy, vz)) = face.uv_axes();
+        let base = (pos.0 + nx, pos.1 + ny, pos.2 + nz);
+        let side1 = self.is_solid((base.0 + ux * su, base.1 + uy * su, base.2 + uz * su));
+        let side2 = self.is_solid((base.0 + vx * sv, base.1 + vy * sv, base.2 + vz * sv));
+        let corner = self.is_solid((
+            base.0 + ux * su + vx * sv,
+            base.1 + uy * su + vy * sv,
+            base.2 + uz * su + vz * sv,
+        ));
+        let level = if side1 && side2 {
+            0
+        } else {
+            3 - (side1 as i32 + side2 as i32 + corner as i32)
+        };
+        0.4 + 0.2 * level as f32
+    }
+
+    /// AO de las cuatro esquinas de cada cara del vóxel
+    fn voxel_ao(&self, pos: (i32, i32, i32)) -> [[f32; 4]; 6] {
+        let mut ao = [[1.0; 4]; 6];
+        for face in CubeFace::ALL {
+            let corners = &mut ao[face.index()];
+            for (i, (su, sv)) in [(-1, -1), (1, -1), (1, 1), (-1, 1)].into_iter().enumerate() {
+                corners[i] = self.corner_ao(pos, face, su, sv);
+            }
+        }
+        ao
+    }
+
+    /// Un cubo unitario por vóxel con su material y AO horneado; los bloques
+    /// parciales (losas, escalones, pilares) se arman con sus cajas
+    pub fn to_cubes(&self, materials: &MaterialLibrary) -> Vec<Cube> {
+        let mut cubes = Vec::new();
+        for ((x, y, z), symbol) in self.sorted() {
+            let Some(mat) = materials.get(symbol) else {
+                continue;
+            };
+            let center = Vec3::new(x as f32, y as f32, z as f32);
+            match shapes::find(symbol) {
+                Some(block) => cubes.extend(
+                    block
+                        .shape
+                        .boxes(center)
+                        .into_iter()
+                        .map(|(min, max)| Cube::from_bounds(min, max, mat.clone())),
+                ),
+                None => cubes.push(Cube::new(center, 1.0, mat.clone()).with_ao(self.voxel_ao((x, y, z)))),
+            }
+        }
+        cubes
+    }
+}
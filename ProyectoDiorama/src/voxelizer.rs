@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+use crate::math::{Rgba, Vec3};
+
+use crate::error::{Error, Result};
+use crate::textures::Texture;
+use crate::voxel_world::VoxelWorld;
+
+/// Triángulo con índices a posiciones y (opcionalmente) a UVs
+#[derive(Debug, Clone, Copy)]
+struct Triangle {
+    v: [usize; 3],
+    vt: Option<[usize; 3]>,
+}
+
+/// Malla OBJ mínima: posiciones, UVs y caras trianguladas en abanico
+#[derive(Debug, Clone, Default)]
+pub struct ObjMesh {
+    positions: Vec<Vec3>,
+    uvs: Vec<(f32, f32)>,
+    triangles: Vec<Triangle>,
+}
+
+impl ObjMesh {
+    pub fn load(path: &str) -> Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        Self::parse(&text, path)
+    }
+
+    pub fn parse(text: &str, path: &str) -> Result<Self> {
+        let mut mesh = ObjMesh::default();
+
+        for (i, raw) in text.lines().enumerate() {
+            let err = |msg: String| Error::Parse {
+                path: path.to_string(),
+                line: i + 1,
+                msg,
+            };
+            let mut words = raw.split_whitespace();
+            let Some(kind) = words.next() else { continue };
+            let params: Vec<&str> = words.collect();
+            let float = |s: &str| s.parse::<f32>().map_err(|_| err(format!("número inválido: {}", s)));
+
+            match kind {
+                "v" if params.len() >= 3 => {
+                    mesh.positions.push(Vec3::new(float(params[0])?, float(params[1])?, float(params[2])?));
+                }
+                "vt" if params.len() >= 2 => {
+                    mesh.uvs.push((float(params[0])?, float(params[1])?));
+                }
+                "f" if params.len() >= 3 => {
+                    // Cada vértice es v, v/vt, v//vn o v/vt/vn (índices desde 1, negativos relativos)
+                    let mut corners = Vec::with_capacity(params.len());
+                    for p in &params {
+                        let mut parts = p.split('/');
+                        let v = resolve(parts.next(), mesh.positions.len())
+                            .ok_or_else(|| err(format!("vértice inválido: {}", p)))?;
+                        let vt = resolve(parts.next(), mesh.uvs.len());
+                        corners.push((v, vt));
+                    }
+                    for k in 1..corners.len() - 1 {
+                        let (a, b, c) = (corners[0], corners[k], corners[k + 1]);
+                        let vt = match (a.1, b.1, c.1) {
+                            (Some(x), Some(y), Some(z)) => Some([x, y, z]),
+                            _ => None,
+                        };
+                        mesh.triangles.push(Triangle { v: [a.0, b.0, c.0], vt });
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if mesh.triangles.is_empty() {
+            return Err(Error::Parse {
+                path: path.to_string(),
+                line: 0,
+                msg: "la malla no tiene caras".to_string(),
+            });
+        }
+        Ok(mesh)
+    }
+}
+
+/// Convierte un índice OBJ (1-based o negativo) a índice 0-based válido
+fn resolve(index: Option<&str>, len: usize) -> Option<usize> {
+    let i: i64 = index.filter(|s| !s.is_empty())?.parse().ok()?;
+    let resolved = if i < 0 { len as i64 + i } else { i - 1 };
+    (0..len as i64).contains(&resolved).then_some(resolved as usize)
+}
+
+fn color_distance(a: Rgba, b: Rgba) -> i32 {
+    let (dr, dg, db) = (a.r as i32 - b.r as i32, a.g as i32 - b.g as i32, a.b as i32 - b.b as i32);
+    dr * dr + dg * dg + db * db
+}
+
+/// Material de los vóxeles sin color de textura si la directiva no da otro:
+/// lana blanca, opaca (el primero de la paleta podía ser agua)
+pub const DEFAULT_SYMBOL: char = 'W';
+
+/// Voxeliza la superficie de la malla: el eje más largo ocupa `resolution`
+/// vóxeles y la malla se apoya con su esquina mínima en `origin`.
+/// Cada vóxel toma el material de `palette` cuyo color es más cercano al
+/// promedio de las muestras de `texture` que cayeron en él, o
+/// `default_symbol` si no hay textura (o la malla no tiene UV ahí).
+pub fn voxelize(
+    mesh: &ObjMesh,
+    resolution: u32,
+    origin: (i32, i32, i32),
+    texture: Option<&Texture>,
+    palette: &[(char, Rgba)],
+    default_symbol: char,
+    world: &mut VoxelWorld,
+) -> usize {
+    let mut min = Vec3::new(f32::INFINITY, f32::INFINITY, f32::INFINITY);
+    let mut max = Vec3::new(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY);
+    for p in &mesh.positions {
+        min = Vec3::new(min.x.min(p.x), min.y.min(p.y), min.z.min(p.z));
+        max = Vec3::new(max.x.max(p.x), max.y.max(p.y), max.z.max(p.z));
+    }
+    let extent = (max - min).x.max((max - min).y).max((max - min).z).max(1e-6);
+    // Ajuste para que la cara máxima caiga dentro del último vóxel
+    let scale = (resolution as f32 - 1e-3) / extent;
+
+    // Suma de color y cantidad de muestras por celda
+    let mut cells: HashMap<(i32, i32, i32), ([u32; 3], u32)> = HashMap::new();
+
+    for tri in &mesh.triangles {
+        let [a, b, c] = tri.v.map(|i| (mesh.positions[i] - min) * scale);
+        let uv = tri.vt.map(|vt| vt.map(|i| mesh.uvs[i]));
+
+        // Paso de muestreo de medio vóxel a lo largo de las aristas
+        let longest = (b - a).length().max((c - a).length()).max((c - b).length());
+        let steps = (longest * 2.0).ceil().max(1.0) as u32;
+
+        for i in 0..=steps {
+            for j in 0..=(steps - i) {
+                let (s, t) = (i as f32 / steps as f32, j as f32 / steps as f32);
+                let p = a + (b - a) * s + (c - a) * t;
+                let cell = (p.x.floor() as i32, p.y.floor() as i32, p.z.floor() as i32);
+
+                let entry = cells.entry(cell).or_insert(([0; 3], 0));
+                if let (Some(tex), Some([ua, ub, uc])) = (texture, uv) {
+                    let u = ua.0 + (ub.0 - ua.0) * s + (uc.0 - ua.0) * t;
+                    let v = ua.1 + (ub.1 - ua.1) * s + (uc.1 - ua.1) * t;
+                    let col = tex.sample((u, v));
+                    entry.0[0] += col.r as u32;
+                    entry.0[1] += col.g as u32;
+                    entry.0[2] += col.b as u32;
+                    entry.1 += 1;
+                }
+            }
+        }
+    }
+
+    let (ox, oy, oz) = origin;
+    for (&(x, y, z), &(sum, count)) in &cells {
+        let symbol = match sum.map(|c| c.checked_div(count)) {
+            [Some(r), Some(g), Some(b)] => {
+                let avg = Rgba::new(r as u8, g as u8, b as u8, 255);
+                palette
+                    .iter()
+                    .min_by_key(|(_, c)| color_distance(*c, avg))
+                    .map(|(s, _)| *s)
+                    .unwrap_or(default_symbol)
+            }
+            _ => default_symbol,
+        };
+        world.set((ox + x, oy + y, oz + z), symbol);
+    }
+    cells.len()
+}
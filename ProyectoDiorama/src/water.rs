@@ -0,0 +1,77 @@
+use crate::math::Vec3;
+
+/// Olas procedurales para superficies de agua: suma de senos que se
+/// desplazan con el tiempo. Solo perturba caras que miran hacia arriba.
+#[derive(Debug, Clone, Copy)]
+pub struct Waves {
+    /// Inclinación máxima de la normal
+    pub amplitude: f32,
+    /// Ondas por unidad de mundo
+    pub frequency: f32,
+    /// Velocidad de desplazamiento (radianes por segundo)
+    pub speed: f32,
+}
+
+impl Default for Waves {
+    fn default() -> Self {
+        Waves {
+            amplitude: 0.15,
+            frequency: 1.7,
+            speed: 1.5,
+        }
+    }
+}
+
+impl Waves {
+    /// Gradiente (dh/dx, dh/dz) del campo de alturas en `p` al tiempo `time`
+    fn gradient(&self, p: Vec3, time: f32) -> (f32, f32) {
+        let f = self.frequency;
+        let t = time * self.speed;
+        // Tres trenes de olas en direcciones distintas para evitar patrones obvios
+        let dx = (p.x * f + t).cos() * f
+            + 0.5 * (p.x * f * 1.9 + p.z * f * 0.7 - t * 1.3).cos() * f * 1.9;
+        let dz = (p.z * f * 1.3 + t * 0.8).cos() * f * 1.3
+            + 0.5 * (p.x * f * 1.9 + p.z * f * 0.7 - t * 1.3).cos() * f * 0.7;
+        (dx, dz)
+    }
+
+    /// Normal perturbada por las olas; caras no superiores no cambian
+    pub fn perturb(&self, normal: Vec3, point: Vec3, time: f32) -> Vec3 {
+        if normal.y < 0.5 {
+            return normal;
+        }
+        let (dx, dz) = self.gradient(point, time);
+        Vec3::new(-dx * self.amplitude, 1.0, -dz * self.amplitude).normalized()
+    }
+}
+
+/// Óptica de agua profunda para superficies con Fresnel: cuánto absorbe por
+/// unidad de profundidad cada canal y el color al que tiende lo profundo
+#[derive(Debug, Clone, Copy)]
+pub struct WaterOptics {
+    pub absorption: Vec3,
+    pub deep_color: Vec3,
+    /// Profundidad usada cuando el rayo refractado no toca fondo
+    pub max_depth: f32,
+}
+
+impl Default for WaterOptics {
+    fn default() -> Self {
+        WaterOptics {
+            absorption: Vec3::new(0.45, 0.12, 0.08),
+            deep_color: Vec3::new(0.02, 0.12, 0.2),
+            max_depth: 20.0,
+        }
+    }
+}
+
+impl WaterOptics {
+    /// Fracción de luz que sobrevive tras recorrer `depth` (Beer-Lambert)
+    pub fn transmittance(&self, depth: f32) -> Vec3 {
+        Vec3::new(
+            (-self.absorption.x * depth).exp(),
+            (-self.absorption.y * depth).exp(),
+            (-self.absorption.z * depth).exp(),
+        )
+    }
+}
@@ -0,0 +1,55 @@
+use crate::math::Vec3;
+
+use crate::geometry::{self, Aabb};
+use crate::material::Material;
+use crate::ray_intersect::{CubeFace, Intersect, RayIntersect};
+
+/// Plano de agua horizontal y rectangular a altura `height`, limitado en X/Z.
+/// Reemplaza cientos de cubos transparentes para lagunas y estanques.
+#[derive(Debug, Clone)]
+pub struct WaterPlane {
+    pub height: f32,
+    pub min: (f32, f32),
+    pub max: (f32, f32),
+    pub material: Material,
+}
+
+impl WaterPlane {
+    pub fn new(height: f32, min: (f32, f32), max: (f32, f32), material: Material) -> Self {
+        WaterPlane { height, min, max, material }
+    }
+}
+
+impl RayIntersect for WaterPlane {
+    fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Intersect {
+        let up = Vec3::new(0.0, 1.0, 0.0);
+        let Some(t) = geometry::ray_plane(ray_origin, ray_direction, Vec3::new(0.0, self.height, 0.0), up) else {
+            return Intersect::empty();
+        };
+
+        let point = *ray_origin + *ray_direction * t;
+        if point.x < self.min.0 || point.x > self.max.0 || point.z < self.min.1 || point.z > self.max.1 {
+            return Intersect::empty();
+        }
+
+        // UV en unidades de mundo para que la textura (si hay) se repita
+        let u = point.x - point.x.floor();
+        let v = point.z - point.z.floor();
+        Intersect::new(
+            point,
+            Vec3::new(0.0, 1.0, 0.0),
+            t,
+            self.material.clone(),
+            u,
+            v,
+            CubeFace::Top,
+        )
+    }
+
+    fn bounds(&self) -> Option<Aabb> {
+        Some(Aabb::new(
+            Vec3::new(self.min.0, self.height, self.min.1),
+            Vec3::new(self.max.0, self.height, self.max.1),
+        ))
+    }
+}
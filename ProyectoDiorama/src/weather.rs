@@ -0,0 +1,187 @@
+use crate::math::Vec3;
+use crate::particles::ParticleHit;
+use crate::rng::Rng;
+
+/// Tipo de precipitación
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Precipitation {
+    /// Gotas como trazos verticales finos, rápidos y casi transparentes
+    Rain,
+    /// Copos como discos blancos que caen lento y se mecen
+    Snow,
+}
+
+impl Precipitation {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "rain" => Some(Precipitation::Rain),
+            "snow" => Some(Precipitation::Snow),
+            _ => None,
+        }
+    }
+}
+
+/// Lluvia o nieve sobre el diorama. Como los emisores de partículas, no
+/// guarda estado: el volumen se divide en columnas de 1×1 en XZ, cada
+/// columna tiene unas pocas gotas con posición y fase sacadas de un hash, y
+/// su altura en cualquier instante se deriva de `time`. Un rayo recorre
+/// solo las columnas que cruza.
+#[derive(Debug, Clone)]
+pub struct Weather {
+    pub kind: Precipitation,
+    /// Gotas (o copos) por columna
+    pub drops: u32,
+    /// Volumen donde cae la precipitación
+    pub min: Vec3,
+    pub max: Vec3,
+    pub seed: u64,
+}
+
+impl Weather {
+    /// `intensity` 1 es una lluvia (o nevada) moderada
+    pub fn new(kind: Precipitation, intensity: f32, min: Vec3, max: Vec3) -> Self {
+        Weather {
+            kind,
+            drops: (intensity * 3.0).round().max(1.0) as u32,
+            min,
+            max,
+            seed: 0x3E47,
+        }
+    }
+
+    /// Velocidad de caída, largo del trazo, radio, color y opacidad
+    fn look(&self) -> (f32, f32, f32, Vec3, f32) {
+        match self.kind {
+            Precipitation::Rain => (14.0, 0.6, 0.012, Vec3::new(0.75, 0.8, 0.9), 0.35),
+            Precipitation::Snow => (1.2, 0.0, 0.05, Vec3::new(0.95, 0.95, 1.0), 0.9),
+        }
+    }
+
+    /// Cuánto se oscurece el color de una superficie con esta normal: las
+    /// caras de arriba quedan mojadas con lluvia (la nieve no moja)
+    pub fn wetness(&self, normal: Vec3) -> f32 {
+        match self.kind {
+            Precipitation::Rain if normal.y > 0.5 => 0.65,
+            _ => 1.0,
+        }
+    }
+
+    /// Tramo `[entrada, salida]` del rayo dentro del volumen
+    fn clip(&self, origin: &Vec3, direction: &Vec3) -> Option<(f32, f32)> {
+        let o = [origin.x, origin.y, origin.z];
+        let d = [direction.x, direction.y, direction.z];
+        let lo = [self.min.x, self.min.y, self.min.z];
+        let hi = [self.max.x, self.max.y, self.max.z];
+        let (mut t0, mut t1) = (0.0f32, f32::INFINITY);
+        for axis in 0..3 {
+            if d[axis] == 0.0 {
+                if o[axis] < lo[axis] || o[axis] > hi[axis] {
+                    return None;
+                }
+                continue;
+            }
+            let a = (lo[axis] - o[axis]) / d[axis];
+            let b = (hi[axis] - o[axis]) / d[axis];
+            t0 = t0.max(a.min(b));
+            t1 = t1.min(a.max(b));
+        }
+        (t0 < t1).then_some((t0, t1))
+    }
+
+    /// Gota (o copo) más cercana que cruza el rayo antes de `max_distance`
+    pub fn hit(&self, origin: &Vec3, direction: &Vec3, time: f32, max_distance: f32) -> Option<ParticleHit> {
+        let (t0, t1) = self.clip(origin, direction)?;
+        let t1 = t1.min(max_distance);
+        if t0 >= t1 {
+            return None;
+        }
+
+        // Recorrido de las columnas que cruza el rayo (DDA en XZ)
+        let start = *origin + *direction * t0;
+        let (mut cx, mut cz) = (start.x.floor() as i32, start.z.floor() as i32);
+        let axis = |o: f32, d: f32, cell: i32| -> (i32, f32, f32) {
+            if d > 0.0 {
+                (1, (cell as f32 + 1.0 - o) / d, 1.0 / d)
+            } else if d < 0.0 {
+                (-1, (cell as f32 - o) / d, -1.0 / d)
+            } else {
+                (0, f32::INFINITY, f32::INFINITY)
+            }
+        };
+        let (step_x, mut next_x, delta_x) = axis(origin.x, direction.x, cx);
+        let (step_z, mut next_z, delta_z) = axis(origin.z, direction.z, cz);
+
+        let mut best: Option<ParticleHit> = None;
+        loop {
+            let limit = best.map_or(t1, |b| b.distance);
+            if let Some(hit) = self.hit_column((cx, cz), origin, direction, time, (t0, limit)) {
+                best = Some(hit);
+            }
+            let exit = next_x.min(next_z);
+            if exit >= best.map_or(t1, |b| b.distance) {
+                return best;
+            }
+            if next_x < next_z {
+                cx += step_x;
+                next_x += delta_x;
+            } else {
+                cz += step_z;
+                next_z += delta_z;
+            }
+        }
+    }
+
+    /// Impacto más cercano dentro de `span` (de–hasta, en `t`) con las
+    /// gotas de la columna `cell`
+    fn hit_column(
+        &self,
+        cell: (i32, i32),
+        origin: &Vec3,
+        direction: &Vec3,
+        time: f32,
+        span: (f32, f32),
+    ) -> Option<ParticleHit> {
+        let ((cx, cz), (from, limit)) = (cell, span);
+        let (speed, length, radius, color, opacity) = self.look();
+        let height = self.max.y - self.min.y;
+        let mut best: Option<ParticleHit> = None;
+
+        for k in 0..self.drops {
+            let cell = Vec3::new(cx as f32, k as f32, cz as f32);
+            let mut rng = Rng::at_point(self.seed, cell);
+            let (fx, fz, phase) = (rng.next_f32(), rng.next_f32(), rng.next_f32());
+            let mut x = cx as f32 + 0.2 + fx * 0.6;
+            let mut z = cz as f32 + 0.2 + fz * 0.6;
+            let y = self.max.y - (phase * height + time * speed).rem_euclid(height);
+            if self.kind == Precipitation::Snow {
+                let sway = time * 1.5 + phase * 40.0;
+                x += sway.sin() * 0.15;
+                z += (sway * 0.7).cos() * 0.15;
+            }
+
+            // Punto del rayo más cercano al eje vertical de la gota
+            let flat = direction.x * direction.x + direction.z * direction.z;
+            let t = if length > 0.0 && flat > 1e-8 {
+                ((x - origin.x) * direction.x + (z - origin.z) * direction.z) / flat
+            } else {
+                (Vec3::new(x, y, z) - *origin).dot(*direction) / direction.dot(*direction)
+            };
+            if t <= from || t >= best.map_or(limit, |b| b.distance) {
+                continue;
+            }
+            let p = *origin + *direction * t;
+            let (dx, dz) = (p.x - x, p.z - z);
+            let inside = if length > 0.0 {
+                // Trazo: de `y` hacia arriba, lo que cayó en el último instante
+                dx * dx + dz * dz <= radius * radius && (y..=y + length).contains(&p.y)
+            } else {
+                (p - Vec3::new(x, y, z)).length() <= radius
+            };
+            if inside {
+                best = Some(ParticleHit { distance: t, color, opacity });
+            }
+        }
+
+        best
+    }
+}
@@ -0,0 +1,979 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::animation::{self, ModelMotion};
+use crate::camera::Camera;
+use crate::cube::Cube;
+use crate::error::{Error, Result};
+use crate::light::{Light, LightPath};
+use crate::material::{vector3_to_color, Material, MaterialLibrary};
+use crate::math::{Rgba, Vec3};
+use crate::ray_intersect::RayIntersect;
+use crate::scene::{self, Scene};
+use crate::scene_file::SceneFile;
+use crate::sky::{Sky, Sun};
+use crate::texture_manager::TextureManager;
+use crate::voxel_world::VoxelWorld;
+use crate::{
+    emissive, heightmap, irradiance_cache, lightmap, lod, matcap, occluder_cache, particles,
+    photon_map, post, scene_bounds, script, shapes, sky_light, sprites, stamps, stats, terrain,
+    textures, toon, transform, voxel_text, voxelizer, water, water_plane, weather,
+};
+
+/// Ajustes del armado que no vienen del archivo de escena (el binario los
+/// toma de la línea de comandos)
+#[derive(Debug, Clone)]
+pub struct WorldOptions {
+    /// Tamaño de la imagen, para la caché de oclusores por píxel
+    pub width: usize,
+    pub height: usize,
+    /// PNG en escala de grises a extruir como terreno bajo el diorama
+    pub heightmap: Option<String>,
+    /// Altura máxima (en vóxeles) del terreno del heightmap
+    pub heightmap_height: i32,
+    /// Bandas de material del heightmap, p. ej. `0.3:B,0.7:W,1.0:Q`
+    pub heightmap_bands: String,
+    /// Lado del terreno procedural (Perlin) alrededor del diorama; 0 = sin terreno
+    pub terrain: i32,
+    /// Semilla del terreno procedural
+    pub terrain_seed: u64,
+    /// Fotones a trazar para cáusticas; 0 = desactivado
+    pub caustics: usize,
+    /// Rayos por muestra de la caché de irradiancia; 0 = sin luz indirecta
+    pub gi: u32,
+    /// Rechaza muestras de GI con más de este múltiplo del brillo medio; 0 = no
+    pub reject_outliers: f32,
+    /// Intensidad de la luz difusa del cielo; 0 = desactivada
+    pub sky_light: f32,
+    /// Dirección `x,y,z` hacia el disco del sol (reemplaza al de la escena)
+    pub sun: Option<(f32, f32, f32)>,
+    /// Sombreado matcap de depuración: `clay` o la ruta de una imagen de esfera
+    pub matcap: Option<String>,
+    /// Bandas de luz del estilo caricatura si la escena no define `cel`; 0 = desactivado
+    pub toon: u32,
+    /// Resolución de los lightmaps por cara; 0 = sin horneado
+    pub bake: usize,
+    /// Rayos de sombra por punto; 1 = sombras duras
+    pub shadow_samples: u32,
+    /// Rayos primarios por píxel (antialiasing)
+    pub spp: u32,
+    /// Diámetro de la lente para profundidad de campo; 0 = todo enfocado
+    pub aperture: f32,
+    /// Distancia de enfoque; si falta, la del centro de la cámara
+    pub focus: Option<f32>,
+    /// Radio de la luz principal para sombras suaves
+    pub light_radius: f32,
+    /// Radio del círculo que recorre la luz alrededor del diorama; 0 = luz fija
+    pub light_orbit: f32,
+    /// Radianes por segundo de la luz en su círculo
+    pub light_orbit_speed: f32,
+    /// Cuánto sube y baja la luz mientras da vueltas
+    pub light_bob: f32,
+    /// Giro fijo del diorama completo alrededor del eje Y (grados)
+    pub model_yaw: f32,
+    /// Escala uniforme del diorama completo
+    pub model_scale: f32,
+    /// Desplazamiento del diorama completo, `x,y,z`
+    pub model_offset: (f32, f32, f32),
+    /// Velocidad de giro del diorama sobre su pedestal (grados por segundo)
+    pub model_spin: f32,
+    /// Semilla del muestreo aleatorio; misma semilla, misma imagen
+    pub seed: u64,
+    /// Rebotes máximos por rayo; si falta, el de la escena o el por defecto
+    pub max_depth: Option<u32>,
+    /// Irradiancia bajo la cual un bloque emisivo no ilumina un punto
+    pub light_cutoff: f32,
+    /// Radiancia máxima por muestra de GI; 0 = sin límite
+    pub firefly_clamp: f32,
+    /// Tramado ordenado en toda salida de 8 bits
+    pub dither: bool,
+    /// Distancia desde la que los trozos del mundo usan media resolución; 0 = no
+    pub lod: f32,
+}
+
+impl Default for WorldOptions {
+    fn default() -> Self {
+        WorldOptions {
+            width: 1300,
+            height: 900,
+            heightmap: None,
+            heightmap_height: 6,
+            heightmap_bands: "0.35:B,0.75:W,1.0:Q".to_string(),
+            terrain: 0,
+            terrain_seed: 1,
+            caustics: 0,
+            gi: 0,
+            reject_outliers: 0.0,
+            sky_light: 0.0,
+            sun: None,
+            matcap: None,
+            toon: 0,
+            bake: 0,
+            shadow_samples: 1,
+            spp: 1,
+            aperture: 0.0,
+            focus: None,
+            light_radius: 1.5,
+            light_orbit: 0.0,
+            light_orbit_speed: 0.6,
+            light_bob: 4.0,
+            model_yaw: 0.0,
+            model_scale: 1.0,
+            model_offset: (0.0, 0.0, 0.0),
+            model_spin: 0.0,
+            seed: 1,
+            max_depth: None,
+            light_cutoff: 0.005,
+            firefly_clamp: 0.0,
+            dither: false,
+            lod: 0.0,
+        }
+    }
+}
+
+/// Diorama ya armado que `build` presta a quien lo muestra: la escena lista
+/// para renderizar y lo que hace falta para moverla y editarla
+pub struct Diorama<'a> {
+    /// Cámara inicial, en el espacio del mundo (antes de `motion`)
+    pub camera: Camera,
+    /// Giro, escala y desplazamiento del diorama completo
+    pub motion: &'a ModelMotion,
+    /// Luz principal en el espacio del mundo; `scene.light` es la del
+    /// modelo en t = 0
+    pub light: &'a Light,
+    pub scene: Scene<'a>,
+    pub world: &'a VoxelWorld,
+    pub materials: &'a MaterialLibrary,
+    pub textures: &'a TextureManager,
+}
+
+/// Texturas de los bloques del diorama (carpeta `assets`)
+pub fn load_textures() -> Result<TextureManager> {
+
+    let mut texture_manager = TextureManager::default();
+    let black_texture = textures::Texture::load("./assets/wool_colored_black.png")?;
+    let white_texture = textures::Texture::load("./assets/wool_colored_white.png")?;
+    let red_texture   = textures::Texture::load("./assets/wool_colored_red.png")?;
+    let yellow_texture= textures::Texture::load("./assets/wool_colored_yellow.png")?;
+    let blackstone_texture = textures::Texture::load("./assets/blackstone_top.png")?;
+    let glowstone_texture = textures::Texture::load("./assets/glowstone.png")?;
+    let quartz_texture = textures::Texture::load("./assets/quartz_block_top.png")?;
+    let redstone_texture = textures::Texture::load("./assets/redstone_block.png")?;
+
+    texture_manager.add_texture('n', black_texture);
+    texture_manager.add_texture('w', white_texture);
+    texture_manager.add_texture('r', red_texture);
+    texture_manager.add_texture('y', yellow_texture);
+    texture_manager.add_texture('B', blackstone_texture);
+    texture_manager.add_texture('G', glowstone_texture);
+    texture_manager.add_texture('Q', quartz_texture);
+    texture_manager.add_texture('S', redstone_texture);
+    Ok(texture_manager)
+}
+
+/// Materiales de los bloques del diorama, del terreno procedural y de las
+/// estructuras (árboles); los bloques parciales se agregan en `build`
+pub fn default_materials() -> MaterialLibrary {
+
+    let mat_black = Material::with_texture(Vec3::new(0.5, 0.5, 0.5), 10.0, [0.9, 0.1, 0.0, 0.0], 0.0, 'n');
+    let mat_white = Material::with_texture(Vec3::new(0.5, 0.5, 0.5), 10.0, [0.9, 0.1, 0.0, 0.0], 0.0, 'w');
+    let mat_red   = Material::with_texture(Vec3::new(0.5, 0.5, 0.5), 10.0, [0.9, 0.1, 0.0, 0.0], 0.0, 'r');
+    let mat_yellow= Material::with_texture(Vec3::new(0.5, 0.5, 0.5), 10.0, [0.9, 0.1, 0.0, 0.0], 0.0, 'y');
+    // --- Materiales nuevos ---
+    let mat_blackstone = Material::with_texture(
+        Vec3::new(0.0, 0.0, 0.0), // negro completo
+        25.0,                        // rugosidad
+        [0.0, 0.0, 0.0, 0.0],        // sin especular, sin emisión
+        0.0,                         // reflectividad
+        'B'                           // símbolo
+    );
+
+    let mat_glowstone = Material::with_texture(
+    Vec3::new(1.0, 0.85, 0.4), // tono dorado-amarillo
+    5.0,                          // un poco de rugosidad (no espejo)
+    [0.8, 0.1, 0.1, 1.5],         // fuerte difusión, poca reflexión, algo especular, emisión fuerte
+    0.0,                          // no refracta
+    'G'                           // símbolo
+)
+    .with_emission(1.2);
+
+
+    let mat_quartz = Material::with_texture(
+        Vec3::new(1.0, 1.0, 1.0), // blanco puro
+        50.0,                         
+        [0.9, 0.9, 0.9, 0.0],        // especular alta para reflejar
+        0.3,                          // completamente reflectivo
+        'Q'                           // símbolo
+    );
+
+    let mat_redstone = Material::with_texture(
+        Vec3::new(0.8, 0.0, 0.0), // rojo oscuro
+        25.0,                         
+        [0.5, 0.0, 0.0, 0.0],        
+        0.2,                    
+        'S'                             
+    );
+
+
+    let mut materials = MaterialLibrary::default();
+    materials.insert('W', mat_white);
+    materials.insert('N', mat_black);
+    materials.insert('R', mat_red);
+    materials.insert('Y', mat_yellow);
+    materials.insert('B', mat_blackstone);
+    materials.insert('G', mat_glowstone);
+    materials.insert('Q', mat_quartz);
+    materials.insert('S', mat_redstone);
+
+    // --- Materiales del terreno procedural (sin textura) ---
+    materials.insert(terrain::GRASS, Material::solid(Vec3::new(0.3, 0.65, 0.2), 8.0, [0.9, 0.05, 0.0, 0.0], 0.0));
+    materials.insert(terrain::DIRT, Material::solid(Vec3::new(0.45, 0.3, 0.18), 4.0, [0.9, 0.02, 0.0, 0.0], 0.0));
+    materials.insert(terrain::STONE, Material::solid(Vec3::new(0.5, 0.5, 0.5), 12.0, [0.85, 0.1, 0.0, 0.0], 0.0));
+    materials.insert(stamps::WOOD, Material::solid(Vec3::new(0.4, 0.27, 0.13), 6.0, [0.9, 0.05, 0.0, 0.0], 0.0));
+    materials.insert(stamps::LEAVES, Material::solid(Vec3::new(0.2, 0.5, 0.15), 4.0, [0.9, 0.02, 0.0, 0.0], 0.0));
+    materials.insert(
+        terrain::WATER,
+        Material::solid(Vec3::new(0.15, 0.35, 0.6), 60.0, [0.4, 0.5, 0.2, 0.4], 1.33)
+            .with_waves(water::Waves::default()),
+    );
+    materials
+}
+
+/// Capas del diorama (Pikachu sobre su pedestal y la Pokeball), de abajo
+/// hacia arriba; ver `VoxelWorld::from_layers`
+pub fn default_layers() -> Vec<Vec<&'static str>> {
+
+    vec![
+        // Layer 1
+        vec![
+            "0000000000",
+            "0000000000",
+            "0000000000",
+            "0000WW0000",
+            "0000WW0000",
+            "0000000000",
+            "0000000000",
+            "0000000000",
+            "0000000000",
+            "0000000000",
+        ],
+        // Layer 2
+        vec![
+            "0000000000",
+            "0000000000",
+            "0000WWWW00",
+            "000W00W000",
+            "000W00W000",
+            "0000WWWW00",
+            "0000000000",
+            "0000000000",
+            "0000000000",
+            "0000000000",
+        ],
+        // Layer 3  (interpreted from tu matrix con w/o)
+        vec![
+            "0000000000",
+            "0000000000",
+            "00WWWWWW00",
+            "00WOOOOOW0",
+            "00WOOOOOW0",
+            "00WOOOOOW0",
+            "00WOOOOOW0",
+            "00WWWWWW00",
+            "0000000000",
+            "0000000000",
+        ],
+        // Layer 4 (borde W)
+        vec![
+            "0000000000",
+            "0WWWWWWWW0",
+            "0W0000000W",
+            "0W0000000W",
+            "0W0000000W",
+            "0W0000000W",
+            "0W0000000W",
+            "0W0000000W",
+            "0W0000000W",
+            "0000000000",
+        ],
+        // Layer 5 (negra alrededor, N en interior)
+        vec![
+            "WWWWWWWWWW",
+            "WGGGGGGGGW",
+            "WGGGGGGGGW",
+            "0GGGGGGGGGW",
+            "0GGGGGGGGW",
+            "0GGGGGGGGW",
+            "0GGGGGGGGW",
+            "WGGGGGGGGW",
+            "WGGGGGGGGW",
+            "WWWWWWWWWW",
+        ],
+        // Pikachu - Layer 6 (piernas delanteras)
+    vec![
+        "0000000000",
+        "0000000000",
+        "000000Y0Y0",
+        "0000000000",
+        "000000Y0Y0",
+        "0000000000",
+        "0000000000",
+        "0000000000",
+        "0000000000",
+        "0000000000",
+    ],
+
+    // Pikachu - Layer 7 (piernas traseras)
+    vec![
+        "0000000000",
+        "0000000000",
+        "000000Y0Y0",
+        "0000000000",
+        "000000Y0Y0",
+        "0000000000",
+        "0000000000",
+        "0000000000",
+        "0000000000",
+        "0000000000",
+    ],
+
+    // Pikachu - Layer 8 (cuerpo central con base cola)
+    vec![
+        "0000000000",
+        "0000000000",
+        "000SYYYYY0",
+        "000NYYYYY0",
+        "000SYYYYY0",
+        "0000000000",
+        "0000000000",
+        "0000000000",
+        "0000000000",
+        "0000000000",
+    ],
+
+    // Pikachu - Layer 9 (cabeza con mejillas rojas)
+    vec![
+        "0000000000",
+        "0000000000",
+        "000YYYYYY0",
+        "000YYYYYY",
+        "000YYYYYY0",
+        "0000000000",
+        "0000000000",
+        "0000000000",
+        "0000000000",
+        "0000000000",
+    ],
+
+    // Pikachu - Layer 10 (cabeza + fin del cuerpo + cola negra)
+    vec![
+        "0000000000",
+        "0000000000",
+        "000NYY0000",
+        "000YYY000Y",
+        "000NYY0000",
+        "0000000000",
+        "0000000000",
+        "0000000000",
+        "0000000000",
+        "0000000000",
+    ],
+
+    // Pikachu - Layer 11 (ojos negros)
+    vec![
+        "0000000000",
+        "0000000000",
+        "000YYY0000",
+        "000YYY000N",
+        "000YYY0000",
+        "0000000000",
+        "0000000000",
+        "0000000000",
+        "0000000000",
+        "0000000000",
+    ],
+
+    // Pikachu - Layer 12 (antenas en la cabeza)
+    vec![
+        "0000000000",
+        "0000000000",
+        "00000Y0000",
+        "0000000000",
+        "00000Y0000",
+        "0000000000",
+        "0000000000",
+        "0000000000",
+        "0000000000",
+        "0000000000",
+    ],
+    // Pikachu - Layer 13 (antenas en la cabeza)
+    vec![
+        "0000000000",
+        "0000000000",
+        "00000N0000",
+        "0000000000",
+        "00000N0000",
+        "0000000000",
+        "0000000000",
+        "0000000000",
+        "0000000000",
+        "0000000000",
+    ],
+
+    // --- Pokeball superior ---
+    // Layer 2 (vacía, para elevar)
+    vec![
+        "0000000000",
+        "0000000000",
+        "B000000000",
+        "B000000000",
+        "B000000000",
+        "B000000000",
+        "B000000000",
+        "B000000000",
+        "0000000000",
+        "0000000000",
+    ],
+
+    vec![
+        "0000000000",
+        "0000000000",
+        "B000000000",
+        "Q000000000",
+        "Q000000000",
+        "Q000000000",
+        "Q000000000",
+        "B000000000",
+        "0000000000",
+        "0000000000",
+    ],
+
+    // Layer 3 (capa exterior superior)
+    vec![
+        "BBBBBBBBBB",
+        "B00000000B",
+        "B00000000B",
+        "Q00000000B",
+        "Q00000000B",
+        "Q00000000B",
+        "Q00000000B",
+        "B00000000B",
+        "B00000000B",
+        "BBBBBBBBBB",
+    ],
+
+    // Layer 4
+    vec![
+        "0000000000",
+        "0RRRRRRRR0",
+        "B00000000R",
+        "Q00000000R",
+        "Q00000000R",
+        "Q00000000R",
+        "Q00000000R",
+        "B00000000R",
+        "0RRRRRRRR0",
+        "0000000000",
+    ],
+
+    // Layer 5
+    vec![
+        "0000000000",
+        "0000000000",
+        "B0RRRRRR00",
+        "BR000000R0",
+        "BR000000R0",
+        "BR000000R0",
+        "BR000000R0",
+        "B0RRRRRR00",
+        "0000000000",
+        "0000000000",
+    ],
+
+    // Layer 6
+    vec![
+        "0000000000",
+        "0000000000",
+        "0000000000",
+        "0000RRRR000",
+        "000RR00R000",
+        "000RR00R000",
+        "0000RRRR000",
+        "0000000000",
+        "0000000000",
+        "0000000000",
+    ],
+
+    // Layer 7
+    vec![
+        "0000000000",
+        "0000000000",
+        "0000000000",
+        "0000000000",
+        "0000RR0000",
+        "0000RR0000",
+        "0000000000",
+        "0000000000",
+        "0000000000",
+        "0000000000",
+    ],
+
+
+
+    ]
+}
+
+/// Aplica a cada material de `list` el ajuste de la directiva `directive`
+/// del archivo de escena; avisa de los símbolos sin material
+fn apply_material<T: Copy>(
+    materials: &mut MaterialLibrary,
+    list: &[(char, T)],
+    directive: &str,
+    apply: impl Fn(Material, T) -> Material,
+) {
+    for (symbol, value) in list {
+        match materials.get(*symbol).cloned() {
+            Some(mat) => materials.insert(*symbol, apply(mat, *value)),
+            None => eprintln!("Material desconocido para {}: {}", directive, symbol),
+        }
+    }
+}
+
+/// Arma el diorama con la escena `scene_def` y los ajustes `options` y se
+/// lo presta a `show`, que lo renderiza o lo muestra; lo armado vive solo
+/// mientras dura la llamada. Con `keep_camera` (al recargar) se parte de esa
+/// cámara en vez de la inicial.
+///
+/// ```no_run
+/// use diorama::scene_file::SceneFile;
+/// use diorama::world::{self, WorldOptions};
+///
+/// let options = WorldOptions { width: 320, height: 240, ..Default::default() };
+/// let pixels = world::build(&SceneFile::default(), &options, None, |d| {
+///     Ok(diorama::render(320, 240, &d.motion.camera_at(&d.camera, 0.0), &d.scene))
+/// })?;
+/// # Ok::<(), diorama::error::Error>(())
+/// ```
+pub fn build<R>(
+    scene_def: &SceneFile,
+    options: &WorldOptions,
+    keep_camera: Option<Camera>,
+    show: impl FnOnce(Diorama) -> Result<R>,
+) -> Result<R> {
+    // --- Texturas y materiales ---
+    let mut texture_manager = load_textures()?;
+    let mut materials = default_materials();
+
+    // --- Sprites en cruz (plantas, antorchas) con su propia textura ---
+    let sprite_kinds: Vec<_> = sprites::ALL
+        .iter()
+        .map(|art| {
+            let texture = art.texture();
+            texture_manager.add_texture(art.symbol, texture.clone());
+            let material = Material::with_texture(Vec3::new(0.5, 0.5, 0.5), 5.0, [0.9, 0.05, 0.0, 0.0], 0.0, art.symbol);
+            (art.symbol, material, Arc::new(texture))
+        })
+        .collect();
+
+    // --- Bloques parciales: mismo material que su bloque completo ---
+    for block in &shapes::PARTIAL_BLOCKS {
+        let material = scene_def
+            .partials
+            .iter()
+            .rev()
+            .find(|(shape, _)| *shape == block.symbol)
+            .map_or(block.material, |&(_, material)| material);
+        match materials.get(material).cloned() {
+            Some(mat) => materials.insert(block.symbol, mat),
+            None => eprintln!("Material desconocido para partial: {}", material),
+        }
+    }
+
+    // --- Construcción de cubos ---
+    let layers = default_layers();
+    let mut world = VoxelWorld::from_layers(&layers, &materials);
+    let plant_sprites = sprites::from_layers(&layers, &sprite_kinds);
+
+    // --- Archivo de escena (opcional) ---
+    for placement in &scene_def.stamps {
+        if let Some(stamp) = stamps::find(&placement.name) {
+            stamp.apply(&mut world, placement.position);
+        }
+    }
+    let mut script_light = None;
+    for path in &scene_def.scripts {
+        let output = script::run(path)?;
+        for m in &output.materials {
+            materials.insert(m.symbol, Material::solid(m.color, 8.0, [0.9, 0.05, m.reflectivity, 0.0], 0.0));
+        }
+        for (pos, symbol) in &output.blocks {
+            world.set(*pos, *symbol);
+        }
+        println!("Script {}: {} bloques", path, output.blocks.len());
+        script_light = output.light.or(script_light);
+    }
+    for label in &scene_def.labels {
+        voxel_text::stamp_text(&mut world, &label.text, label.position, label.symbol);
+    }
+    if !scene_def.voxelize.is_empty() {
+        let mut palette: Vec<(char, Rgba)> = materials
+            .iter()
+            .map(|(symbol, mat)| (symbol, mat.base_color(&texture_manager)))
+            .collect();
+        palette.sort_by_key(|(symbol, _)| *symbol);
+        for job in &scene_def.voxelize {
+            let mesh = voxelizer::ObjMesh::load(&job.obj_path)?;
+            let texture = job.texture.as_deref().map(textures::Texture::load).transpose()?;
+            let default_symbol = job.default_symbol.unwrap_or(voxelizer::DEFAULT_SYMBOL);
+            if materials.get(default_symbol).is_none() {
+                eprintln!("Material desconocido para voxelize: {}", default_symbol);
+            }
+            let placed = voxelizer::voxelize(
+                &mesh,
+                job.resolution,
+                job.position,
+                texture.as_ref(),
+                &palette,
+                default_symbol,
+                &mut world,
+            );
+            println!("Voxelizado {}: {} vóxeles", job.obj_path, placed);
+        }
+    }
+
+    // --- Terreno procedural (opcional) ---
+    if options.terrain > 0 {
+        let options = terrain::TerrainOptions {
+            size: options.terrain,
+            seed: options.terrain_seed,
+            ..Default::default()
+        };
+        let placed = terrain::generate_terrain(&mut world, &options);
+        println!("Terreno procedural: {} vóxeles", placed);
+    }
+
+    // --- Terreno desde heightmap (opcional), centrado bajo el diorama ---
+    if let Some(path) = &options.heightmap {
+        let (w, h) = image::image_dimensions(path)
+            .map_err(|source| Error::Heightmap { path: path.clone(), source })?;
+        let options = heightmap::HeightmapOptions {
+            max_height: options.heightmap_height,
+            origin: (5 - w as i32 / 2, -options.heightmap_height, 5 - h as i32 / 2),
+            bands: heightmap::parse_bands(&options.heightmap_bands)?,
+        };
+        let placed = heightmap::stamp_heightmap(&mut world, path, &options)?;
+        println!("Heightmap {}: {} vóxeles", path, placed);
+    }
+    apply_material(&mut materials, &scene_def.tints, "tint", Material::with_tint);
+    // Grupos de luces: cada símbolo queda solo en los grupos que se le dan
+    let mut groups: HashMap<char, u32> = HashMap::new();
+    for (symbol, bit) in &scene_def.light_groups {
+        *groups.entry(symbol.to_ascii_uppercase()).or_default() |= bit;
+    }
+    let groups: Vec<(char, u32)> = groups.into_iter().collect();
+    apply_material(&mut materials, &groups, "group", Material::with_light_groups);
+    apply_material(&mut materials, &scene_def.flickers, "flicker", Material::with_flicker);
+    apply_material(&mut materials, &scene_def.dispersions, "dispersion", Material::with_dispersion);
+    apply_material(&mut materials, &scene_def.subsurfaces, "subsurface", Material::with_subsurface);
+    apply_material(&mut materials, &scene_def.films, "film", Material::with_thin_film);
+    apply_material(&mut materials, &scene_def.anisotropies, "aniso", Material::with_anisotropy);
+    apply_material(&mut materials, &scene_def.clearcoats, "clearcoat", Material::with_clearcoat);
+    apply_material(&mut materials, &scene_def.bevels, "bevel", Material::with_bevel);
+    apply_material(&mut materials, &scene_def.tiles, "tile", |mat, tiling| {
+        mat.with_uv_tiling(tiling.scale, tiling.offset)
+    });
+    apply_material(&mut materials, &scene_def.face_uvs, "face_uv", |mat, (face, transform)| {
+        mat.with_face_uv(face, transform)
+    });
+    let mut cubes: Vec<Cube> = world.to_cubes(&materials);
+    for placement in &scene_def.boxes {
+        match materials.get(placement.symbol) {
+            Some(mat) => cubes.push(Cube::from_bounds(placement.min, placement.max, mat.clone())),
+            None => eprintln!("Material desconocido para box: {}", placement.symbol),
+        }
+    }
+
+    // Material de la nieve acumulada, si la escena la pide
+    let snow_material = scene_def.snow.and_then(|symbol| {
+        let material = materials.get(symbol).cloned();
+        if material.is_none() {
+            eprintln!("Material desconocido para snow: {}", symbol);
+        }
+        material
+    });
+
+    let water_material = Material::solid(Vec3::new(0.1, 0.3, 0.45), 80.0, [0.0, 0.6, 0.0, 0.0], 1.33)
+        .with_waves(water::Waves::default())
+        .with_water_optics(water::WaterOptics::default());
+    let water_planes: Vec<water_plane::WaterPlane> = scene_def
+        .water
+        .iter()
+        .map(|w| water_plane::WaterPlane::new(w.height, w.min, w.max, water_material.clone()))
+        .collect();
+
+    // Cajas giradas: un nodo por caja bajo un nodo raíz de decoraciones
+    let decorations = scene_def
+        .obbs
+        .iter()
+        .filter_map(|obb| {
+            let Some(mat) = materials.get(obb.symbol) else {
+                eprintln!("Material desconocido para obb: {}", obb.symbol);
+                return None;
+            };
+            let half = obb.size * 0.5;
+            let placement = transform::Transform::new(obb.center, obb.yaw, 0.0, 0.0, 1.0);
+            Some(transform::Node::new(placement).with_object(Cube::from_bounds(-half, half, mat.clone())))
+        })
+        .fold(transform::Node::default(), transform::Node::with_child);
+
+    // Instancias: los cubos de cada estructura se arman una sola vez y se
+    // comparten entre todas sus copias
+    let mut prototypes: HashMap<&str, transform::Node> = HashMap::new();
+    for placement in &scene_def.instances {
+        let Some(stamp) = stamps::find(&placement.name) else {
+            continue;
+        };
+        prototypes.entry(stamp.name).or_insert_with(|| {
+            let mut local = VoxelWorld::default();
+            stamp.apply(&mut local, (0, 0, 0));
+            local
+                .to_cubes(&materials)
+                .into_iter()
+                .fold(transform::Node::default(), transform::Node::with_object)
+        });
+    }
+    let instances: Vec<transform::Instance> = scene_def
+        .instances
+        .iter()
+        .filter_map(|placement| {
+            let prototype = prototypes.get(placement.name.as_str())?;
+            let placement = transform::Transform::new(placement.position, placement.yaw, 0.0, 0.0, placement.scale);
+            Some(transform::Instance::new(prototype, placement))
+        })
+        .collect();
+
+    let emitters: Vec<particles::ParticleEmitter> = scene_def
+        .emitters
+        .iter()
+        .filter_map(|e| particles::ParticleEmitter::preset(&e.kind, e.position))
+        .map(|mut e| {
+            e.seed ^= options.seed.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+            e
+        })
+        .collect();
+
+    // Con --lod los cubos se agrupan en trozos que se simplifican de lejos
+    let lod_chunks = if options.lod > 0.0 { lod::build_chunks(&cubes, options.lod) } else { Vec::new() };
+    let mut objects: Vec<&dyn RayIntersect> = if lod_chunks.is_empty() {
+        cubes.iter().map(|c| c as &dyn RayIntersect).collect()
+    } else {
+        lod_chunks.iter().map(|c| c as &dyn RayIntersect).collect()
+    };
+    objects.extend(water_planes.iter().map(|w| w as &dyn RayIntersect));
+    objects.push(&decorations);
+    objects.extend(instances.iter().map(|i| i as &dyn RayIntersect));
+    objects.extend(plant_sprites.iter().map(|p| p as &dyn RayIntersect));
+
+    // --- Cámara ---
+    let mut camera = Camera::new(
+        Vec3::new(0.0, 15.0, 30.0),
+        Vec3::new(5.0, 5.0, 5.0),
+        Vec3::new(0.0, 1.0, 0.0),
+    );
+    camera.aperture = options.aperture;
+    if let Some(focus) = options.focus {
+        camera.focus_distance = focus;
+    }
+    if let Some(kept) = keep_camera {
+        camera = kept;
+    }
+
+    // --- Límites del diorama (centros de los cubos) ---
+    let mut min = Vec3::new(f32::INFINITY, f32::INFINITY, f32::INFINITY);
+    let mut max = Vec3::new(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY);
+    for cube in &cubes {
+        min = Vec3::new(min.x.min(cube.center.x), min.y.min(cube.center.y), min.z.min(cube.center.z));
+        max = Vec3::new(max.x.max(cube.center.x), max.y.max(cube.center.y), max.z.max(cube.center.z));
+    }
+
+    // --- Transformación global del diorama (gira sobre el centro de su base) ---
+    let (ox, oy, oz) = options.model_offset;
+    let motion = animation::ModelMotion {
+        pivot: Vec3::new((min.x + max.x) * 0.5, min.y - 0.5, (min.z + max.z) * 0.5),
+        offset: Vec3::new(ox, oy, oz),
+        yaw: options.model_yaw,
+        scale: options.model_scale,
+        spin: options.model_spin,
+    };
+
+    // --- Cielo (sol, nubes y noche opcionales) ---
+    let sun = match options.sun {
+        Some((x, y, z)) => Some(Sun::new(Vec3::new(x, y, z))),
+        None => scene_def.sun,
+    };
+    let sky = Sky { sun, clouds: scene_def.clouds.clone(), night: scene_def.night };
+
+    // --- Lluvia o nieve sobre el diorama (opcional) ---
+    let weather = scene_def.weather.map(|(kind, intensity)| {
+        let margin = Vec3::new(3.0, 1.0, 3.0);
+        let top = Vec3::new(0.0, 10.0, 0.0);
+        weather::Weather::new(kind, intensity, min - margin, max + margin + top)
+    });
+
+    // --- Luz ---
+
+    let world_light = match script_light {
+        Some(l) => Light::new(l.position, Rgba::new(255, 255, 255, 255), l.intensity),
+        None => Light::new(
+            Vec3::new(-20.0, 20.0, 15.0), // un poco más arriba y adelante
+            Rgba::new(255, 255, 255, 255),
+            // más intensidad; de noche solo queda la luz de la luna
+            3.0 * (1.0 - 0.9 * sky.darkness()),
+        ),
+    }
+    .with_radius(options.light_radius);
+    // Demo de iluminación: la luz da vueltas alrededor del diorama a la
+    // altura donde estaba, subiendo y bajando
+    let world_light = if options.light_orbit > 0.0 {
+        let center = Vec3::new((min.x + max.x) * 0.5, world_light.position.y, (min.z + max.z) * 0.5);
+        world_light.with_path(LightPath {
+            center,
+            radius: options.light_orbit,
+            speed: options.light_orbit_speed,
+            bob: options.light_bob,
+            bob_speed: 0.15,
+        })
+    } else {
+        world_light
+    };
+    let light2 = motion.light_at(&world_light, 0.0);
+    // Luces adicionales de la escena, fijas en el espacio del modelo (si el
+    // diorama gira, giran con él)
+    let extra_lights: Vec<Light> = scene_def
+        .lights
+        .iter()
+        .map(|l| {
+            let light = Light::new(l.position, vector3_to_color(l.color), l.intensity)
+                .with_shadows(l.shadows)
+                .with_groups(l.groups);
+            motion.light_at(&light, 0.0)
+        })
+        .collect();
+
+    // Lo precalculado con la luz (fotones, caché de irradiancia, lightmaps)
+    // queda fijo en el modelo y no sigue a la luz si el diorama gira o la
+    // luz orbita
+    let animated = motion.is_animated() || world_light.is_animated();
+
+    // --- Pre-pase de fotones para cáusticas (opcional) ---
+    if options.caustics > 0 && animated {
+        eprintln!("Cáusticas desactivadas: no sirven con --model-spin ni --light-orbit");
+    }
+    let photon_map = if options.caustics > 0 && !animated {
+        let settings = photon_map::PhotonSettings {
+            count: options.caustics,
+            radius: 0.25,
+            target_center: (min + max) * 0.5,
+            target_radius: (max - min).length() * 0.5 + 1.0,
+            seed: options.seed,
+        };
+        let scene = Scene {
+            bias: scene_def.bias.unwrap_or(scene::ORIGIN_BIAS),
+            ..Scene::new(&objects, &light2, &texture_manager)
+        };
+        let map = photon_map::PhotonMap::build(&scene, &settings);
+        println!("Mapa de cáusticas: {} fotones guardados de {}", map.photon_count(), options.caustics);
+        Some(map)
+    } else {
+        None
+    };
+
+    // --- Bloques emisivos como luces de área (solo con GI) ---
+    let emissive_blocks = if options.gi > 0 {
+        emissive::EmissiveBlock::from_cubes(&cubes, &texture_manager)
+    } else {
+        Vec::new()
+    };
+
+    // --- Cajas envolventes para descartar rayos al cielo ---
+    let scene_bounds = scene_bounds::SceneBounds::new(&objects);
+    let occluder_cache = occluder_cache::OccluderCache::new(options.width, options.height);
+
+    // --- Caché de irradiancia para luz indirecta (opcional) ---
+    if options.gi > 0 && animated {
+        eprintln!("Luz indirecta desactivada: la caché no sirve con --model-spin ni --light-orbit");
+    }
+    let irradiance_cache = (options.gi > 0 && !animated).then(|| {
+        irradiance_cache::IrradianceCache::new(irradiance_cache::IrradianceSettings {
+            samples: options.gi,
+            outlier_factor: options.reject_outliers,
+            ..Default::default()
+        })
+    });
+
+    // --- Luz difusa del cielo (opcional) ---
+    let sky_irradiance = (options.sky_light > 0.0)
+        .then(|| sky_light::SkyIrradiance::from_sky(|d| sky.background(d, 0.0), options.sky_light));
+
+    // --- Matcap de depuración (opcional) ---
+    let matcap = options.matcap.as_deref().map(matcap::Matcap::from_spec).transpose()?;
+
+    // --- Bandas de luz: las de la escena, o parejas con `--toon` ---
+    let cel = scene_def.cel.clone().or_else(|| (options.toon > 0).then(|| toon::Cel::even(options.toon)));
+
+    // --- Post-proceso de la imagen (viñeta, aberración, grano, LUT) ---
+    let post_stack = post::PostStack { effects: scene_def.post.clone() };
+
+    let scene = Scene {
+        bounds: Some(&scene_bounds),
+        occluders: Some(&occluder_cache),
+        photons: photon_map.as_ref(),
+        irradiance: irradiance_cache.as_ref(),
+        sky: &sky,
+        fog: scene_def.fog,
+        sky_light: sky_irradiance.as_ref(),
+        ambient: scene_def.ambient,
+        shadow_samples: options.shadow_samples,
+        pixel_samples: options.spp,
+        emitters: &emitters,
+        extra_lights: &extra_lights,
+        weather: weather.as_ref(),
+        snow: snow_material.as_ref(),
+        matcap: matcap.as_ref(),
+        cel: cel.as_ref(),
+        toon: (options.toon > 0).then(toon::Toon::default),
+        bias: scene_def.bias.unwrap_or(scene::ORIGIN_BIAS),
+        seed: options.seed,
+        max_depth: options.max_depth.or(scene_def.max_depth).unwrap_or(scene::MAX_DEPTH),
+        post: (!post_stack.is_empty()).then_some(&post_stack),
+        emissive: &emissive_blocks,
+        light_cutoff: options.light_cutoff,
+        firefly_clamp: if options.firefly_clamp > 0.0 { options.firefly_clamp } else { f32::INFINITY },
+        dither: options.dither,
+        ..Scene::new(&objects, &light2, &texture_manager)
+    };
+
+    // --- Lightmaps horneados para la vista estática (opcional) ---
+    if options.bake > 0 && animated {
+        eprintln!("Lightmaps desactivados: no sirven con --model-spin ni --light-orbit");
+    }
+    let lightmaps = (options.bake > 0 && !animated).then(|| {
+        let maps = lightmap::Lightmaps::bake(&world.exposed_faces(), &scene, options.bake);
+        println!("Lightmaps: {} caras horneadas a {}x{}", maps.face_count(), options.bake, options.bake);
+        maps
+    });
+    let scene = Scene { lightmaps: lightmaps.as_ref(), ..scene };
+
+    let stats = stats::SceneStats { voxels: Some(world.len()), ..scene.stats() };
+    println!("{}", stats);
+
+    show(Diorama {
+        camera,
+        motion: &motion,
+        light: &world_light,
+        scene,
+        world: &world,
+        materials: &materials,
+        textures: &texture_manager,
+    })
+}
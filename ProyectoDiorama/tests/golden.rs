@@ -0,0 +1,46 @@
+//! Regresión de imágenes: los mismos casos que `golden/run.sh`, renderizados
+//! con el binario de este build y comparados con las referencias de
+//! `golden/`. Una referencia que falta es un fallo.
+
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Nombre de la referencia y opciones extra de cada caso
+const CASES: &[(&str, &[&str])] = &[
+    ("base", &[]),
+    ("decorado", &["--scene", "scenes/decorado.scene"]),
+    ("espiral", &["--scene", "scenes/espiral.scene"]),
+    ("sombras_suaves", &["--shadow-samples", "8", "--light-radius", "2"]),
+    ("gi", &["--gi", "16", "--sky-light", "0.5"]),
+];
+
+#[test]
+fn renders_match_references() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let out = std::env::temp_dir().join(format!("diorama-golden-{}", std::process::id()));
+    std::fs::create_dir_all(&out).unwrap();
+
+    let mut failures = Vec::new();
+    for (name, extra) in CASES {
+        let reference = root.join("golden").join(format!("{}.png", name));
+        let rendered = out.join(format!("{}.png", name));
+        let result = Command::new(env!("CARGO_BIN_EXE_ProyectoDiorama"))
+            .current_dir(&root)
+            .args(["--width", "160", "--height", "120", "--seed", "7"])
+            .arg("--output")
+            .arg(&rendered)
+            .arg("--golden")
+            .arg(&reference)
+            .args(*extra)
+            .output()
+            .unwrap();
+        if !result.status.success() {
+            // La última línea es el error; lo anterior es la barra de progreso
+            let stderr = String::from_utf8_lossy(&result.stderr);
+            failures.push(format!("{}: {}", name, stderr.trim().lines().last().unwrap_or("")));
+        }
+    }
+
+    std::fs::remove_dir_all(&out).ok();
+    assert!(failures.is_empty(), "imágenes distintas de la referencia:\n{}", failures.join("\n"));
+}